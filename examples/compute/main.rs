@@ -0,0 +1,56 @@
+use wgpu_bootstrap::{
+    util::{bind_group::BindGroupBuilder, pipeline::ComputePipelineBuilder},
+    wgpu::{self, util::DeviceExt},
+    ContextError, HeadlessContext,
+};
+
+const COUNT: u32 = 1024;
+const WORKGROUP_SIZE: u32 = 64;
+
+// Sums 0..COUNT on the GPU and checks the result on the CPU
+fn main() -> Result<(), ContextError> {
+    env_logger::init();
+
+    let headless = HeadlessContext::new(1, 1, wgpu::TextureFormat::Rgba8Unorm)?;
+    let context = headless.context();
+
+    let values: Vec<u32> = (0..COUNT).collect();
+    let input_buffer = context
+        .device()
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Input Buffer"),
+            contents: bytemuck::cast_slice(&values),
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+    let sum_buffer = context
+        .device()
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Sum Buffer"),
+            contents: bytemuck::cast_slice(&[0u32]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        });
+
+    let (bind_group_layout, bind_group) = BindGroupBuilder::new()
+        .label("Compute Bind Group")
+        .storage(0, &input_buffer, true, wgpu::ShaderStages::COMPUTE)
+        .storage(1, &sum_buffer, false, wgpu::ShaderStages::COMPUTE)
+        .build(&context);
+
+    let shader = context
+        .device()
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+        });
+
+    let pipeline = ComputePipelineBuilder::new(&shader)
+        .label("Compute Pipeline")
+        .bind_group_layout(&bind_group_layout)
+        .build(&context);
+
+    context.dispatch(&pipeline, &[&bind_group], (COUNT / WORKGROUP_SIZE, 1, 1));
+
+    let sum = context.read_buffer::<u32>(&sum_buffer, ..)?[0];
+    println!("GPU sum: {sum}, expected: {}", values.iter().sum::<u32>());
+    Ok(())
+}
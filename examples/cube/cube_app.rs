@@ -1,8 +1,11 @@
 use wgpu_bootstrap::{
     cgmath, egui,
-    util::orbit_camera::{CameraUniform, OrbitCamera},
-    wgpu::{self, util::DeviceExt},
-    App, Context,
+    util::{
+        debug_draw::DebugDraw,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+    },
+    wgpu, App, Context,
 };
 
 #[repr(C)]
@@ -153,28 +156,16 @@ pub struct CubeApp {
     render_pipeline: wgpu::RenderPipeline,
     num_indices: u32,
     camera: OrbitCamera,
+    debug_draw: DebugDraw,
 }
 
 impl CubeApp {
     pub fn new(context: &Context) -> Self {
-        let index_buffer = context
-            .device()
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(INDEXES),
-                usage: wgpu::BufferUsages::INDEX,
-            });
+        let index_buffer = context.create_index_buffer(INDEXES, "Index Buffer");
 
         let num_indices = INDEXES.len() as u32;
 
-        let vertex_buffer =
-            context
-                .device()
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Vertex Buffer"),
-                    contents: bytemuck::cast_slice(VERTICES),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
+        let vertex_buffer = context.create_vertex_buffer(VERTICES, "Vertex Buffer");
 
         let camera_bind_group_layout = context
             .device()
@@ -187,64 +178,11 @@ impl CubeApp {
                 source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
             });
 
-        let pipeline_layout =
-            context
-                .device()
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&camera_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-
-        let render_pipeline =
-            context
-                .device()
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Render Pipeline"),
-                    layout: Some(&pipeline_layout),
-                    vertex: wgpu::VertexState {
-                        module: &shader,
-                        entry_point: "vs_main",
-                        buffers: &[Vertex::desc()],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: "fs_main",
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: context.format(),
-                            blend: Some(wgpu::BlendState::REPLACE),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: Some(wgpu::Face::Back),
-                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        // Requires Features::DEPTH_CLIP_CONTROL
-                        unclipped_depth: false,
-                        // Requires Features::CONSERVATIVE_RASTERIZATION
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: context.depth_stencil_format(),
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Less,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: 1,
-                        mask: !0,
-                        alpha_to_coverage_enabled: false,
-                    },
-                    multiview: None,
-                    cache: None,
-                });
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(Vertex::desc())
+            .build(context);
 
         let mut camera = OrbitCamera::new(
             context,
@@ -264,6 +202,7 @@ impl CubeApp {
             render_pipeline,
             num_indices,
             camera,
+            debug_draw: DebugDraw::new(context),
         }
     }
 }
@@ -273,11 +212,21 @@ impl App for CubeApp {
         self.camera.input(input, context);
     }
 
+    fn update(&mut self, _delta_time: f32, context: &Context) {
+        // Axes through the cube, drawn on top so they stay visible
+        self.debug_draw
+            .set_depth_test(false)
+            .axes(cgmath::Matrix4::from_scale(0.8))
+            .wire_cube(cgmath::point3(0.0, 0.0, 0.0), 1.1, [0.2, 0.2, 0.2])
+            .flush(context);
+    }
+
     fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
         render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
         render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        self.debug_draw.draw(render_pass, self.camera.bind_group());
     }
 }
@@ -3,9 +3,9 @@ mod cube_app;
 use std::sync::Arc;
 
 use crate::cube_app::CubeApp;
-use wgpu_bootstrap::{egui, Runner};
+use wgpu_bootstrap::{egui, ContextError, Runner};
 
-fn main() {
+fn main() -> Result<(), ContextError> {
     let mut runner = Runner::new(
         "Cube App",
         800,
@@ -15,5 +15,5 @@ fn main() {
         0,
         Box::new(|context| Arc::new(CubeApp::new(context))),
     );
-    runner.run();
+    runner.run()
 }
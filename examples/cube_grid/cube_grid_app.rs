@@ -0,0 +1,257 @@
+use wgpu_bootstrap::{
+    cgmath, egui,
+    util::{
+        instance_buffer::{InstanceBuffer, TRANSFORM_ATTRIBUTES},
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+        text::TextRenderer,
+    },
+    wgpu, App, Context,
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+impl Vertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+            ],
+        }
+    }
+}
+
+const VERTICES: &[Vertex] = &[
+    Vertex {
+        position: [0.5, 0.5, 0.5],
+        color: [1.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [-0.5, 0.5, 0.5],
+        color: [1.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [-0.5, -0.5, 0.5],
+        color: [1.0, 0.0, 0.0],
+    },
+    Vertex {
+        position: [0.5, -0.5, 0.5],
+        color: [1.0, 0.0, 0.0],
+    },
+    ///////////////////////////////////
+    Vertex {
+        position: [0.5, 0.5, 0.5],
+        color: [0.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [0.5, -0.5, 0.5],
+        color: [0.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [0.5, -0.5, -0.5],
+        color: [0.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [0.5, 0.5, -0.5],
+        color: [0.0, 1.0, 0.0],
+    },
+    //////////////////////////////
+    Vertex {
+        position: [0.5, 0.5, -0.5],
+        color: [0.0, 0.0, 1.0],
+    },
+    Vertex {
+        position: [0.5, -0.5, -0.5],
+        color: [0.0, 0.0, 1.0],
+    },
+    Vertex {
+        position: [-0.5, -0.5, -0.5],
+        color: [0.0, 0.0, 1.0],
+    },
+    Vertex {
+        position: [-0.5, 0.5, -0.5],
+        color: [0.0, 0.0, 1.0],
+    },
+    //////////////////////////////
+    Vertex {
+        position: [-0.5, 0.5, 0.5],
+        color: [1.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [-0.5, 0.5, -0.5],
+        color: [1.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [-0.5, -0.5, -0.5],
+        color: [1.0, 1.0, 0.0],
+    },
+    Vertex {
+        position: [-0.5, -0.5, 0.5],
+        color: [1.0, 1.0, 0.0],
+    },
+    //////////////////////////////
+    Vertex {
+        position: [0.5, 0.5, 0.5],
+        color: [0.0, 1.0, 1.0],
+    },
+    Vertex {
+        position: [0.5, 0.5, -0.5],
+        color: [0.0, 1.0, 1.0],
+    },
+    Vertex {
+        position: [-0.5, 0.5, -0.5],
+        color: [0.0, 1.0, 1.0],
+    },
+    Vertex {
+        position: [-0.5, 0.5, 0.5],
+        color: [0.0, 1.0, 1.0],
+    },
+    //////////////////////////////
+    Vertex {
+        position: [0.5, -0.5, 0.5],
+        color: [1.0, 0.0, 1.0],
+    },
+    Vertex {
+        position: [-0.5, -0.5, 0.5],
+        color: [1.0, 0.0, 1.0],
+    },
+    Vertex {
+        position: [-0.5, -0.5, -0.5],
+        color: [1.0, 0.0, 1.0],
+    },
+    Vertex {
+        position: [0.5, -0.5, -0.5],
+        color: [1.0, 0.0, 1.0],
+    },
+];
+
+#[rustfmt::skip]
+const INDEXES: &[u32] = &[
+     0,  1,  2,  0,  2,  3,
+     4,  5,  6,  4,  6,  7,
+     8,  9, 10,  8, 10, 11,
+    12, 13, 14, 12, 14, 15,
+    16, 17, 18, 16, 18, 19,
+    20, 21, 22, 20, 22, 23,
+];
+
+const GRID_SIZE: i32 = 20;
+const SPACING: f32 = 1.5;
+
+pub struct CubeGridApp {
+    mesh: GpuMesh,
+    instance_buffer: InstanceBuffer<[[f32; 4]; 4]>,
+    render_pipeline: wgpu::RenderPipeline,
+    camera: OrbitCamera,
+    text: TextRenderer,
+}
+
+impl CubeGridApp {
+    pub fn new(context: &Context) -> Self {
+        let mesh = GpuMesh::new(context, VERTICES, INDEXES);
+
+        let offset = (GRID_SIZE - 1) as f32 * SPACING / 2.0;
+        let transforms: Vec<[[f32; 4]; 4]> = (0..GRID_SIZE)
+            .flat_map(|x| (0..GRID_SIZE).map(move |z| (x, z)))
+            .map(|(x, z)| {
+                let translation = cgmath::vec3(
+                    x as f32 * SPACING - offset,
+                    0.0,
+                    z as f32 * SPACING - offset,
+                );
+                let rotation = cgmath::Matrix4::from_angle_y(cgmath::Deg((x * z) as f32 * 7.0));
+                (cgmath::Matrix4::from_translation(translation) * rotation).into()
+            })
+            .collect();
+        let instance_buffer = InstanceBuffer::new(context, &transforms);
+
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            });
+
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(Vertex::desc())
+            .vertex_layout(instance_buffer.vertex_buffer_layout(&TRANSFORM_ATTRIBUTES))
+            .build(context);
+
+        let mut camera = OrbitCamera::new(
+            context,
+            45.0,
+            context.size().x / context.size().y,
+            0.1,
+            100.0,
+        );
+        camera
+            .set_target(cgmath::point3(0.0, 0.0, 0.0))
+            .set_polar(cgmath::point3(40.0, 0.0, 0.6))
+            .update(context);
+
+        Self {
+            mesh,
+            instance_buffer,
+            render_pipeline,
+            camera,
+            text: TextRenderer::new(),
+        }
+    }
+}
+
+impl App for CubeGridApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        let height = context.size().y;
+        self.text
+            .draw_fps(context, egui::Color32::BLACK)
+            .draw_text(
+                "Drag with the left button to orbit",
+                8.0,
+                height - 28.0,
+                16.0,
+                egui::Color32::DARK_GRAY,
+            )
+            .flush(egui_ctx);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(1, self.instance_buffer.buffer().slice(..));
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        self.mesh
+            .draw_instanced(render_pass, 0..self.instance_buffer.count());
+    }
+}
@@ -0,0 +1,19 @@
+mod cube_grid_app;
+
+use std::sync::Arc;
+
+use crate::cube_grid_app::CubeGridApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Cube Grid App",
+        800,
+        600,
+        egui::Color32::from_rgb(245, 245, 245),
+        32,
+        0,
+        Box::new(|context| Arc::new(CubeGridApp::new(context))),
+    );
+    runner.run()
+}
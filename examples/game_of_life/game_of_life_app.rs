@@ -0,0 +1,170 @@
+use wgpu_bootstrap::{
+    egui, include_shader,
+    util::{
+        bind_group::BindGroupBuilder,
+        pipeline::{ComputePipelineBuilder, RenderPipelineBuilder},
+        storage_texture::StorageTexture,
+    },
+    wgpu, App, Context, ContextError,
+};
+
+const WIDTH: u32 = 200;
+const HEIGHT: u32 = 150;
+// Matches @workgroup_size in life.wgsl
+const WORKGROUP_SIZE: u32 = 8;
+
+// Conway's game of life on the GPU: each step reads the cells from one storage texture and
+// writes the next generation into the other, then the latest one is drawn over the window
+pub struct GameOfLifeApp {
+    cells: [StorageTexture; 2],
+    // Index of the texture holding the latest generation
+    current: usize,
+    // step_groups[i] reads cells[i] and writes the other one
+    step_groups: [wgpu::BindGroup; 2],
+    draw_groups: [wgpu::BindGroup; 2],
+    step_pipeline: wgpu::ComputePipeline,
+    draw_pipeline: wgpu::RenderPipeline,
+    running: bool,
+    steps_per_second: f32,
+    elapsed: f32,
+    pending_steps: u32,
+    seed: u32,
+}
+
+impl GameOfLifeApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let format = wgpu::TextureFormat::Rgba8Unorm;
+        let cells = [
+            StorageTexture::with_label(context, WIDTH, HEIGHT, format, "Cells A")?,
+            StorageTexture::with_label(context, WIDTH, HEIGHT, format, "Cells B")?,
+        ];
+
+        let step_bind_group = |from: &StorageTexture, to: &StorageTexture| {
+            BindGroupBuilder::new()
+                .label("Step Bind Group")
+                .texture(0, from.view(), wgpu::ShaderStages::COMPUTE)
+                .storage_texture(
+                    1,
+                    to.view(),
+                    format,
+                    wgpu::StorageTextureAccess::WriteOnly,
+                    wgpu::ShaderStages::COMPUTE,
+                )
+                .build(context)
+        };
+        let (step_layout, step_a) = step_bind_group(&cells[0], &cells[1]);
+        let (_, step_b) = step_bind_group(&cells[1], &cells[0]);
+
+        let draw_bind_group = |cells: &StorageTexture| {
+            BindGroupBuilder::new()
+                .label("Draw Bind Group")
+                .texture(0, cells.view(), wgpu::ShaderStages::FRAGMENT)
+                .build(context)
+        };
+        let (draw_layout, draw_a) = draw_bind_group(&cells[0]);
+        let (_, draw_b) = draw_bind_group(&cells[1]);
+
+        let life_shader = include_shader!(context, "life.wgsl")?;
+        let step_pipeline = ComputePipelineBuilder::new(&life_shader)
+            .label("Step Pipeline")
+            .bind_group_layout(&step_layout)
+            .build(context);
+        let shader = include_shader!(context, "shader.wgsl")?;
+        let draw_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Draw Pipeline")
+            .bind_group_layout(&draw_layout)
+            .cull_mode(None)
+            .build(context);
+
+        let mut app = Self {
+            cells,
+            current: 0,
+            step_groups: [step_a, step_b],
+            draw_groups: [draw_a, draw_b],
+            step_pipeline,
+            draw_pipeline,
+            running: true,
+            steps_per_second: 15.0,
+            elapsed: 0.0,
+            pending_steps: 0,
+            seed: 1,
+        };
+        app.randomize(context);
+        Ok(app)
+    }
+
+    // About a third of the cells alive, from a xorshift generator
+    fn randomize(&mut self, context: &Context) {
+        let mut pixels = Vec::with_capacity((WIDTH * HEIGHT * 4) as usize);
+        for _ in 0..WIDTH * HEIGHT {
+            self.seed ^= self.seed << 13;
+            self.seed ^= self.seed >> 17;
+            self.seed ^= self.seed << 5;
+            let alive = if self.seed % 3 == 0 { 255 } else { 0 };
+            pixels.extend_from_slice(&[alive, 0, 0, 255]);
+        }
+        self.current = 0;
+        context.queue().write_texture(
+            self.cells[0].texture().as_image_copy(),
+            &pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(WIDTH * 4),
+                rows_per_image: Some(HEIGHT),
+            },
+            self.cells[0].texture().size(),
+        );
+    }
+}
+
+impl App for GameOfLifeApp {
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::Window::new("Game of Life").show(egui_ctx, |ui| {
+            ui.checkbox(&mut self.running, "running");
+            ui.add(egui::Slider::new(&mut self.steps_per_second, 1.0..=60.0).text("steps/s"));
+            if ui.button("Step").clicked() {
+                self.pending_steps += 1;
+            }
+            if ui.button("Randomize").clicked() {
+                self.randomize(context);
+            }
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, _context: &Context) {
+        if !self.running {
+            return;
+        }
+        self.elapsed += delta_time;
+        let step = 1.0 / self.steps_per_second;
+        while self.elapsed >= step {
+            self.elapsed -= step;
+            self.pending_steps += 1;
+        }
+        // Don't catch up after a stall
+        self.pending_steps = self.pending_steps.min(4);
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, _context: &Context) {
+        for _ in 0..std::mem::take(&mut self.pending_steps) {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Step Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&self.step_pipeline);
+            compute_pass.set_bind_group(0, &self.step_groups[self.current], &[]);
+            compute_pass.dispatch_workgroups(
+                WIDTH.div_ceil(WORKGROUP_SIZE),
+                HEIGHT.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+            self.current = 1 - self.current;
+        }
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.draw_pipeline);
+        render_pass.set_bind_group(0, &self.draw_groups[self.current], &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,30 @@
+// A cell is alive when its red channel is 1
+@group(0) @binding(0)
+var current: texture_2d<f32>;
+@group(0) @binding(1)
+var next: texture_storage_2d<rgba8unorm, write>;
+
+fn alive(cell: vec2<i32>, size: vec2<i32>) -> u32 {
+    // The grid wraps around at the edges
+    let wrapped = (cell + size) % size;
+    return u32(textureLoad(current, wrapped, 0).r > 0.5);
+}
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<i32>(textureDimensions(current));
+    let cell = vec2<i32>(id.xy);
+    if cell.x >= size.x || cell.y >= size.y {
+        return;
+    }
+    var neighbors = 0u;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            if x != 0 || y != 0 {
+                neighbors += alive(cell + vec2<i32>(x, y), size);
+            }
+        }
+    }
+    let lives = neighbors == 3u || (neighbors == 2u && alive(cell, size) == 1u);
+    textureStore(next, cell, vec4<f32>(f32(lives), 0.0, 0.0, 1.0));
+}
@@ -0,0 +1,19 @@
+mod game_of_life_app;
+
+use std::sync::Arc;
+
+use crate::game_of_life_app::GameOfLifeApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Game of Life App",
+        800,
+        600,
+        egui::Color32::from_rgb(10, 10, 15),
+        32,
+        0,
+        Box::new(|context| Arc::new(GameOfLifeApp::new(context).unwrap())),
+    );
+    runner.run()
+}
@@ -0,0 +1,25 @@
+@group(0) @binding(0)
+var cells: texture_2d<f32>;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+// One triangle covering the whole window
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.tex_coords = uv;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(cells));
+    let cell = vec2<i32>(min(in.tex_coords * size, size - 1.0));
+    let alive = textureLoad(cells, cell, 0).r;
+    return vec4<f32>(mix(vec3<f32>(0.04, 0.04, 0.06), vec3<f32>(0.3, 0.9, 0.5), alive), 1.0);
+}
@@ -0,0 +1,158 @@
+use wgpu_bootstrap::{
+    egui,
+    gamepad::{Axis, Button, GamepadEvent},
+    util::{
+        bind_group::BindGroupBuilder, pipeline::RenderPipelineBuilder,
+        uniform_buffer::UniformBuffer,
+    },
+    wgpu, App, Context, ContextError, Vertex,
+};
+
+const SPEED: f32 = 1.0;
+const BUTTONS: [Button; 4] = [Button::South, Button::East, Button::West, Button::North];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct TriangleVertex {
+    position: [f32; 2],
+    color: [f32; 3],
+}
+
+const VERTICES: &[TriangleVertex] = &[
+    TriangleVertex {
+        position: [0.0, 0.2],
+        color: [1.0, 0.0, 0.0],
+    },
+    TriangleVertex {
+        position: [-0.2, -0.2],
+        color: [0.0, 1.0, 0.0],
+    },
+    TriangleVertex {
+        position: [0.2, -0.2],
+        color: [0.0, 0.0, 1.0],
+    },
+];
+
+// Matches Params in shader.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    offset: [f32; 2],
+    scale: f32,
+    highlight: f32,
+}
+
+// The left stick moves the triangle, the right trigger grows it, South brightens it and East
+// puts it back in the center. Runs without a controller, the triangle just stays still.
+pub struct GamepadApp {
+    vertex_buffer: wgpu::Buffer,
+    params: Params,
+    params_buffer: UniformBuffer<Params>,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    log: Vec<String>,
+}
+
+impl GamepadApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let vertex_buffer = context.create_vertex_buffer(VERTICES, "Vertex Buffer");
+
+        let params = Params {
+            offset: [0.0, 0.0],
+            scale: 1.0,
+            highlight: 0.0,
+        };
+        let params_buffer = UniformBuffer::new(context, params)?;
+        let (bind_group_layout, bind_group) = BindGroupBuilder::new()
+            .label("Params Bind Group")
+            .uniform(0, params_buffer.buffer(), wgpu::ShaderStages::VERTEX)
+            .build(context);
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            });
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&bind_group_layout)
+            .vertex_layout(TriangleVertex::desc())
+            .cull_mode(None)
+            .build(context);
+
+        Ok(Self {
+            vertex_buffer,
+            params,
+            params_buffer,
+            bind_group,
+            render_pipeline,
+            log: Vec::new(),
+        })
+    }
+}
+
+impl App for GamepadApp {
+    fn gamepad_event(&mut self, event: &GamepadEvent, context: &Context) {
+        let line = match event {
+            GamepadEvent::Connected(id) => format!(
+                "connected: {}",
+                context.gamepads().name(*id).unwrap_or_default()
+            ),
+            GamepadEvent::Disconnected(id) => format!("disconnected: {id}"),
+        };
+        self.log.push(line);
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        let gamepads = context.gamepads();
+        if gamepads.is_button_down(Button::East) {
+            self.params.offset = [0.0, 0.0];
+        }
+        let step = SPEED * delta_time;
+        self.params.offset[0] =
+            (self.params.offset[0] + gamepads.axis_value(Axis::LeftStickX) * step).clamp(-1.0, 1.0);
+        self.params.offset[1] =
+            (self.params.offset[1] + gamepads.axis_value(Axis::LeftStickY) * step).clamp(-1.0, 1.0);
+        self.params.scale = 1.0 + gamepads.button_value(Button::RightTrigger2);
+        self.params.highlight = if gamepads.is_button_down(Button::South) {
+            0.6
+        } else {
+            0.0
+        };
+        self.params_buffer.update(context.queue(), self.params);
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        let gamepads = context.gamepads();
+        egui::Window::new("Gamepad").show(egui_ctx, |ui| {
+            match gamepads.active() {
+                Some(id) => ui.label(format!("active: {}", gamepads.name(id).unwrap_or_default())),
+                None => ui.label("no controller connected"),
+            };
+            ui.label(format!(
+                "left stick: {:.2} {:.2} (deadzone {:.2})",
+                gamepads.axis_value(Axis::LeftStickX),
+                gamepads.axis_value(Axis::LeftStickY),
+                gamepads.deadzone()
+            ));
+            let down: Vec<String> = BUTTONS
+                .iter()
+                .filter(|button| gamepads.is_button_down(**button))
+                .map(|button| format!("{button:?}"))
+                .collect();
+            ui.label(format!("buttons: {}", down.join(" ")));
+            ui.separator();
+            for line in self.log.iter().rev().take(5) {
+                ui.label(line);
+            }
+        });
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..VERTICES.len() as u32, 0..1);
+    }
+}
@@ -0,0 +1,20 @@
+mod gamepad_app;
+
+use std::sync::Arc;
+
+use crate::gamepad_app::GamepadApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Gamepad App",
+        800,
+        600,
+        egui::Color32::from_rgb(245, 245, 245),
+        0,
+        0,
+        Box::new(|context| Arc::new(GamepadApp::new(context).unwrap())),
+    );
+    runner.set_gamepad_deadzone(0.15);
+    runner.run()
+}
@@ -0,0 +1,33 @@
+struct Params {
+    offset: vec2<f32>,
+    scale: f32,
+    highlight: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.color = mix(model.color, vec3<f32>(1.0, 1.0, 1.0), params.highlight);
+    out.clip_position = vec4<f32>(model.position * params.scale + params.offset, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
@@ -0,0 +1,254 @@
+use wgpu_bootstrap::{
+    cgmath, egui,
+    util::{
+        bind_group::BindGroupBuilder,
+        geometry::icosphere,
+        instance_buffer::InstanceBuffer,
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+        render_target::RenderTarget,
+        uniform_buffer::UniformBuffer,
+    },
+    wgpu, App, Context, ContextError, Vertex,
+};
+
+// Albedo, normal and world position, written at @location(0), (1) and (2) by geometry.wgsl
+const GBUFFER_FORMATS: [wgpu::TextureFormat; 3] = [
+    wgpu::TextureFormat::Rgba8Unorm,
+    wgpu::TextureFormat::Rgba16Float,
+    wgpu::TextureFormat::Rgba16Float,
+];
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+const GRID_SIZE: i32 = 7;
+const LIGHT_COUNT: usize = 4;
+const MODES: [&str; 4] = ["Lit", "Albedo", "Normal", "Position"];
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 2)]
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+// Matches Lighting in lighting.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Lighting {
+    positions: [[f32; 4]; LIGHT_COUNT],
+    colors: [[f32; 4]; LIGHT_COUNT],
+    mode: u32,
+    _padding: [u32; 3],
+}
+
+// Deferred shading: the spheres are drawn once into three color attachments, then a full
+// screen pass lights every pixel from them with a few moving point lights
+pub struct GBufferApp {
+    mesh: GpuMesh,
+    instances: InstanceBuffer<Instance>,
+    gbuffer: RenderTarget,
+    geometry_pipeline: wgpu::RenderPipeline,
+    lighting: Lighting,
+    lighting_buffer: UniformBuffer<Lighting>,
+    lighting_layout: wgpu::BindGroupLayout,
+    lighting_bind_group: wgpu::BindGroup,
+    lighting_pipeline: wgpu::RenderPipeline,
+    camera: OrbitCamera,
+}
+
+impl GBufferApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let (positions, indices) = icosphere(3);
+        let vertices: Vec<MeshVertex> = positions
+            .iter()
+            .map(|position| MeshVertex {
+                position: (*position * 0.4).into(),
+                normal: (*position).into(),
+            })
+            .collect();
+        let mesh = GpuMesh::new(context, &vertices, &indices);
+
+        let half = GRID_SIZE / 2;
+        let instances: Vec<Instance> = (-half..=half)
+            .flat_map(|x| (-half..=half).map(move |z| (x, z)))
+            .map(|(x, z)| Instance {
+                position: [x as f32, 0.0, z as f32],
+                color: [
+                    0.5 + 0.5 * (x as f32 / half as f32),
+                    0.8,
+                    0.5 + 0.5 * (z as f32 / half as f32),
+                ],
+            })
+            .collect();
+        let instances = InstanceBuffer::new(context, &instances);
+
+        let gbuffer = RenderTarget::with_formats(
+            context,
+            context.size().x as u32,
+            context.size().y as u32,
+            &GBUFFER_FORMATS,
+            Some(DEPTH_FORMAT),
+        );
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(10.0, 0.0, 0.6))
+            .update(context);
+
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let geometry_shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Geometry Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("geometry.wgsl").into()),
+            });
+        let geometry_pipeline = RenderPipelineBuilder::new(&geometry_shader)
+            .label("Geometry Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .formats(&GBUFFER_FORMATS)
+            .depth_format(Some(DEPTH_FORMAT))
+            .sample_count(1)
+            .try_build_for(context, &gbuffer)?;
+
+        let lighting = Lighting {
+            positions: [[0.0; 4]; LIGHT_COUNT],
+            colors: [
+                [1.0, 0.3, 0.3, 1.0],
+                [0.3, 1.0, 0.3, 1.0],
+                [0.3, 0.3, 1.0, 1.0],
+                [1.0, 1.0, 0.6, 1.0],
+            ],
+            mode: 0,
+            _padding: [0; 3],
+        };
+        let lighting_buffer = UniformBuffer::new(context, lighting)?;
+        let (lighting_layout, lighting_bind_group) = BindGroupBuilder::new()
+            .label("Lighting Bind Group")
+            .texture(0, gbuffer.color_view(0), wgpu::ShaderStages::FRAGMENT)
+            .texture(1, gbuffer.color_view(1), wgpu::ShaderStages::FRAGMENT)
+            .texture(2, gbuffer.color_view(2), wgpu::ShaderStages::FRAGMENT)
+            .uniform(3, lighting_buffer.buffer(), wgpu::ShaderStages::FRAGMENT)
+            .build(context);
+
+        let lighting_shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Lighting Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("lighting.wgsl").into()),
+            });
+        let lighting_pipeline = RenderPipelineBuilder::new(&lighting_shader)
+            .label("Lighting Pipeline")
+            .bind_group_layout(&lighting_layout)
+            .cull_mode(None)
+            .depth_write(false)
+            .depth_compare(wgpu::CompareFunction::Always)
+            .build(context);
+
+        Ok(Self {
+            mesh,
+            instances,
+            gbuffer,
+            geometry_pipeline,
+            lighting,
+            lighting_buffer,
+            lighting_layout,
+            lighting_bind_group,
+            lighting_pipeline,
+            camera,
+        })
+    }
+}
+
+impl App for GBufferApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+        self.gbuffer.resize(context, new_width, new_height);
+        self.lighting_bind_group = lighting_bind_group(
+            context,
+            &self.lighting_layout,
+            &self.gbuffer,
+            &self.lighting_buffer,
+        );
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("G-Buffer").show(egui_ctx, |ui| {
+            for (mode, name) in MODES.iter().enumerate() {
+                ui.radio_value(&mut self.lighting.mode, mode as u32, *name);
+            }
+        });
+    }
+
+    fn update(&mut self, _delta_time: f32, context: &Context) {
+        let time = context.time().elapsed_seconds();
+        for (i, position) in self.lighting.positions.iter_mut().enumerate() {
+            let angle = time * 0.5 + i as f32 * std::f32::consts::TAU / LIGHT_COUNT as f32;
+            let radius = 2.0 + i as f32;
+            *position = [radius * angle.cos(), 1.0, radius * angle.sin(), 1.0];
+        }
+        self.lighting_buffer.update(context.queue(), self.lighting);
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, _context: &Context) {
+        let mut render_pass = self
+            .gbuffer
+            .begin_pass(encoder, Some(wgpu::Color::TRANSPARENT));
+        render_pass.set_pipeline(&self.geometry_pipeline);
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        render_pass.set_vertex_buffer(1, self.instances.buffer().slice(..));
+        self.mesh
+            .draw_instanced(&mut render_pass, 0..self.instances.count());
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.lighting_pipeline);
+        render_pass.set_bind_group(0, &self.lighting_bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+// Same entries as the bind group built in new, on the views of the resized G-buffer
+fn lighting_bind_group(
+    context: &Context,
+    layout: &wgpu::BindGroupLayout,
+    gbuffer: &RenderTarget,
+    lighting_buffer: &UniformBuffer<Lighting>,
+) -> wgpu::BindGroup {
+    let mut entries: Vec<wgpu::BindGroupEntry> = (0..gbuffer.color_count())
+        .map(|i| wgpu::BindGroupEntry {
+            binding: i as u32,
+            resource: wgpu::BindingResource::TextureView(gbuffer.color_view(i)),
+        })
+        .collect();
+    entries.push(wgpu::BindGroupEntry {
+        binding: 3,
+        resource: lighting_buffer.binding_resource(),
+    });
+    context
+        .device()
+        .create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Lighting Bind Group"),
+            layout,
+            entries: &entries,
+        })
+}
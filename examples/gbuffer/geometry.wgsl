@@ -0,0 +1,49 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) world_position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(2) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.world_position = model.position + instance.position;
+    out.normal = model.normal;
+    out.color = instance.color;
+    out.clip_position = camera.proj * camera.view * vec4<f32>(out.world_position, 1.0);
+    return out;
+}
+
+// One output per G-buffer attachment, in the order of the render target formats
+struct GBufferOutput {
+    @location(0) albedo: vec4<f32>,
+    @location(1) normal: vec4<f32>,
+    @location(2) position: vec4<f32>,
+};
+
+@fragment
+fn fs_main(in: VertexOutput) -> GBufferOutput {
+    var out: GBufferOutput;
+    // Alpha 1 marks the covered pixels, the lighting pass skips the others
+    out.albedo = vec4<f32>(in.color, 1.0);
+    out.normal = vec4<f32>(normalize(in.normal), 0.0);
+    out.position = vec4<f32>(in.world_position, 1.0);
+    return out;
+}
@@ -0,0 +1,59 @@
+const LIGHT_COUNT: u32 = 4u;
+
+struct Lighting {
+    positions: array<vec4<f32>, 4>,
+    colors: array<vec4<f32>, 4>,
+    mode: u32,
+};
+@group(0) @binding(0) var albedo_texture: texture_2d<f32>;
+@group(0) @binding(1) var normal_texture: texture_2d<f32>;
+@group(0) @binding(2) var position_texture: texture_2d<f32>;
+@group(0) @binding(3) var<uniform> lighting: Lighting;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+};
+
+// One triangle covering the whole screen
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The G-buffer has the size of the screen, so pixels are read one to one
+    let pixel = vec2<i32>(in.position.xy);
+    let albedo = textureLoad(albedo_texture, pixel, 0);
+    if albedo.a == 0.0 {
+        discard;
+    }
+    let normal = textureLoad(normal_texture, pixel, 0).xyz;
+    let position = textureLoad(position_texture, pixel, 0).xyz;
+
+    switch lighting.mode {
+        case 1u: {
+            return vec4<f32>(albedo.rgb, 1.0);
+        }
+        case 2u: {
+            return vec4<f32>(normal * 0.5 + vec3<f32>(0.5), 1.0);
+        }
+        case 3u: {
+            return vec4<f32>(fract(position), 1.0);
+        }
+        default: {}
+    }
+
+    var color = albedo.rgb * 0.05;
+    for (var i = 0u; i < LIGHT_COUNT; i += 1u) {
+        let to_light = lighting.positions[i].xyz - position;
+        let distance = length(to_light);
+        let diffuse = max(dot(normal, to_light / distance), 0.0);
+        let attenuation = 1.0 / (1.0 + 0.3 * distance * distance);
+        color += albedo.rgb * lighting.colors[i].rgb * diffuse * attenuation;
+    }
+    return vec4<f32>(color, 1.0);
+}
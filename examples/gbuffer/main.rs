@@ -0,0 +1,19 @@
+mod gbuffer_app;
+
+use std::sync::Arc;
+
+use crate::gbuffer_app::GBufferApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "G-Buffer App",
+        800,
+        600,
+        egui::Color32::from_rgb(20, 20, 30),
+        32,
+        0,
+        Box::new(|context| Arc::new(GBufferApp::new(context).unwrap())),
+    );
+    runner.run()
+}
@@ -1,8 +1,10 @@
 use wgpu_bootstrap::{
     cgmath, egui,
-    util::orbit_camera::{CameraUniform, OrbitCamera},
-    wgpu::{self, util::DeviceExt},
-    App, Context,
+    util::{
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+    },
+    wgpu, App, Context,
 };
 
 #[repr(C)]
@@ -153,29 +155,15 @@ pub struct GuiApp {
     render_pipeline: wgpu::RenderPipeline,
     num_indices: u32,
     camera: OrbitCamera,
-    fps: f32,
 }
 
 impl GuiApp {
     pub fn new(context: &Context) -> Self {
-        let index_buffer = context
-            .device()
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(INDEXES),
-                usage: wgpu::BufferUsages::INDEX,
-            });
+        let index_buffer = context.create_index_buffer(INDEXES, "Index Buffer");
 
         let num_indices = INDEXES.len() as u32;
 
-        let vertex_buffer =
-            context
-                .device()
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Vertex Buffer"),
-                    contents: bytemuck::cast_slice(VERTICES),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
+        let vertex_buffer = context.create_vertex_buffer(VERTICES, "Vertex Buffer");
 
         let camera_bind_group_layout = context
             .device()
@@ -188,64 +176,11 @@ impl GuiApp {
                 source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
             });
 
-        let pipeline_layout =
-            context
-                .device()
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&camera_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-
-        let render_pipeline =
-            context
-                .device()
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Render Pipeline"),
-                    layout: Some(&pipeline_layout),
-                    vertex: wgpu::VertexState {
-                        module: &shader,
-                        entry_point: "vs_main",
-                        buffers: &[Vertex::desc()],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: "fs_main",
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: context.format(),
-                            blend: Some(wgpu::BlendState::REPLACE),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: Some(wgpu::Face::Back),
-                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        // Requires Features::DEPTH_CLIP_CONTROL
-                        unclipped_depth: false,
-                        // Requires Features::CONSERVATIVE_RASTERIZATION
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: context.depth_stencil_format(),
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Less,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: 1,
-                        mask: !0,
-                        alpha_to_coverage_enabled: false,
-                    },
-                    multiview: None,
-                    cache: None,
-                });
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(Vertex::desc())
+            .build(context);
 
         let mut camera = OrbitCamera::new(
             context,
@@ -265,7 +200,6 @@ impl GuiApp {
             render_pipeline,
             num_indices,
             camera,
-            fps: 0.0,
         }
     }
 }
@@ -283,16 +217,15 @@ impl App for GuiApp {
         render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
     }
 
-    fn update(&mut self, delta_time: f32, _context: &Context) {
-        self.fps = 1.0 / delta_time;
-    }
-
     fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
         egui::Window::new("Params").show(egui_ctx, |ui| {
             let mut radius = self.camera.radius();
             ui.add(egui::Slider::new(&mut radius, 2.0..=10.0).text("radius"));
             self.camera.set_radius(radius).update(context);
-            ui.add(egui::Label::new(format!("FPS: {}", self.fps.round())));
+            ui.add(egui::Label::new(format!(
+                "FPS: {}",
+                context.fps_counter().fps().round()
+            )));
         });
     }
 }
@@ -0,0 +1,154 @@
+use wgpu_bootstrap::{
+    cgmath, egui, include_shader,
+    util::{
+        bloom::Bloom,
+        geometry::icosphere,
+        instance_buffer::InstanceBuffer,
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+    },
+    wgpu, App, Context, ContextError, Vertex,
+};
+
+const SPHERE_COUNT: i32 = 7;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 2)]
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+// A row of spheres, each twice as bright as the previous one. Rendered with Runner::set_hdr,
+// the bright ones keep their hue instead of clipping to white, and the exposure slider brings
+// the dark or the bright end into range. The bloom is applied to the HDR target in
+// App::post_process.
+pub struct HdrApp {
+    mesh: GpuMesh,
+    instances: InstanceBuffer<Instance>,
+    render_pipeline: wgpu::RenderPipeline,
+    camera: OrbitCamera,
+    exposure: f32,
+    bloom: Bloom,
+    bloom_enabled: bool,
+    threshold: f32,
+    intensity: f32,
+}
+
+impl HdrApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let hdr_target = context
+            .hdr_target()
+            .ok_or_else(|| ContextError::Other(String::from("run with Runner::set_hdr(true)")))?;
+
+        let (positions, indices) = icosphere(3);
+        let vertices: Vec<MeshVertex> = positions
+            .iter()
+            .map(|position| MeshVertex {
+                position: (*position * 0.4).into(),
+                normal: (*position).into(),
+            })
+            .collect();
+        let mesh = GpuMesh::new(context, &vertices, &indices);
+
+        let half = SPHERE_COUNT / 2;
+        let instances: Vec<Instance> = (-half..=half)
+            .map(|i| {
+                let intensity = 2.0f32.powi(i);
+                Instance {
+                    position: [0.0, 0.0, i as f32],
+                    color: [intensity, 0.4 * intensity, 0.1 * intensity],
+                }
+            })
+            .collect();
+        let instances = InstanceBuffer::new(context, &instances);
+
+        let shader = include_shader!(context, "shader.wgsl")?;
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        // The default format is the HDR one here, see Runner::set_hdr
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .build(context);
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(8.0, 0.0, 0.0))
+            .update(context);
+
+        let bloom = Bloom::new(context, hdr_target)?;
+
+        Ok(Self {
+            mesh,
+            instances,
+            render_pipeline,
+            camera,
+            exposure: context.exposure(),
+            threshold: bloom.threshold(),
+            intensity: bloom.intensity(),
+            bloom,
+            bloom_enabled: true,
+        })
+    }
+}
+
+impl App for HdrApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+        if let Some(hdr_target) = context.hdr_target() {
+            self.bloom.resize(context, hdr_target);
+        }
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::Window::new("HDR").show(egui_ctx, |ui| {
+            ui.add(
+                egui::Slider::new(&mut self.exposure, 0.05..=8.0)
+                    .logarithmic(true)
+                    .text("exposure"),
+            );
+            ui.checkbox(&mut self.bloom_enabled, "bloom");
+            ui.add(egui::Slider::new(&mut self.threshold, 0.0..=8.0).text("threshold"));
+            ui.add(egui::Slider::new(&mut self.intensity, 0.0..=2.0).text("intensity"));
+        });
+        context.set_exposure(self.exposure);
+        self.bloom
+            .set_threshold(context.queue(), self.threshold)
+            .set_intensity(context.queue(), self.intensity);
+    }
+
+    fn post_process(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        if let (true, Some(hdr_target)) = (self.bloom_enabled, context.hdr_target()) {
+            self.bloom.apply(encoder, hdr_target);
+        }
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        render_pass.set_vertex_buffer(1, self.instances.buffer().slice(..));
+        self.mesh
+            .draw_instanced(render_pass, 0..self.instances.count());
+    }
+}
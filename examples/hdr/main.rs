@@ -0,0 +1,20 @@
+mod hdr_app;
+
+use std::sync::Arc;
+
+use crate::hdr_app::HdrApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "HDR App",
+        800,
+        600,
+        egui::Color32::from_rgb(20, 20, 30),
+        32,
+        0,
+        Box::new(|context| Arc::new(HdrApp::new(context).unwrap())),
+    );
+    runner.set_hdr(true);
+    runner.run()
+}
@@ -0,0 +1,37 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.normal = model.normal;
+    out.color = instance.color;
+    out.clip_position = camera.proj * camera.view * vec4<f32>(model.position + instance.position, 1.0);
+    return out;
+}
+
+// Linear radiance, well above 1 for the brightest spheres
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let light = max(dot(normalize(in.normal), normalize(vec3<f32>(1.0, 2.0, 3.0))), 0.0) * 0.9 + 0.1;
+    return vec4<f32>(in.color * light, 1.0);
+}
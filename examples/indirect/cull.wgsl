@@ -0,0 +1,63 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+struct Params {
+    count: u32,
+    radius: f32,
+    // Moves the frustum planes inward so the culling can be seen on screen
+    inset: f32,
+};
+@group(0) @binding(1) var<uniform> params: Params;
+
+struct Instance {
+    position: vec3<f32>,
+    scale: f32,
+};
+@group(0) @binding(2) var<storage, read> instances: array<Instance>;
+@group(0) @binding(3) var<storage, read_write> visible: array<Instance>;
+
+// Same layout as wgpu::util::DrawIndexedIndirectArgs
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+@group(0) @binding(4) var<storage, read_write> draw: DrawArgs;
+
+fn row(m: mat4x4<f32>, i: u32) -> vec4<f32> {
+    return vec4<f32>(m[0][i], m[1][i], m[2][i], m[3][i]);
+}
+
+// Planes extracted from the view projection matrix, clip space depth is 0..1 in wgpu
+fn in_frustum(center: vec3<f32>, radius: f32) -> bool {
+    let view_proj = camera.proj * camera.view;
+    let r0 = row(view_proj, 0u);
+    let r1 = row(view_proj, 1u);
+    let r2 = row(view_proj, 2u);
+    let r3 = row(view_proj, 3u);
+    var planes = array<vec4<f32>, 6>(r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2);
+    for (var i = 0u; i < 6u; i++) {
+        let plane = planes[i] / length(planes[i].xyz);
+        if dot(plane.xyz, center) + plane.w < params.inset - radius {
+            return false;
+        }
+    }
+    return true;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.count {
+        return;
+    }
+    let instance = instances[id.x];
+    if in_frustum(instance.position, params.radius * instance.scale) {
+        let index = atomicAdd(&draw.instance_count, 1u);
+        visible[index] = instance;
+    }
+}
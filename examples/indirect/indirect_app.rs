@@ -0,0 +1,215 @@
+use wgpu_bootstrap::{
+    cgmath, egui,
+    util::{
+        bind_group::BindGroupBuilder,
+        geometry::icosphere,
+        indirect_buffer::IndirectBuffer,
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::{ComputePipelineBuilder, RenderPipelineBuilder},
+        uniform_buffer::UniformBuffer,
+    },
+    wgpu::{self, util::DeviceExt},
+    App, Context, ContextError, Vertex,
+};
+
+const GRID_SIZE: i32 = 32;
+const WORKGROUP_SIZE: u32 = 64;
+const SPHERE_RADIUS: f32 = 0.3;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+// Also the storage layout used by the culling shader, 16 bytes with no padding
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 2)]
+    position: [f32; 3],
+    scale: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullParams {
+    count: u32,
+    radius: f32,
+    inset: f32,
+    _padding: u32,
+}
+
+// Every frame a compute pass copies the instances inside the camera frustum to a second buffer
+// and counts them in the indirect buffer, the draw then uses that count without a readback
+pub struct IndirectApp {
+    mesh: GpuMesh,
+    visible_buffer: wgpu::Buffer,
+    indirect_buffer: IndirectBuffer,
+    params: CullParams,
+    params_buffer: UniformBuffer<CullParams>,
+    cull_pipeline: wgpu::ComputePipeline,
+    cull_bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    camera: OrbitCamera,
+}
+
+impl IndirectApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let (positions, indices) = icosphere(1);
+        let vertices: Vec<MeshVertex> = positions
+            .iter()
+            .map(|position| MeshVertex {
+                position: (*position * SPHERE_RADIUS).into(),
+                normal: (*position).into(),
+            })
+            .collect();
+        let mesh = GpuMesh::new(context, &vertices, &indices);
+
+        let half = GRID_SIZE / 2;
+        let instances: Vec<Instance> = (-half..half)
+            .flat_map(|x| (-half..half).flat_map(move |y| (-half..half).map(move |z| (x, y, z))))
+            .map(|(x, y, z)| Instance {
+                position: [x as f32, y as f32, z as f32],
+                scale: 1.0,
+            })
+            .collect();
+        let instance_buffer =
+            context
+                .device()
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Instance Buffer"),
+                    contents: bytemuck::cast_slice(&instances),
+                    usage: wgpu::BufferUsages::STORAGE,
+                });
+        let visible_buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Visible Instance Buffer"),
+            size: instance_buffer.size(),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX,
+            mapped_at_creation: false,
+        });
+        let indirect_buffer = IndirectBuffer::new(context, 1)?;
+
+        let params = CullParams {
+            count: instances.len() as u32,
+            radius: SPHERE_RADIUS,
+            inset: 0.0,
+            _padding: 0,
+        };
+        let params_buffer = UniformBuffer::new(context, params)?;
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(30.0, 0.6, 0.4))
+            .update(context);
+
+        let (cull_bind_group_layout, cull_bind_group) = BindGroupBuilder::new()
+            .label("Cull Bind Group")
+            .uniform(0, camera.buffer(), wgpu::ShaderStages::COMPUTE)
+            .uniform(1, params_buffer.buffer(), wgpu::ShaderStages::COMPUTE)
+            .storage(2, &instance_buffer, true, wgpu::ShaderStages::COMPUTE)
+            .storage(3, &visible_buffer, false, wgpu::ShaderStages::COMPUTE)
+            .storage(
+                4,
+                indirect_buffer.buffer(),
+                false,
+                wgpu::ShaderStages::COMPUTE,
+            )
+            .build(context);
+
+        let cull_shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Cull Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("cull.wgsl").into()),
+            });
+        let cull_pipeline = ComputePipelineBuilder::new(&cull_shader)
+            .label("Cull Pipeline")
+            .bind_group_layout(&cull_bind_group_layout)
+            .build(context);
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            });
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .build(context);
+
+        Ok(Self {
+            mesh,
+            visible_buffer,
+            indirect_buffer,
+            params,
+            params_buffer,
+            cull_pipeline,
+            cull_bind_group,
+            render_pipeline,
+            camera,
+        })
+    }
+}
+
+impl App for IndirectApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("Culling").show(egui_ctx, |ui| {
+            ui.add(egui::Slider::new(&mut self.params.inset, 0.0..=4.0).text("frustum inset"));
+        });
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        // The instance count is reset and the culling pass adds the visible instances to it
+        self.indirect_buffer.write(
+            context.queue(),
+            &[wgpu::util::DrawIndexedIndirectArgs {
+                index_count: self.mesh.index_count(),
+                instance_count: 0,
+                first_index: 0,
+                base_vertex: 0,
+                first_instance: 0,
+            }],
+        );
+        self.params_buffer.update(context.queue(), self.params);
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Cull Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.cull_pipeline);
+        compute_pass.set_bind_group(0, &self.cull_bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.params.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        render_pass.set_vertex_buffer(1, self.visible_buffer.slice(..));
+        self.mesh.draw_indirect(
+            render_pass,
+            &self.indirect_buffer,
+            self.indirect_buffer.offset(0),
+        );
+    }
+}
@@ -0,0 +1,19 @@
+mod indirect_app;
+
+use std::sync::Arc;
+
+use crate::indirect_app::IndirectApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Indirect App",
+        800,
+        600,
+        egui::Color32::from_rgb(245, 245, 245),
+        32,
+        0,
+        Box::new(|context| Arc::new(IndirectApp::new(context).unwrap())),
+    );
+    runner.run()
+}
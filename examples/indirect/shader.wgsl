@@ -0,0 +1,38 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) scale: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    instance: InstanceInput,
+) -> VertexOutput {
+    var out: VertexOutput;
+    let light = max(dot(model.normal, normalize(vec3<f32>(1.0, 2.0, 3.0))), 0.0) * 0.7 + 0.3;
+    out.color = (normalize(abs(instance.position) + vec3<f32>(0.1)) * 0.8 + 0.2) * light;
+    let position = model.position * instance.scale + instance.position;
+    out.clip_position = camera.proj * camera.view * vec4<f32>(position, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
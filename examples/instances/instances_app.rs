@@ -3,9 +3,9 @@ use wgpu_bootstrap::{
     util::{
         geometry::icosphere,
         orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
     },
-    wgpu::{self, util::DeviceExt},
-    App, Context,
+    wgpu, App, Context,
 };
 
 #[repr(C)]
@@ -59,6 +59,7 @@ impl Instance {
 pub struct InstanceApp {
     vertex_buffer: wgpu::Buffer,
     instance_buffer: wgpu::Buffer,
+    positions: Vec<cgmath::Vector3<f32>>,
     index_buffer: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
     num_indices: u32,
@@ -78,13 +79,7 @@ impl InstanceApp {
             })
             .collect();
 
-        let index_buffer = context
-            .device()
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(indices.as_slice()),
-                usage: wgpu::BufferUsages::INDEX,
-            });
+        let index_buffer = context.create_index_buffer(indices.as_slice(), "Index Buffer");
 
         let instances: Vec<Instance> = positions
             .iter()
@@ -96,23 +91,9 @@ impl InstanceApp {
         let num_indices = indices.len() as u32;
         let num_instances = instances.len() as u32;
 
-        let vertex_buffer =
-            context
-                .device()
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Vertex Buffer"),
-                    contents: bytemuck::cast_slice(vertices.as_slice()),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
-
-        let instance_buffer =
-            context
-                .device()
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Instance Buffer"),
-                    contents: bytemuck::cast_slice(instances.as_slice()),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
+        let vertex_buffer = context.create_vertex_buffer(vertices.as_slice(), "Vertex Buffer");
+
+        let instance_buffer = context.create_vertex_buffer(instances.as_slice(), "Instance Buffer");
 
         let shader = context
             .device()
@@ -125,64 +106,12 @@ impl InstanceApp {
             .device()
             .create_bind_group_layout(&CameraUniform::desc());
 
-        let pipeline_layout =
-            context
-                .device()
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&camera_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-
-        let render_pipeline =
-            context
-                .device()
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Render Pipeline"),
-                    layout: Some(&pipeline_layout),
-                    vertex: wgpu::VertexState {
-                        module: &shader,
-                        entry_point: "vs_main",
-                        buffers: &[Vertex::desc(), Instance::desc()],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: "fs_main",
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: context.format(),
-                            blend: Some(wgpu::BlendState::REPLACE),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: Some(wgpu::Face::Back),
-                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        // Requires Features::DEPTH_CLIP_CONTROL
-                        unclipped_depth: false,
-                        // Requires Features::CONSERVATIVE_RASTERIZATION
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: context.depth_stencil_format(),
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Less,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: 1,
-                        mask: !0,
-                        alpha_to_coverage_enabled: false,
-                    },
-                    multiview: None,
-                    cache: None,
-                });
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(Vertex::desc())
+            .vertex_layout(Instance::desc())
+            .build(context);
 
         let aspect = context.size().x / context.size().y;
         let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
@@ -193,6 +122,7 @@ impl InstanceApp {
         Self {
             vertex_buffer,
             instance_buffer,
+            positions,
             index_buffer,
             render_pipeline,
             num_indices,
@@ -207,6 +137,20 @@ impl App for InstanceApp {
         self.camera.input(input, context);
     }
 
+    // The instances pulse in and out, their new positions are uploaded every frame
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        let time = context.time().elapsed_seconds();
+        let instances: Vec<Instance> = self
+            .positions
+            .iter()
+            .enumerate()
+            .map(|(i, position)| Instance {
+                position: (*position * (1.0 + 0.1 * (2.0 * time + i as f32).sin())).into(),
+            })
+            .collect();
+        context.write_buffer(encoder, &self.instance_buffer, 0, &instances);
+    }
+
     fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
         render_pass.set_pipeline(&self.render_pipeline);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
@@ -0,0 +1,19 @@
+mod particles_app;
+
+use std::sync::Arc;
+
+use crate::particles_app::ParticlesApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Particles App",
+        800,
+        600,
+        egui::Color32::from_rgb(20, 20, 30),
+        32,
+        0,
+        Box::new(|context| Arc::new(ParticlesApp::new(context).unwrap())),
+    );
+    runner.run()
+}
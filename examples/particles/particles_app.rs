@@ -0,0 +1,78 @@
+use wgpu_bootstrap::{
+    cgmath, egui,
+    util::{orbit_camera::OrbitCamera, particles::ParticleSystem},
+    wgpu, App, Context, ContextError,
+};
+
+const MAX_PARTICLES: u32 = 100_000;
+
+// A fountain simulated in a compute pass and drawn as instanced quads, tuned from the GUI
+pub struct ParticlesApp {
+    particles: ParticleSystem,
+    spawn_count: u32,
+    speed: f32,
+    spread: f32,
+    gravity: f32,
+    camera: OrbitCamera,
+}
+
+impl ParticlesApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let mut particles = ParticleSystem::new(context, MAX_PARTICLES)?;
+        particles
+            .set_emitter(cgmath::point3(0.0, -1.0, 0.0))
+            .set_spawn_count(MAX_PARTICLES / 2);
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(8.0, 0.0, 0.2))
+            .update(context);
+
+        Ok(Self {
+            particles,
+            spawn_count: MAX_PARTICLES / 2,
+            speed: 5.0,
+            spread: 0.3,
+            gravity: 9.81,
+            camera,
+        })
+    }
+}
+
+impl App for ParticlesApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::Window::new("Particles").show(egui_ctx, |ui| {
+            ui.add(egui::Slider::new(&mut self.spawn_count, 0..=MAX_PARTICLES).text("count"));
+            ui.add(egui::Slider::new(&mut self.speed, 0.0..=10.0).text("speed"));
+            ui.add(egui::Slider::new(&mut self.spread, 0.0..=1.5).text("spread"));
+            ui.add(egui::Slider::new(&mut self.gravity, 0.0..=20.0).text("gravity"));
+            if ui.button("Reset").clicked() {
+                self.particles.reset(context);
+            }
+        });
+        self.particles
+            .set_spawn_count(self.spawn_count)
+            .set_speed(self.speed)
+            .set_spread(self.spread)
+            .set_gravity(cgmath::vec3(0.0, -self.gravity, 0.0));
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        self.particles.update(encoder, context);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.particles.draw(render_pass, self.camera.bind_group());
+    }
+}
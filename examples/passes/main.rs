@@ -0,0 +1,19 @@
+mod passes_app;
+
+use std::sync::Arc;
+
+use crate::passes_app::PassesApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Passes App",
+        800,
+        600,
+        egui::Color32::from_rgb(140, 170, 210),
+        32,
+        0,
+        Box::new(|context| Arc::new(PassesApp::new(context).unwrap())),
+    );
+    runner.run()
+}
@@ -0,0 +1,335 @@
+use wgpu_bootstrap::{
+    cgmath::{self, EuclideanSpace, InnerSpace},
+    egui, include_shader,
+    util::{
+        bind_group::BindGroupBuilder,
+        depth_target::DepthTarget,
+        geometry::icosphere,
+        instance_buffer::InstanceBuffer,
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+        post_process::PostProcess,
+        render_target::RenderTarget,
+        tonemap::{Tonemap, HDR_FORMAT},
+        uniform_buffer::UniformBuffer,
+    },
+    wgpu, App, Context, ContextError, PassList, Vertex,
+};
+
+const SHADOW_MAP_SIZE: u32 = 2048;
+const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+const LIGHT_EXTENT: f32 = 8.0;
+const LIGHT_DISTANCE: f32 = 15.0;
+// Linear, tonemapped with the rest of the scene
+const SKY: wgpu::Color = wgpu::Color {
+    r: 0.25,
+    g: 0.4,
+    b: 0.7,
+    a: 1.0,
+};
+
+// Copies the tonemapped frame to the window
+const BLIT: &str = "
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(input_texture, input_sampler, in.tex_coords);
+}
+";
+
+// Maps the [-1, 1] depth of cgmath::ortho to the [0, 1] of wgpu, keeping w at 1
+#[rustfmt::skip]
+const ORTHO_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 2)]
+    position: [f32; 3],
+    scale: f32,
+    color: [f32; 3],
+}
+
+// Matches Light in shader.wgsl and shadow.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Light {
+    view_proj: [[f32; 4]; 4],
+    direction: [f32; 4],
+    intensity: f32,
+    _padding: [f32; 3],
+}
+
+// The frame is a PassList of three passes instead of code spread over prepare and render: the
+// shadow map from the light, the scene lit with it into an HDR target, then the tonemap of that
+// target into the frame render shows.
+pub struct PassesApp {
+    sphere: GpuMesh,
+    spheres: InstanceBuffer<Instance>,
+    ground: GpuMesh,
+    ground_instance: InstanceBuffer<Instance>,
+    shadow_map: DepthTarget,
+    shadow_pipeline: wgpu::RenderPipeline,
+    shadow_bind_group: wgpu::BindGroup,
+    scene: RenderTarget,
+    scene_pipeline: wgpu::RenderPipeline,
+    tonemap: Tonemap,
+    frame: RenderTarget,
+    blit: PostProcess,
+    blit_bind_group: wgpu::BindGroup,
+    light: Light,
+    light_buffer: UniformBuffer<Light>,
+    light_bind_group: wgpu::BindGroup,
+    camera: OrbitCamera,
+    light_angle: f32,
+    exposure: f32,
+}
+
+impl PassesApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let (positions, indices) = icosphere(3);
+        let vertices: Vec<MeshVertex> = positions
+            .iter()
+            .map(|position| MeshVertex {
+                position: (*position * 0.5).into(),
+                normal: (*position).into(),
+            })
+            .collect();
+        let sphere = GpuMesh::new(context, &vertices, &indices);
+        let spheres: Vec<Instance> = (0..6)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 6.0;
+                Instance {
+                    position: [3.5 * angle.cos(), 0.8 + 0.5 * i as f32, 3.5 * angle.sin()],
+                    scale: 1.4,
+                    color: [0.3 + 0.12 * i as f32, 0.5, 0.9 - 0.12 * i as f32],
+                }
+            })
+            .collect();
+        let spheres = InstanceBuffer::new(context, &spheres);
+
+        let up = [0.0, 1.0, 0.0];
+        let ground_vertices = [
+            MeshVertex {
+                position: [-1.0, 0.0, -1.0],
+                normal: up,
+            },
+            MeshVertex {
+                position: [-1.0, 0.0, 1.0],
+                normal: up,
+            },
+            MeshVertex {
+                position: [1.0, 0.0, 1.0],
+                normal: up,
+            },
+            MeshVertex {
+                position: [1.0, 0.0, -1.0],
+                normal: up,
+            },
+        ];
+        let ground = GpuMesh::new(context, &ground_vertices, &[0, 1, 2, 0, 2, 3]);
+        let ground_instance = InstanceBuffer::new(
+            context,
+            &[Instance {
+                position: [0.0, 0.0, 0.0],
+                scale: LIGHT_EXTENT,
+                color: [0.8, 0.8, 0.75],
+            }],
+        );
+
+        let light = Light {
+            view_proj: cgmath::Matrix4::from_scale(1.0).into(),
+            direction: [0.0, 1.0, 0.0, 0.0],
+            intensity: 1.5,
+            _padding: [0.0; 3],
+        };
+        let light_buffer = UniformBuffer::new(context, light)?;
+        let (light_layout, light_bind_group) = BindGroupBuilder::new()
+            .label("Light Bind Group")
+            .uniform(
+                0,
+                light_buffer.buffer(),
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+            )
+            .build(context);
+
+        let shadow_map =
+            DepthTarget::new(context, SHADOW_MAP_SIZE, SHADOW_MAP_SIZE, SHADOW_FORMAT)?;
+        let shadow_layout = context
+            .device()
+            .create_bind_group_layout(&DepthTarget::desc());
+        let shadow_bind_group = shadow_map.bind_group(context, &shadow_layout);
+
+        let shadow_shader = include_shader!(context, "shadow.wgsl")?;
+        let shadow_pipeline = RenderPipelineBuilder::new(&shadow_shader)
+            .label("Shadow Pipeline")
+            .bind_group_layout(&light_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .depth_target(&shadow_map)
+            .depth_bias(2, 2.0)
+            .build(context);
+
+        let width = context.size().x as u32;
+        let height = context.size().y as u32;
+        let scene = RenderTarget::new(
+            context,
+            width,
+            height,
+            HDR_FORMAT,
+            Some(wgpu::TextureFormat::Depth32Float),
+        );
+        let shader = include_shader!(context, "shader.wgsl")?;
+        let camera_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let scene_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Scene Pipeline")
+            .bind_group_layout(&camera_layout)
+            .bind_group_layout(&light_layout)
+            .bind_group_layout(&shadow_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .render_target(&scene)
+            .try_build_for(context, &scene)?;
+
+        let tonemap = Tonemap::new(context)?;
+        let frame = RenderTarget::new(context, width, height, context.format(), None);
+        let blit = PostProcess::new(context, BLIT)?;
+        let blit_bind_group = blit.bind_group(context, frame.texture_view());
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(16.0, 0.6, 0.6))
+            .update(context);
+
+        let mut app = Self {
+            sphere,
+            spheres,
+            ground,
+            ground_instance,
+            shadow_map,
+            shadow_pipeline,
+            shadow_bind_group,
+            scene,
+            scene_pipeline,
+            tonemap,
+            frame,
+            blit,
+            blit_bind_group,
+            light,
+            light_buffer,
+            light_bind_group,
+            camera,
+            light_angle: 0.8,
+            exposure: 1.0,
+        };
+        app.update_light(context);
+        Ok(app)
+    }
+
+    fn update_light(&mut self, context: &Context) {
+        let direction =
+            cgmath::vec3(self.light_angle.cos(), 1.5, self.light_angle.sin()).normalize();
+        let view = cgmath::Matrix4::look_at_rh(
+            cgmath::Point3::from_vec(direction * LIGHT_DISTANCE),
+            cgmath::point3(0.0, 0.0, 0.0),
+            cgmath::Vector3::unit_y(),
+        );
+        let proj = cgmath::ortho(
+            -LIGHT_EXTENT,
+            LIGHT_EXTENT,
+            -LIGHT_EXTENT,
+            LIGHT_EXTENT,
+            1.0,
+            2.0 * LIGHT_DISTANCE,
+        );
+        self.light.view_proj = (ORTHO_TO_WGPU_MATRIX * proj * view).into();
+        self.light.direction = direction.extend(0.0).into();
+        self.light_buffer.update(context.queue(), self.light);
+    }
+
+    fn draw_scene(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_vertex_buffer(1, self.spheres.buffer().slice(..));
+        self.sphere
+            .draw_instanced(render_pass, 0..self.spheres.count());
+        render_pass.set_vertex_buffer(1, self.ground_instance.buffer().slice(..));
+        self.ground
+            .draw_instanced(render_pass, 0..self.ground_instance.count());
+    }
+}
+
+impl App for PassesApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+        self.scene.resize(context, new_width, new_height);
+        self.frame.resize(context, new_width, new_height);
+        self.blit_bind_group = self.blit.bind_group(context, self.frame.texture_view());
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::Window::new("Passes").show(egui_ctx, |ui| {
+            ui.add(egui::Slider::new(&mut self.light.intensity, 0.0..=10.0).text("light"));
+            if ui
+                .add(egui::Slider::new(&mut self.exposure, 0.1..=4.0).text("exposure"))
+                .changed()
+            {
+                self.tonemap.set_exposure(context.queue(), self.exposure);
+            }
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        self.light_angle += 0.3 * delta_time;
+        self.update_light(context);
+    }
+
+    fn passes<'a>(&'a mut self, passes: &mut PassList<'a>, _context: &Context) {
+        let app = &*self;
+        passes
+            .depth("Shadow", &app.shadow_map, |render_pass, _| {
+                render_pass.set_pipeline(&app.shadow_pipeline);
+                render_pass.set_bind_group(0, &app.light_bind_group, &[]);
+                app.draw_scene(render_pass);
+            })
+            .color("Scene", &app.scene, Some(SKY), |render_pass, _| {
+                render_pass.set_pipeline(&app.scene_pipeline);
+                render_pass.set_bind_group(0, app.camera.bind_group(), &[]);
+                render_pass.set_bind_group(1, &app.light_bind_group, &[]);
+                render_pass.set_bind_group(2, &app.shadow_bind_group, &[]);
+                app.draw_scene(render_pass);
+            })
+            .encoder("Tonemap", |encoder, context| {
+                app.tonemap.apply(
+                    context,
+                    encoder,
+                    app.scene.texture_view(),
+                    app.frame.texture_view(),
+                );
+            });
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.blit.draw(render_pass, &self.blit_bind_group);
+    }
+}
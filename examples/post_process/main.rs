@@ -0,0 +1,19 @@
+mod post_process_app;
+
+use std::sync::Arc;
+
+use crate::post_process_app::PostProcessApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Post Process App",
+        800,
+        600,
+        egui::Color32::from_rgb(245, 245, 245),
+        32,
+        0,
+        Box::new(|context| Arc::new(PostProcessApp::new(context))),
+    );
+    runner.run()
+}
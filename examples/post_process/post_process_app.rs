@@ -0,0 +1,200 @@
+use wgpu_bootstrap::{
+    cgmath, egui,
+    util::{
+        geometry::icosphere,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+        post_process::PostProcess,
+        render_target::RenderTarget,
+    },
+    wgpu, App, Context,
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Vertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    color: [f32; 3],
+}
+
+impl Vertex {
+    fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x3,
+                },
+            ],
+        }
+    }
+}
+
+// Matches Params in vignette.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Params {
+    grayscale: f32,
+    vignette: f32,
+    radius: f32,
+    _padding: f32,
+}
+
+pub struct PostProcessApp {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    render_pipeline: wgpu::RenderPipeline,
+    num_indices: u32,
+    camera: OrbitCamera,
+    scene: RenderTarget,
+    post_process: PostProcess,
+    post_process_bind_group: wgpu::BindGroup,
+    params: Params,
+}
+
+impl PostProcessApp {
+    pub fn new(context: &Context) -> Self {
+        let (positions, indices) = icosphere(3);
+
+        let vertices: Vec<Vertex> = positions
+            .iter()
+            .map(|position| Vertex {
+                position: (*position).into(),
+                normal: (*position).into(),
+                color: [250.0 / 255.0, 143.0 / 255.0, 47.0 / 255.0],
+            })
+            .collect();
+
+        let index_buffer = context.create_index_buffer(indices.as_slice(), "Index Buffer");
+
+        let num_indices = indices.len() as u32;
+
+        let vertex_buffer = context.create_vertex_buffer(vertices.as_slice(), "Vertex Buffer");
+
+        // The scene is drawn offscreen, then the post process draws it to the window
+        let scene = RenderTarget::new(
+            context,
+            context.size().x as u32,
+            context.size().y as u32,
+            context.format(),
+            Some(wgpu::TextureFormat::Depth32Float),
+        );
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Scene Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("scene.wgsl").into()),
+            });
+
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Scene Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(Vertex::desc())
+            .format(scene.format())
+            .depth_format(scene.depth_format())
+            .sample_count(1)
+            .build(context);
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(3.0, 0.0, 0.0))
+            .update(context);
+
+        let params = Params {
+            grayscale: 1.0,
+            vignette: 1.0,
+            radius: 0.75,
+            _padding: 0.0,
+        };
+        let post_process = PostProcess::with_params(context, include_str!("vignette.wgsl"), params)
+            .expect("Failed to create the post process");
+        let post_process_bind_group = post_process.bind_group(context, scene.texture_view());
+
+        Self {
+            vertex_buffer,
+            index_buffer,
+            render_pipeline,
+            num_indices,
+            camera,
+            scene,
+            post_process,
+            post_process_bind_group,
+            params,
+        }
+    }
+}
+
+impl App for PostProcessApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+        self.scene.resize(context, new_width, new_height);
+        self.post_process_bind_group = self
+            .post_process
+            .bind_group(context, self.scene.texture_view());
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::Window::new("Post Process").show(egui_ctx, |ui| {
+            let mut changed = ui
+                .add(egui::Slider::new(&mut self.params.grayscale, 0.0..=1.0).text("Grayscale"))
+                .changed();
+            changed |= ui
+                .add(egui::Slider::new(&mut self.params.vignette, 0.0..=1.0).text("Vignette"))
+                .changed();
+            changed |= ui
+                .add(egui::Slider::new(&mut self.params.radius, 0.3..=1.0).text("Radius"))
+                .changed();
+            if changed {
+                self.post_process.set_params(context.queue(), self.params);
+            }
+        });
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, _context: &Context) {
+        let mut render_pass = self.scene.begin_pass(
+            encoder,
+            Some(wgpu::Color {
+                r: 0.9,
+                g: 0.9,
+                b: 0.9,
+                a: 1.0,
+            }),
+        );
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.post_process
+            .draw(render_pass, &self.post_process_bind_group);
+    }
+}
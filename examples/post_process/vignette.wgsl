@@ -0,0 +1,22 @@
+// VertexOutput, vs_main, input_texture and input_sampler come from PostProcess
+struct Params {
+    grayscale: f32,
+    vignette: f32,
+    radius: f32,
+    _padding: f32,
+};
+@group(0) @binding(2) var<uniform> params: Params;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(input_texture, input_sampler, in.tex_coords).rgb;
+
+    let luminance = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    let graded = mix(color, vec3<f32>(luminance), params.grayscale);
+
+    let distance = length(in.tex_coords - vec2<f32>(0.5, 0.5));
+    let falloff = smoothstep(params.radius, params.radius - 0.3, distance);
+    let vignette = mix(1.0, falloff, params.vignette);
+
+    return vec4<f32>(graded * vignette, 1.0);
+}
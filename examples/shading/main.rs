@@ -3,9 +3,9 @@ mod shading_app;
 use std::sync::Arc;
 
 use crate::shading_app::ShadingApp;
-use wgpu_bootstrap::{egui, Runner};
+use wgpu_bootstrap::{egui, ActionMap, ContextError, Runner};
 
-fn main() {
+fn main() -> Result<(), ContextError> {
     let mut runner = Runner::new(
         "Shading App",
         800,
@@ -15,5 +15,11 @@ fn main() {
         0,
         Box::new(|context| Arc::new(ShadingApp::new(context))),
     );
-    runner.run();
+    let mut actions = ActionMap::new();
+    actions.bind("toggle_wireframe", egui::Key::W);
+    runner
+        .set_msaa(4)
+        .set_wireframe_support(true)
+        .set_actions(actions);
+    runner.run()
 }
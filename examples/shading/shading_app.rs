@@ -3,49 +3,25 @@ use wgpu_bootstrap::{
     util::{
         geometry::icosphere,
         orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::{RenderPipelineBuilder, WireframePipeline},
     },
-    wgpu::{self, util::DeviceExt},
-    App, Context,
+    wgpu, App, Context, Vertex,
 };
 
+// Layout generated from the fields, in locations 0, 1 and 2
 #[repr(C)]
-#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
 struct Vertex {
     position: [f32; 3],
     normal: [f32; 3],
     color: [f32; 3],
 }
 
-impl Vertex {
-    fn desc() -> wgpu::VertexBufferLayout<'static> {
-        wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    offset: 0,
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-                wgpu::VertexAttribute {
-                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
-                    shader_location: 2,
-                    format: wgpu::VertexFormat::Float32x3,
-                },
-            ],
-        }
-    }
-}
-
 pub struct ShadingApp {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     render_pipeline: wgpu::RenderPipeline,
+    wireframe: Option<WireframePipeline>,
     num_indices: u32,
     camera: OrbitCamera,
 }
@@ -63,24 +39,11 @@ impl ShadingApp {
             })
             .collect();
 
-        let index_buffer = context
-            .device()
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Index Buffer"),
-                contents: bytemuck::cast_slice(indices.as_slice()),
-                usage: wgpu::BufferUsages::INDEX,
-            });
+        let index_buffer = context.create_index_buffer(indices.as_slice(), "Index Buffer");
 
         let num_indices = indices.len() as u32;
 
-        let vertex_buffer =
-            context
-                .device()
-                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                    label: Some("Vertex Buffer"),
-                    contents: bytemuck::cast_slice(vertices.as_slice()),
-                    usage: wgpu::BufferUsages::VERTEX,
-                });
+        let vertex_buffer = context.create_vertex_buffer(vertices.as_slice(), "Vertex Buffer");
 
         let shader = context
             .device()
@@ -93,64 +56,17 @@ impl ShadingApp {
             .device()
             .create_bind_group_layout(&CameraUniform::desc());
 
-        let pipeline_layout =
-            context
-                .device()
-                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                    label: Some("Render Pipeline Layout"),
-                    bind_group_layouts: &[&camera_bind_group_layout],
-                    push_constant_ranges: &[],
-                });
-
-        let render_pipeline =
-            context
-                .device()
-                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                    label: Some("Render Pipeline"),
-                    layout: Some(&pipeline_layout),
-                    vertex: wgpu::VertexState {
-                        module: &shader,
-                        entry_point: "vs_main",
-                        buffers: &[Vertex::desc()],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    },
-                    fragment: Some(wgpu::FragmentState {
-                        module: &shader,
-                        entry_point: "fs_main",
-                        targets: &[Some(wgpu::ColorTargetState {
-                            format: context.format(),
-                            blend: Some(wgpu::BlendState::REPLACE),
-                            write_mask: wgpu::ColorWrites::ALL,
-                        })],
-                        compilation_options: wgpu::PipelineCompilationOptions::default(),
-                    }),
-                    primitive: wgpu::PrimitiveState {
-                        topology: wgpu::PrimitiveTopology::TriangleList,
-                        strip_index_format: None,
-                        front_face: wgpu::FrontFace::Ccw,
-                        cull_mode: Some(wgpu::Face::Back),
-                        // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                        polygon_mode: wgpu::PolygonMode::Fill,
-                        // Requires Features::DEPTH_CLIP_CONTROL
-                        unclipped_depth: false,
-                        // Requires Features::CONSERVATIVE_RASTERIZATION
-                        conservative: false,
-                    },
-                    depth_stencil: Some(wgpu::DepthStencilState {
-                        format: context.depth_stencil_format(),
-                        depth_write_enabled: true,
-                        depth_compare: wgpu::CompareFunction::Less,
-                        stencil: wgpu::StencilState::default(),
-                        bias: wgpu::DepthBiasState::default(),
-                    }),
-                    multisample: wgpu::MultisampleState {
-                        count: 1,
-                        mask: !0,
-                        alpha_to_coverage_enabled: false,
-                    },
-                    multiview: None,
-                    cache: None,
-                });
+        let mut builder = RenderPipelineBuilder::new(&shader);
+        builder
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(Vertex::desc());
+        let render_pipeline = builder.build(context);
+        // Without POLYGON_MODE_LINE on this GPU the W key does nothing
+        let wireframe = builder
+            .build_wireframe(context)
+            .inspect_err(|err| log::warn!("wireframe unavailable: {err}"))
+            .ok();
 
         let aspect = context.size().x / context.size().y;
         let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
@@ -162,6 +78,7 @@ impl ShadingApp {
             vertex_buffer,
             index_buffer,
             render_pipeline,
+            wireframe,
             num_indices,
             camera,
         }
@@ -171,10 +88,25 @@ impl ShadingApp {
 impl App for ShadingApp {
     fn input(&mut self, input: eframe::egui::InputState, context: &Context) {
         self.camera.input(input, context);
+        if context.input().was_action_pressed("toggle_wireframe") {
+            if let Some(wireframe) = &mut self.wireframe {
+                wireframe.toggle();
+            }
+        }
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
     }
 
     fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
-        render_pass.set_pipeline(&self.render_pipeline);
+        let pipeline = match &self.wireframe {
+            Some(wireframe) => wireframe.pipeline(),
+            None => &self.render_pipeline,
+        };
+        render_pass.set_pipeline(pipeline);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
         render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
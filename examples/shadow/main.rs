@@ -0,0 +1,19 @@
+mod shadow_app;
+
+use std::sync::Arc;
+
+use crate::shadow_app::ShadowApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Shadow App",
+        800,
+        600,
+        egui::Color32::from_rgb(140, 170, 210),
+        32,
+        0,
+        Box::new(|context| Arc::new(ShadowApp::new(context).unwrap())),
+    );
+    runner.run()
+}
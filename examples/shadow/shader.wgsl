@@ -0,0 +1,75 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+struct Light {
+    view_proj: mat4x4<f32>,
+    // Toward the light
+    direction: vec4<f32>,
+    pcf: u32,
+};
+@group(1) @binding(0) var<uniform> light: Light;
+
+@group(2) @binding(0) var shadow_map: texture_depth_2d;
+@group(2) @binding(1) var shadow_sampler: sampler_comparison;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) scale: f32,
+    @location(4) color: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) normal: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) light_position: vec4<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    let world = vec4<f32>(model.position * instance.scale + instance.position, 1.0);
+    var out: VertexOutput;
+    out.normal = model.normal;
+    out.color = instance.color;
+    out.light_position = light.view_proj * world;
+    out.clip_position = camera.proj * camera.view * world;
+    return out;
+}
+
+// 1 when lit, 0 in the shadow
+fn shadow(light_position: vec4<f32>) -> f32 {
+    let ndc = light_position.xyz / light_position.w;
+    // Past the far plane of the light is never shadowed
+    if ndc.z > 1.0 {
+        return 1.0;
+    }
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+    if light.pcf == 0u {
+        return textureSampleCompareLevel(shadow_map, shadow_sampler, uv, ndc.z);
+    }
+    // Each sample already filters 2x2 texels, 3x3 of them soften the edge over a few more
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_map));
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y += 1) {
+        for (var x = -1; x <= 1; x += 1) {
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, ndc.z);
+        }
+    }
+    return lit / 9.0;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let diffuse = max(dot(normalize(in.normal), normalize(light.direction.xyz)), 0.0);
+    let brightness = 0.25 + 0.75 * diffuse * shadow(in.light_position);
+    return vec4<f32>(in.color * brightness, 1.0);
+}
@@ -0,0 +1,23 @@
+struct Light {
+    view_proj: mat4x4<f32>,
+    direction: vec4<f32>,
+    pcf: u32,
+};
+@group(0) @binding(0) var<uniform> light: Light;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct InstanceInput {
+    @location(2) position: vec3<f32>,
+    @location(3) scale: f32,
+    @location(4) color: vec3<f32>,
+};
+
+// Depth only, the pipeline has no fragment stage
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> @builtin(position) vec4<f32> {
+    return light.view_proj * vec4<f32>(model.position * instance.scale + instance.position, 1.0);
+}
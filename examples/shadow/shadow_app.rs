@@ -0,0 +1,281 @@
+use wgpu_bootstrap::{
+    cgmath::{self, EuclideanSpace, InnerSpace},
+    egui, include_shader,
+    util::{
+        bind_group::BindGroupBuilder,
+        depth_target::DepthTarget,
+        geometry::icosphere,
+        instance_buffer::InstanceBuffer,
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+        uniform_buffer::UniformBuffer,
+    },
+    wgpu, App, Context, ContextError, Vertex,
+};
+
+const SHADOW_MAP_SIZE: u32 = 2048;
+const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+// Half the side of the square the light's orthographic projection covers
+const LIGHT_EXTENT: f32 = 8.0;
+const LIGHT_DISTANCE: f32 = 15.0;
+
+// Maps the [-1, 1] depth of cgmath::ortho to the [0, 1] of wgpu, keeping w at 1
+#[rustfmt::skip]
+const ORTHO_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.0,
+    0.0, 0.0, 0.5, 1.0,
+);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 2)]
+    position: [f32; 3],
+    scale: f32,
+    color: [f32; 3],
+}
+
+// Matches Light in shader.wgsl and shadow.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Light {
+    view_proj: [[f32; 4]; 4],
+    direction: [f32; 4],
+    pcf: u32,
+    _padding: [u32; 3],
+}
+
+// A directional light turning around spheres above a ground plane. The scene is first drawn
+// into a DepthTarget from the light, then the main pass compares each fragment's depth seen
+// from the light with the map.
+pub struct ShadowApp {
+    sphere: GpuMesh,
+    spheres: InstanceBuffer<Instance>,
+    ground: GpuMesh,
+    ground_instance: InstanceBuffer<Instance>,
+    shadow_map: DepthTarget,
+    shadow_pipeline: wgpu::RenderPipeline,
+    shadow_bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    light: Light,
+    light_buffer: UniformBuffer<Light>,
+    light_bind_group: wgpu::BindGroup,
+    camera: OrbitCamera,
+    light_angle: f32,
+    animate: bool,
+    pcf: bool,
+}
+
+impl ShadowApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let (positions, indices) = icosphere(3);
+        let vertices: Vec<MeshVertex> = positions
+            .iter()
+            .map(|position| MeshVertex {
+                position: (*position * 0.5).into(),
+                normal: (*position).into(),
+            })
+            .collect();
+        let sphere = GpuMesh::new(context, &vertices, &indices);
+        let spheres: Vec<Instance> = (0..8)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 8.0;
+                let radius = if i % 2 == 0 { 2.5 } else { 4.5 };
+                Instance {
+                    position: [
+                        radius * angle.cos(),
+                        0.6 + 0.4 * i as f32,
+                        radius * angle.sin(),
+                    ],
+                    scale: 1.0 + 0.2 * (i % 3) as f32,
+                    color: [0.9, 0.4 + 0.07 * i as f32, 0.3],
+                }
+            })
+            .collect();
+        let spheres = InstanceBuffer::new(context, &spheres);
+
+        let up = [0.0, 1.0, 0.0];
+        let ground_vertices = [
+            MeshVertex {
+                position: [-1.0, 0.0, -1.0],
+                normal: up,
+            },
+            MeshVertex {
+                position: [-1.0, 0.0, 1.0],
+                normal: up,
+            },
+            MeshVertex {
+                position: [1.0, 0.0, 1.0],
+                normal: up,
+            },
+            MeshVertex {
+                position: [1.0, 0.0, -1.0],
+                normal: up,
+            },
+        ];
+        let ground = GpuMesh::new(context, &ground_vertices, &[0, 1, 2, 0, 2, 3]);
+        let ground_instance = InstanceBuffer::new(
+            context,
+            &[Instance {
+                position: [0.0, 0.0, 0.0],
+                scale: LIGHT_EXTENT,
+                color: [0.8, 0.8, 0.75],
+            }],
+        );
+
+        let light = Light {
+            view_proj: cgmath::Matrix4::from_scale(1.0).into(),
+            direction: [0.0, 1.0, 0.0, 0.0],
+            pcf: 1,
+            _padding: [0; 3],
+        };
+        let light_buffer = UniformBuffer::new(context, light)?;
+        let (light_layout, light_bind_group) = BindGroupBuilder::new()
+            .label("Light Bind Group")
+            .uniform(
+                0,
+                light_buffer.buffer(),
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+            )
+            .build(context);
+
+        let shadow_map =
+            DepthTarget::new(context, SHADOW_MAP_SIZE, SHADOW_MAP_SIZE, SHADOW_FORMAT)?;
+        let shadow_layout = context
+            .device()
+            .create_bind_group_layout(&DepthTarget::desc());
+        let shadow_bind_group = shadow_map.bind_group(context, &shadow_layout);
+
+        let shadow_shader = include_shader!(context, "shadow.wgsl")?;
+        let shadow_pipeline = RenderPipelineBuilder::new(&shadow_shader)
+            .label("Shadow Pipeline")
+            .bind_group_layout(&light_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .depth_target(&shadow_map)
+            .depth_bias(2, 2.0)
+            .build(context);
+
+        let shader = include_shader!(context, "shader.wgsl")?;
+        let camera_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&camera_layout)
+            .bind_group_layout(&light_layout)
+            .bind_group_layout(&shadow_layout)
+            .vertex_layout(MeshVertex::desc())
+            .vertex_layout(Instance::desc())
+            .build(context);
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 45.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(16.0, 0.6, 0.6))
+            .update(context);
+
+        let mut app = Self {
+            sphere,
+            spheres,
+            ground,
+            ground_instance,
+            shadow_map,
+            shadow_pipeline,
+            shadow_bind_group,
+            render_pipeline,
+            light,
+            light_buffer,
+            light_bind_group,
+            camera,
+            light_angle: 0.8,
+            animate: true,
+            pcf: true,
+        };
+        app.update_light(context);
+        Ok(app)
+    }
+
+    fn update_light(&mut self, context: &Context) {
+        let direction =
+            cgmath::vec3(self.light_angle.cos(), 1.5, self.light_angle.sin()).normalize();
+        let view = cgmath::Matrix4::look_at_rh(
+            cgmath::Point3::from_vec(direction * LIGHT_DISTANCE),
+            cgmath::point3(0.0, 0.0, 0.0),
+            cgmath::Vector3::unit_y(),
+        );
+        let proj = cgmath::ortho(
+            -LIGHT_EXTENT,
+            LIGHT_EXTENT,
+            -LIGHT_EXTENT,
+            LIGHT_EXTENT,
+            1.0,
+            2.0 * LIGHT_DISTANCE,
+        );
+        self.light.view_proj = (ORTHO_TO_WGPU_MATRIX * proj * view).into();
+        self.light.direction = direction.extend(0.0).into();
+        self.light.pcf = u32::from(self.pcf);
+        self.light_buffer.update(context.queue(), self.light);
+    }
+
+    fn draw_scene(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_vertex_buffer(1, self.spheres.buffer().slice(..));
+        self.sphere
+            .draw_instanced(render_pass, 0..self.spheres.count());
+        render_pass.set_vertex_buffer(1, self.ground_instance.buffer().slice(..));
+        self.ground
+            .draw_instanced(render_pass, 0..self.ground_instance.count());
+    }
+}
+
+impl App for ShadowApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("Shadow").show(egui_ctx, |ui| {
+            ui.checkbox(&mut self.animate, "animate light");
+            ui.checkbox(&mut self.pcf, "PCF");
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        if self.animate {
+            self.light_angle += 0.3 * delta_time;
+        }
+        self.update_light(context);
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, _context: &Context) {
+        let mut render_pass = self.shadow_map.begin_pass(encoder);
+        render_pass.set_pipeline(&self.shadow_pipeline);
+        render_pass.set_bind_group(0, &self.light_bind_group, &[]);
+        self.draw_scene(&mut render_pass);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        render_pass.set_bind_group(1, &self.light_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.shadow_bind_group, &[]);
+        self.draw_scene(render_pass);
+    }
+}
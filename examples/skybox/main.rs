@@ -0,0 +1,19 @@
+mod skybox_app;
+
+use std::sync::Arc;
+
+use crate::skybox_app::SkyboxApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Skybox App",
+        800,
+        600,
+        egui::Color32::from_rgb(0, 0, 0),
+        32,
+        0,
+        Box::new(|context| Arc::new(SkyboxApp::new(context).unwrap())),
+    );
+    runner.run()
+}
@@ -0,0 +1,39 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var sky: texture_cube<f32>;
+@group(1) @binding(1) var sky_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+};
+
+@vertex
+fn vs_main(model: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.proj * camera.view * vec4<f32>(model.position, 1.0);
+    out.position = model.position;
+    out.normal = model.normal;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // The camera position, from the inverse of the rigid view matrix
+    let rotation = mat3x3<f32>(camera.view[0].xyz, camera.view[1].xyz, camera.view[2].xyz);
+    let eye = -(transpose(rotation) * camera.view[3].xyz);
+    let incident = normalize(in.position - eye);
+    let reflected = reflect(incident, normalize(in.normal));
+    let color = textureSample(sky, sky_sampler, reflected).rgb;
+    return vec4<f32>(color * 0.9, 1.0);
+}
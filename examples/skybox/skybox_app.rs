@@ -0,0 +1,164 @@
+use wgpu_bootstrap::{
+    cgmath::{self, InnerSpace},
+    egui, include_shader,
+    util::{
+        bind_group::BindGroupBuilder,
+        geometry::icosphere,
+        mesh::GpuMesh,
+        orbit_camera::{CameraUniform, OrbitCamera},
+        pipeline::RenderPipelineBuilder,
+        skybox::Skybox,
+        texture::Texture,
+    },
+    wgpu, App, Context, ContextError, Vertex,
+};
+
+const FACE_SIZE: u32 = 256;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct MeshVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+// The direction a texel of a cubemap face looks at, s and t in [-1, 1] from the top-left
+// corner of the face
+fn face_direction(face: usize, s: f32, t: f32) -> cgmath::Vector3<f32> {
+    match face {
+        0 => cgmath::vec3(1.0, -t, -s),
+        1 => cgmath::vec3(-1.0, -t, s),
+        2 => cgmath::vec3(s, 1.0, t),
+        3 => cgmath::vec3(s, -1.0, -t),
+        4 => cgmath::vec3(s, -t, 1.0),
+        _ => cgmath::vec3(-s, -t, -1.0),
+    }
+    .normalize()
+}
+
+// A sky gradient with a sun over a dark ground, computed for each direction instead of loaded
+// from six images
+fn face_pixels(face: usize) -> Vec<u8> {
+    let sun = cgmath::vec3(0.5, 0.4, -0.6).normalize();
+    let zenith = cgmath::vec3(0.2, 0.4, 0.85);
+    let horizon = cgmath::vec3(0.85, 0.9, 0.95);
+    let ground = cgmath::vec3(0.3, 0.27, 0.22);
+    let mut pixels = Vec::with_capacity((FACE_SIZE * FACE_SIZE * 4) as usize);
+    for y in 0..FACE_SIZE {
+        for x in 0..FACE_SIZE {
+            let s = 2.0 * (x as f32 + 0.5) / FACE_SIZE as f32 - 1.0;
+            let t = 2.0 * (y as f32 + 0.5) / FACE_SIZE as f32 - 1.0;
+            let direction = face_direction(face, s, t);
+            let mut color = if direction.y >= 0.0 {
+                horizon + (zenith - horizon) * direction.y.sqrt()
+            } else {
+                ground * (1.0 + direction.y * 0.5)
+            };
+            if direction.dot(sun) > 0.998 {
+                color = cgmath::vec3(1.0, 0.95, 0.8);
+            }
+            pixels.extend_from_slice(&[
+                (color.x * 255.0) as u8,
+                (color.y * 255.0) as u8,
+                (color.z * 255.0) as u8,
+                255,
+            ]);
+        }
+    }
+    pixels
+}
+
+// A mirror sphere under a procedural sky. The same cubemap is drawn around the camera by
+// Skybox and sampled by the sphere shader for its reflections.
+pub struct SkyboxApp {
+    sphere: GpuMesh,
+    pipeline: wgpu::RenderPipeline,
+    sky_bind_group: wgpu::BindGroup,
+    skybox: Skybox,
+    camera: OrbitCamera,
+    show_sky: bool,
+}
+
+impl SkyboxApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let faces: Vec<Vec<u8>> = (0..6).map(face_pixels).collect();
+        let cubemap = Texture::cubemap_from_rgba(
+            context,
+            FACE_SIZE,
+            std::array::from_fn(|face| faces[face].as_slice()),
+            "Sky",
+        )?;
+        let skybox = Skybox::new(context, &cubemap)?;
+
+        let (positions, indices) = icosphere(4);
+        let vertices: Vec<MeshVertex> = positions
+            .iter()
+            .map(|position| MeshVertex {
+                position: (*position).into(),
+                normal: (*position).into(),
+            })
+            .collect();
+        let sphere = GpuMesh::new(context, &vertices, &indices);
+
+        let camera_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let (sky_layout, sky_bind_group) = BindGroupBuilder::new()
+            .label("Sky Bind Group")
+            .texture_cube(0, cubemap.view(), wgpu::ShaderStages::FRAGMENT)
+            .sampler(1, cubemap.sampler(), wgpu::ShaderStages::FRAGMENT)
+            .build(context);
+        let shader = include_shader!(context, "shader.wgsl")?;
+        let pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Sphere Pipeline")
+            .bind_group_layout(&camera_layout)
+            .bind_group_layout(&sky_layout)
+            .vertex_layout(MeshVertex::desc())
+            .build(context);
+
+        let aspect = context.size().x / context.size().y;
+        let mut camera = OrbitCamera::new(context, 60.0, aspect, 0.1, 100.0);
+        camera
+            .set_polar(cgmath::point3(3.0, 0.4, 0.2))
+            .update(context);
+
+        Ok(Self {
+            sphere,
+            pipeline,
+            sky_bind_group,
+            skybox,
+            camera,
+            show_sky: true,
+        })
+    }
+}
+
+impl App for SkyboxApp {
+    fn input(&mut self, input: egui::InputState, context: &Context) {
+        self.camera.input(input, context);
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        self.camera
+            .set_aspect(new_width as f32 / new_height as f32)
+            .update(context);
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("Skybox").show(egui_ctx, |ui| {
+            ui.checkbox(&mut self.show_sky, "show the sky");
+            ui.label("drag to orbit, the sky stays at infinity");
+        });
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, self.camera.bind_group(), &[]);
+        render_pass.set_bind_group(1, &self.sky_bind_group, &[]);
+        self.sphere.draw(render_pass);
+        // Last, so the sky only shades what the sphere left uncovered
+        if self.show_sky {
+            self.skybox.draw(render_pass, self.camera.bind_group());
+        }
+    }
+}
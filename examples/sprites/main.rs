@@ -0,0 +1,19 @@
+mod sprites_app;
+
+use std::sync::Arc;
+
+use crate::sprites_app::SpritesApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Sprites App",
+        800,
+        600,
+        egui::Color32::from_rgb(30, 30, 40),
+        0,
+        0,
+        Box::new(|context| Arc::new(SpritesApp::new(context).unwrap())),
+    );
+    runner.run()
+}
@@ -0,0 +1,175 @@
+use wgpu_bootstrap::{
+    egui,
+    util::{
+        sprite_batch::{Origin, SpriteBatch, SpriteTexture},
+        texture::Texture,
+    },
+    wgpu, App, Context, ContextError,
+};
+
+const CELL: u32 = 32;
+const SPRITE_COUNT: usize = 200;
+const SPRITE_SIZE: f32 = 48.0;
+
+struct Sprite {
+    position: [f32; 2],
+    velocity: [f32; 2],
+    cell: u32,
+}
+
+// A 2x2 atlas, each cell a colored disc on a contrasting square
+fn atlas_pixels() -> Vec<u8> {
+    let size = 2 * CELL;
+    let colors = [
+        [[230, 80, 60], [250, 220, 200]],
+        [[60, 160, 90], [210, 240, 210]],
+        [[60, 100, 220], [210, 220, 250]],
+        [[230, 190, 40], [90, 60, 20]],
+    ];
+    let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let cell = (y / CELL * 2 + x / CELL) as usize;
+            let dx = (x % CELL) as f32 + 0.5 - CELL as f32 / 2.0;
+            let dy = (y % CELL) as f32 + 0.5 - CELL as f32 / 2.0;
+            let inside = dx * dx + dy * dy < (CELL as f32 * 0.35).powi(2);
+            let [r, g, b] = colors[cell][usize::from(!inside)];
+            pixels.extend_from_slice(&[r, g, b, 255]);
+        }
+    }
+    pixels
+}
+
+// Sprites from an atlas bouncing around the window, with a rounded panel and circles drawn
+// over them. The window shows how many draw calls the batch needed.
+pub struct SpritesApp {
+    sprite_batch: SpriteBatch,
+    atlas: SpriteTexture,
+    sprites: Vec<Sprite>,
+    origin: Origin,
+    sort_by_texture: bool,
+}
+
+impl SpritesApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let mut sprite_batch = SpriteBatch::new(context)?;
+        let texture = Texture::from_rgba(context, 2 * CELL, 2 * CELL, &atlas_pixels(), "Atlas")?;
+        let atlas = sprite_batch.add_texture(context, &texture);
+
+        let size = context.size();
+        let sprites = (0..SPRITE_COUNT)
+            .map(|i| {
+                // Spread without a random crate, the golden angle scatters them evenly enough
+                let t = i as f32 * 2.399_963;
+                Sprite {
+                    position: [
+                        (0.5 + 0.45 * (t * 0.7).sin()) * (size.x - SPRITE_SIZE),
+                        (0.5 + 0.45 * (t * 1.3).cos()) * (size.y - SPRITE_SIZE),
+                    ],
+                    velocity: [150.0 * t.cos(), 150.0 * t.sin()],
+                    cell: i as u32 % 4,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            sprite_batch,
+            atlas,
+            sprites,
+            origin: Origin::TopLeft,
+            sort_by_texture: false,
+        })
+    }
+}
+
+impl App for SpritesApp {
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("Sprites").show(egui_ctx, |ui| {
+            ui.radio_value(&mut self.origin, Origin::TopLeft, "top-left origin");
+            ui.radio_value(&mut self.origin, Origin::Center, "center origin");
+            ui.checkbox(&mut self.sort_by_texture, "sort by texture");
+            ui.label(format!("draw calls: {}", self.sprite_batch.batch_count()));
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        let size = context.size();
+        let bounds = [size.x - SPRITE_SIZE, size.y - SPRITE_SIZE];
+        for sprite in &mut self.sprites {
+            for ((position, velocity), bound) in sprite
+                .position
+                .iter_mut()
+                .zip(&mut sprite.velocity)
+                .zip(bounds)
+            {
+                *position += *velocity * delta_time;
+                if *position < 0.0 || *position > bound {
+                    *velocity = -*velocity;
+                    *position = position.clamp(0.0, bound);
+                }
+            }
+        }
+
+        // The scene is laid out from the top-left corner and only moved for the center origin,
+        // where y goes up, so it shows upside down there
+        let offset = match self.origin {
+            Origin::TopLeft => [0.0, 0.0],
+            Origin::Center => [-size.x / 2.0, -size.y / 2.0],
+        };
+        let batch = &mut self.sprite_batch;
+        batch
+            .set_origin(self.origin)
+            .set_sort_by_texture(self.sort_by_texture);
+        for (i, sprite) in self.sprites.iter().enumerate() {
+            let region = [
+                (sprite.cell % 2 * CELL) as f32,
+                (sprite.cell / 2 * CELL) as f32,
+                CELL as f32,
+                CELL as f32,
+            ];
+            let position = [
+                sprite.position[0] + offset[0],
+                sprite.position[1] + offset[1],
+            ];
+            batch.sprite_region(
+                self.atlas,
+                region,
+                position,
+                [SPRITE_SIZE, SPRITE_SIZE],
+                [1.0, 1.0, 1.0, 1.0],
+            );
+            // A shadow every few sprites interleaves the textures
+            if i % 10 == 0 {
+                batch.circle(
+                    [position[0] + SPRITE_SIZE, position[1] + SPRITE_SIZE],
+                    8.0,
+                    [0.0, 0.0, 0.0, 0.5],
+                );
+            }
+        }
+        batch
+            .rounded_rect(
+                [20.0 + offset[0], 20.0 + offset[1]],
+                [240.0, 120.0],
+                16.0,
+                [0.1, 0.1, 0.1, 0.7],
+            )
+            .rect(
+                [40.0 + offset[0], 60.0 + offset[1]],
+                [200.0, 4.0],
+                [1.0, 1.0, 1.0, 0.8],
+            );
+        for i in 0..5 {
+            batch.circle(
+                [60.0 + 40.0 * i as f32 + offset[0], 100.0 + offset[1]],
+                6.0 + 2.0 * i as f32,
+                [1.0, 0.6, 0.2, 1.0],
+            );
+        }
+        batch.flush(context);
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.sprite_batch.draw(render_pass);
+    }
+}
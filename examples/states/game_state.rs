@@ -0,0 +1,95 @@
+use wgpu_bootstrap::{
+    egui,
+    util::{
+        bind_group::BindGroupBuilder, pipeline::RenderPipelineBuilder,
+        uniform_buffer::UniformBuffer,
+    },
+    wgpu, App, Context, Transition,
+};
+
+use crate::{menu_state::MenuState, pause_state::PauseState};
+
+// A spinning triangle, it stops while the pause screen is on top since only the top state is
+// updated
+pub struct GameState {
+    angle: f32,
+    params: UniformBuffer<[f32; 4]>,
+    bind_group: wgpu::BindGroup,
+    render_pipeline: wgpu::RenderPipeline,
+    transition: Option<Transition>,
+}
+
+impl GameState {
+    pub fn new(context: &Context) -> Self {
+        let params =
+            UniformBuffer::with_label(context, [0.0, 1.0, 0.0, 0.0], "Params Buffer").unwrap();
+        let (layout, bind_group) = BindGroupBuilder::new()
+            .label("Params Bind Group")
+            .uniform(0, params.buffer(), wgpu::ShaderStages::VERTEX)
+            .build(context);
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            });
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Render Pipeline")
+            .bind_group_layout(&layout)
+            .cull_mode(None)
+            .build(context);
+
+        Self {
+            angle: 0.0,
+            params,
+            bind_group,
+            render_pipeline,
+            transition: None,
+        }
+    }
+}
+
+impl App for GameState {
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("Game").show(egui_ctx, |ui| {
+            ui.label("Escape pauses");
+            if ui.button("Menu").clicked() {
+                self.transition = Some(Transition::Replace(Box::new(MenuState::default())));
+            }
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        self.angle += delta_time;
+        let size = context.size();
+        self.params
+            .update(context.queue(), [self.angle, size.x / size.y, 0.0, 0.0]);
+    }
+
+    // Escape pauses instead of closing the window
+    fn event(&mut self, event: &egui::Event, _context: &Context) -> bool {
+        let escape = matches!(
+            event,
+            egui::Event::Key {
+                key: egui::Key::Escape,
+                pressed: true,
+                ..
+            }
+        );
+        if escape {
+            self.transition = Some(Transition::Push(Box::new(PauseState::default())));
+        }
+        escape
+    }
+
+    fn transition(&mut self, _context: &Context) -> Transition {
+        self.transition.take().unwrap_or(Transition::None)
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,21 @@
+mod game_state;
+mod menu_state;
+mod pause_state;
+
+use std::sync::Arc;
+
+use crate::menu_state::MenuState;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "States App",
+        800,
+        600,
+        egui::Color32::from_rgb(245, 245, 245),
+        0,
+        0,
+        Box::new(|_context| Arc::new(MenuState::default())),
+    );
+    runner.run()
+}
@@ -0,0 +1,33 @@
+use wgpu_bootstrap::{egui, App, Context, Transition};
+
+use crate::game_state::GameState;
+
+// Only a GUI: the game starts in place of the menu, quitting pops the last state
+#[derive(Default)]
+pub struct MenuState {
+    transition: Option<Transition>,
+}
+
+impl App for MenuState {
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::CentralPanel::default()
+            .frame(egui::Frame::none())
+            .show(egui_ctx, |ui| {
+                ui.vertical_centered(|ui| {
+                    ui.add_space(200.0);
+                    ui.heading("States App");
+                    if ui.button("Play").clicked() {
+                        self.transition =
+                            Some(Transition::Replace(Box::new(GameState::new(context))));
+                    }
+                    if ui.button("Quit").clicked() {
+                        self.transition = Some(Transition::Pop);
+                    }
+                });
+            });
+    }
+
+    fn transition(&mut self, _context: &Context) -> Transition {
+        self.transition.take().unwrap_or(Transition::None)
+    }
+}
@@ -0,0 +1,48 @@
+use wgpu_bootstrap::{egui, App, Context, Transition};
+
+// Drawn over the paused game, which keeps rendering its last frame
+#[derive(Default)]
+pub struct PauseState {
+    transition: Option<Transition>,
+}
+
+impl App for PauseState {
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        egui::Window::new("Paused")
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .collapsible(false)
+            .resizable(false)
+            .show(egui_ctx, |ui| {
+                if ui.button("Resume").clicked() {
+                    self.transition = Some(Transition::Pop);
+                }
+                if ui.button("Quit").clicked() {
+                    context.request_exit();
+                }
+            });
+    }
+
+    // Escape resumes instead of closing the window
+    fn event(&mut self, event: &egui::Event, _context: &Context) -> bool {
+        let escape = matches!(
+            event,
+            egui::Event::Key {
+                key: egui::Key::Escape,
+                pressed: true,
+                ..
+            }
+        );
+        if escape {
+            self.transition = Some(Transition::Pop);
+        }
+        escape
+    }
+
+    fn transition(&mut self, _context: &Context) -> Transition {
+        self.transition.take().unwrap_or(Transition::None)
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,23 @@
+// x is the angle in radians, y the aspect ratio
+@group(0) @binding(0)
+var<uniform> params: vec4<f32>;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let corner = f32(index) * 2.094395;
+    let angle = params.x + corner;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(0.5 * cos(angle) / params.y, 0.5 * sin(angle), 0.0, 1.0);
+    out.color = vec3<f32>(f32(index == 0u), f32(index == 1u), f32(index == 2u));
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(in.color, 1.0);
+}
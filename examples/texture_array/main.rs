@@ -0,0 +1,19 @@
+mod texture_array_app;
+
+use std::sync::Arc;
+
+use crate::texture_array_app::TextureArrayApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Texture Array App",
+        800,
+        600,
+        egui::Color32::from_rgb(30, 30, 40),
+        0,
+        0,
+        Box::new(|context| Arc::new(TextureArrayApp::new(context).unwrap())),
+    );
+    runner.run()
+}
@@ -0,0 +1,32 @@
+@group(0) @binding(0) var tiles: texture_2d_array<f32>;
+@group(0) @binding(1) var tiles_sampler: sampler;
+
+struct VertexInput {
+    @location(0) position: vec2<f32>,
+    @location(1) tex_coords: vec2<f32>,
+};
+
+struct InstanceInput {
+    @location(2) offset: vec2<f32>,
+    @location(3) layer: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+    @location(1) @interpolate(flat) layer: u32,
+};
+
+@vertex
+fn vs_main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.position + instance.offset, 0.0, 1.0);
+    out.tex_coords = model.tex_coords;
+    out.layer = instance.layer;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(tiles, tiles_sampler, in.tex_coords, in.layer);
+}
@@ -0,0 +1,172 @@
+use wgpu_bootstrap::{
+    egui, include_shader,
+    util::{
+        bind_group::BindGroupBuilder, instance_buffer::InstanceBuffer, mesh::GpuMesh,
+        pipeline::RenderPipelineBuilder, texture::Texture,
+    },
+    wgpu, App, Context, ContextError, Vertex,
+};
+
+const TILE: u32 = 32;
+const COLUMNS: u32 = 8;
+const ROWS: u32 = 6;
+// Seconds between each tile moving to the next layer
+const STEP: f32 = 0.5;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct TileVertex {
+    position: [f32; 2],
+    tex_coords: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 2)]
+    offset: [f32; 2],
+    layer: u32,
+}
+
+// One tile per layer: a pattern in its own color, so the layer of each tile is easy to tell
+fn tile_pixels(layer: u32) -> Vec<u8> {
+    let colors = [[230, 80, 60], [60, 160, 90], [60, 100, 220], [230, 190, 40]];
+    let [r, g, b] = colors[layer as usize];
+    let mut pixels = Vec::with_capacity((TILE * TILE * 4) as usize);
+    for y in 0..TILE {
+        for x in 0..TILE {
+            let on = match layer {
+                0 => (x / 8 + y / 8) % 2 == 0,
+                1 => (y / 4) % 2 == 0,
+                2 => (x / 4) % 2 == 0,
+                _ => x.abs_diff(TILE / 2) + y.abs_diff(TILE / 2) < TILE / 2,
+            };
+            let shade = if on { 255 } else { 90 };
+            pixels.extend_from_slice(&[
+                (r * shade / 255) as u8,
+                (g * shade / 255) as u8,
+                (b * shade / 255) as u8,
+                255,
+            ]);
+        }
+    }
+    pixels
+}
+
+// A grid of tiles drawn with one instanced call, each instance picking its layer of a 2D array
+// texture. The layers shift along the grid over time.
+pub struct TextureArrayApp {
+    quad: GpuMesh,
+    instances: Vec<Instance>,
+    instance_buffer: InstanceBuffer<Instance>,
+    tiles: Texture,
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    elapsed: f32,
+    animate: bool,
+}
+
+impl TextureArrayApp {
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let layers: Vec<Vec<u8>> = (0..4).map(tile_pixels).collect();
+        let layers: Vec<&[u8]> = layers.iter().map(Vec::as_slice).collect();
+        let tiles = Texture::from_rgba_layers(context, TILE, TILE, &layers, "Tiles")?;
+
+        // One cell of the grid in clip space, with a small gap between tiles
+        let width = 2.0 / COLUMNS as f32 * 0.9;
+        let height = 2.0 / ROWS as f32 * 0.9;
+        let vertices = [
+            TileVertex {
+                position: [0.0, 0.0],
+                tex_coords: [0.0, 1.0],
+            },
+            TileVertex {
+                position: [width, 0.0],
+                tex_coords: [1.0, 1.0],
+            },
+            TileVertex {
+                position: [width, height],
+                tex_coords: [1.0, 0.0],
+            },
+            TileVertex {
+                position: [0.0, height],
+                tex_coords: [0.0, 0.0],
+            },
+        ];
+        let quad = GpuMesh::new(context, &vertices, &[0, 1, 2, 0, 2, 3]);
+
+        let instances: Vec<Instance> = (0..ROWS)
+            .flat_map(|row| (0..COLUMNS).map(move |column| (column, row)))
+            .map(|(column, row)| Instance {
+                offset: [
+                    -1.0 + 2.0 * column as f32 / COLUMNS as f32 + 0.05 * 2.0 / COLUMNS as f32,
+                    -1.0 + 2.0 * row as f32 / ROWS as f32 + 0.05 * 2.0 / ROWS as f32,
+                ],
+                layer: 0,
+            })
+            .collect();
+        let instance_buffer = InstanceBuffer::new(context, &instances);
+
+        let (layout, bind_group) = BindGroupBuilder::new()
+            .label("Tiles Bind Group")
+            .texture_array(0, tiles.view(), wgpu::ShaderStages::FRAGMENT)
+            .sampler(1, tiles.sampler(), wgpu::ShaderStages::FRAGMENT)
+            .build(context);
+        let shader = include_shader!(context, "shader.wgsl")?;
+        let pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Tiles Pipeline")
+            .bind_group_layout(&layout)
+            .vertex_layout(TileVertex::desc())
+            .vertex_layout(Instance::desc())
+            .build(context);
+
+        let mut app = Self {
+            quad,
+            instances,
+            instance_buffer,
+            tiles,
+            pipeline,
+            bind_group,
+            elapsed: 0.0,
+            animate: true,
+        };
+        app.update_layers(context);
+        Ok(app)
+    }
+
+    fn update_layers(&mut self, context: &Context) {
+        let step = (self.elapsed / STEP) as u32;
+        let layer_count = self.tiles.layer_count();
+        for (i, instance) in self.instances.iter_mut().enumerate() {
+            let (column, row) = (i as u32 % COLUMNS, i as u32 / COLUMNS);
+            instance.layer = (column + row + step) % layer_count;
+        }
+        self.instance_buffer
+            .update(context.queue(), &self.instances);
+    }
+}
+
+impl App for TextureArrayApp {
+    fn render_gui(&mut self, egui_ctx: &egui::Context, _context: &Context) {
+        egui::Window::new("Texture array").show(egui_ctx, |ui| {
+            ui.label(format!("layers: {}", self.tiles.layer_count()));
+            ui.checkbox(&mut self.animate, "animate");
+        });
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        if self.animate {
+            self.elapsed += delta_time;
+            self.update_layers(context);
+        }
+    }
+
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(1, self.instance_buffer.buffer().slice(..));
+        self.quad
+            .draw_instanced(render_pass, 0..self.instance_buffer.count());
+    }
+}
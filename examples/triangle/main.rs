@@ -3,9 +3,9 @@ mod triangle_app;
 use std::sync::Arc;
 
 use crate::triangle_app::TriangleApp;
-use wgpu_bootstrap::{egui, Runner};
+use wgpu_bootstrap::{egui, ContextError, Runner};
 
-fn main() {
+fn main() -> Result<(), ContextError> {
     let mut runner = Runner::new(
         "Triangle App",
         800,
@@ -15,5 +15,5 @@ fn main() {
         0,
         Box::new(|context| Arc::new(TriangleApp::new(context))),
     );
-    runner.run();
+    runner.run()
 }
@@ -0,0 +1,26 @@
+// Same triangle as the triangle example, see index.html to run it in a browser
+#[path = "../triangle/triangle_app.rs"]
+mod triangle_app;
+
+use std::sync::Arc;
+
+use crate::triangle_app::TriangleApp;
+use wgpu_bootstrap::{egui, ContextError, Runner};
+
+fn main() -> Result<(), ContextError> {
+    let mut runner = Runner::new(
+        "Web App",
+        800,
+        600,
+        egui::Color32::from_rgb(245, 245, 245),
+        0,
+        0,
+        Box::new(|context| Arc::new(TriangleApp::new(context))),
+    );
+
+    #[cfg(target_arch = "wasm32")]
+    return runner.run_web("canvas");
+
+    #[cfg(not(target_arch = "wasm32"))]
+    runner.run()
+}
@@ -3,9 +3,9 @@ mod wireframe_app;
 use std::sync::Arc;
 
 use crate::wireframe_app::WireframeApp;
-use wgpu_bootstrap::{egui, Runner};
+use wgpu_bootstrap::{egui, ContextError, Runner};
 
-fn main() {
+fn main() -> Result<(), ContextError> {
     let mut runner = Runner::new(
         "Wireframe App",
         800,
@@ -15,5 +15,5 @@ fn main() {
         0,
         Box::new(|context| Arc::new(WireframeApp::new(context))),
     );
-    runner.run();
+    runner.run()
 }
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use eframe::egui::{Key, PointerButton};
+
+use crate::context::ContextError;
+
+// Names of the mouse buttons in Bindings, keys use Key::name
+const MOUSE_BUTTONS: [(PointerButton, &str); 5] = [
+    (PointerButton::Primary, "MousePrimary"),
+    (PointerButton::Secondary, "MouseSecondary"),
+    (PointerButton::Middle, "MouseMiddle"),
+    (PointerButton::Extra1, "MouseExtra1"),
+    (PointerButton::Extra2, "MouseExtra2"),
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Binding {
+    Key(Key),
+    Mouse(PointerButton),
+}
+
+impl Binding {
+    // "W", "Space", "Up"... for keys, "MousePrimary" to "MouseExtra2" for buttons
+    pub fn name(&self) -> &'static str {
+        match self {
+            Binding::Key(key) => key.name(),
+            Binding::Mouse(button) => MOUSE_BUTTONS
+                .iter()
+                .find(|(b, _)| b == button)
+                .map(|(_, name)| *name)
+                .unwrap(),
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        MOUSE_BUTTONS
+            .iter()
+            .find(|(_, n)| *n == name)
+            .map(|(button, _)| Binding::Mouse(*button))
+            .or_else(|| Key::from_name(name).map(Binding::Key))
+    }
+}
+
+impl From<Key> for Binding {
+    fn from(key: Key) -> Self {
+        Binding::Key(key)
+    }
+}
+
+impl From<PointerButton> for Binding {
+    fn from(button: PointerButton) -> Self {
+        Binding::Mouse(button)
+    }
+}
+
+// Named actions ("move_forward", "jump") and the keys or buttons bound to them, queried with
+// Input::is_action_active and Input::was_action_pressed. Give the defaults to
+// Runner::set_actions, rebind at runtime with Input::actions_mut.
+#[derive(Clone, Debug, Default)]
+pub struct ActionMap {
+    // In the order the actions were first bound, for settings screens
+    actions: Vec<(String, Vec<Binding>)>,
+}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Adds a binding, any of them activates the action
+    pub fn bind(&mut self, action: &str, binding: impl Into<Binding>) -> &mut Self {
+        let binding = binding.into();
+        let bindings = self.entry(action);
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        self
+    }
+
+    // Replaces every binding of the action
+    pub fn rebind(&mut self, action: &str, binding: impl Into<Binding>) -> &mut Self {
+        *self.entry(action) = vec![binding.into()];
+        self
+    }
+
+    // The action stays listed, bound to nothing
+    pub fn unbind(&mut self, action: &str) -> &mut Self {
+        self.entry(action).clear();
+        self
+    }
+
+    // Empty for unknown actions
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions
+            .iter()
+            .find(|(name, _)| name == action)
+            .map_or(&[], |(_, bindings)| bindings)
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.iter().map(|(name, _)| name.as_str())
+    }
+
+    // What to save, see Bindings
+    pub fn to_bindings(&self) -> Bindings {
+        Bindings {
+            actions: self
+                .actions
+                .iter()
+                .map(|(name, bindings)| {
+                    let names = bindings
+                        .iter()
+                        .map(|binding| String::from(binding.name()))
+                        .collect();
+                    (name.clone(), names)
+                })
+                .collect(),
+        }
+    }
+
+    // Actions already in the map and missing from the bindings keep theirs, so saved bindings
+    // can be applied over the defaults. Nothing changes when a name is unknown.
+    pub fn apply_bindings(&mut self, bindings: &Bindings) -> Result<&mut Self, ContextError> {
+        let mut parsed = Vec::with_capacity(bindings.actions.len());
+        for (action, names) in &bindings.actions {
+            let action_bindings = names
+                .iter()
+                .map(|name| {
+                    Binding::from_name(name)
+                        .ok_or_else(|| ContextError::UnknownBinding(name.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            parsed.push((action, action_bindings));
+        }
+        for (action, action_bindings) in parsed {
+            *self.entry(action) = action_bindings;
+        }
+        Ok(self)
+    }
+
+    fn entry(&mut self, action: &str) -> &mut Vec<Binding> {
+        let index = match self.actions.iter().position(|(name, _)| name == action) {
+            Some(index) => index,
+            None => {
+                self.actions.push((String::from(action), Vec::new()));
+                self.actions.len() - 1
+            }
+        };
+        &mut self.actions[index].1
+    }
+}
+
+// Plain names for saving an ActionMap in a settings file: action to key and button names, see
+// Binding::name. Serializable with the serde feature, as a map of string lists.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bindings {
+    pub actions: BTreeMap<String, Vec<String>>,
+}
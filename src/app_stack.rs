@@ -0,0 +1,172 @@
+use std::sync::Arc;
+
+use eframe::{
+    egui::{self, InputState},
+    wgpu,
+};
+
+#[cfg(feature = "gamepad")]
+use crate::gamepad::GamepadEvent;
+use crate::{context::Context, pass_list::PassList, runner::App};
+
+// What App::transition asks of the stack of states, applied by the runner at the start of the
+// next frame
+pub enum Transition {
+    None,
+    // The new state goes on top, the current one stops being updated until it's on top again
+    Push(Box<dyn App + Send + Sync>),
+    // Back to the state below, the window closes when there's none
+    Pop,
+    // Swaps the current state for the new one, the states below don't change
+    Replace(Box<dyn App + Send + Sync>),
+}
+
+impl std::fmt::Debug for Transition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Transition::None => write!(f, "None"),
+            Transition::Push(_) => write!(f, "Push"),
+            Transition::Pop => write!(f, "Pop"),
+            Transition::Replace(_) => write!(f, "Replace"),
+        }
+    }
+}
+
+// The states of an app (menu, gameplay, pause screen), each an App of its own. Only the top one
+// gets input, updates, the GUI and the encoder callbacks. render draws the top one, and the ones
+// below it as long as the state above is an overlay (App::is_overlay), lowest first in the same
+// render pass. Every state gets resize and on_device_lost, so one coming back on top already
+// has its targets at the window size. The runner always keeps the app it creates in one.
+pub struct AppStack {
+    // Never shared, Arc only so the runner's app can start the stack
+    states: Vec<Arc<dyn App + Send + Sync>>,
+    pending: Transition,
+}
+
+impl AppStack {
+    pub fn new(app: Arc<dyn App + Send + Sync>) -> Self {
+        Self {
+            states: vec![app],
+            pending: Transition::None,
+        }
+    }
+
+    // Applies the transition the top state returned from its last update. Returns false once
+    // the last state is popped, the runner then closes the window.
+    pub fn apply_transition(&mut self) -> bool {
+        match std::mem::replace(&mut self.pending, Transition::None) {
+            Transition::None => {}
+            Transition::Push(state) => self.states.push(Arc::from(state)),
+            Transition::Pop => {
+                self.states.pop();
+            }
+            Transition::Replace(state) => {
+                self.states.pop();
+                self.states.push(Arc::from(state));
+            }
+        }
+        !self.states.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+
+    fn top_mut(&mut self) -> Option<&mut (dyn App + Send + Sync + 'static)> {
+        self.states
+            .last_mut()
+            .map(|state| Arc::get_mut(state).unwrap())
+    }
+
+    fn each_mut(&mut self) -> impl Iterator<Item = &mut (dyn App + Send + Sync + 'static)> {
+        self.states
+            .iter_mut()
+            .map(|state| Arc::get_mut(state).unwrap())
+    }
+}
+
+impl App for AppStack {
+    fn render(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        // From the topmost state that isn't an overlay
+        let first = self
+            .states
+            .iter()
+            .rposition(|state| !state.is_overlay())
+            .unwrap_or(0);
+        for state in &self.states[first..] {
+            state.render(render_pass);
+        }
+    }
+
+    fn render_gui(&mut self, egui_ctx: &egui::Context, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.render_gui(egui_ctx, context);
+        }
+    }
+
+    fn update(&mut self, delta_time: f32, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.update(delta_time, context);
+            let transition = state.transition(context);
+            self.pending = transition;
+        }
+    }
+
+    fn fixed_update(&mut self, fixed_delta_time: f32, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.fixed_update(fixed_delta_time, context);
+        }
+    }
+
+    fn prepare(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.prepare(encoder, context);
+        }
+    }
+
+    fn passes<'a>(&'a mut self, passes: &mut PassList<'a>, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.passes(passes, context);
+        }
+    }
+
+    fn post_process(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.post_process(encoder, context);
+        }
+    }
+
+    fn input(&mut self, input: InputState, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.input(input, context);
+        }
+    }
+
+    fn event(&mut self, event: &egui::Event, context: &Context) -> bool {
+        self.top_mut()
+            .is_some_and(|state| state.event(event, context))
+    }
+
+    #[cfg(feature = "gamepad")]
+    fn gamepad_event(&mut self, event: &GamepadEvent, context: &Context) {
+        if let Some(state) = self.top_mut() {
+            state.gamepad_event(event, context);
+        }
+    }
+
+    fn resize(&mut self, new_width: u32, new_height: u32, context: &Context) {
+        for state in self.each_mut() {
+            state.resize(new_width, new_height, context);
+        }
+    }
+
+    fn on_device_lost(&mut self, context: &Context) {
+        for state in self.each_mut() {
+            state.on_device_lost(context);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::{Path, PathBuf};
+
+use eframe::wgpu;
+
+use crate::context::{Context, ContextError};
+
+// A relative path is looked up in the asset root (Runner::set_asset_root), then next to the
+// executable, then in the working directory. The first file that exists wins.
+pub fn resolve_path(context: &Context, path: impl AsRef<Path>) -> Result<PathBuf, ContextError> {
+    let path = path.as_ref();
+    if path.is_absolute() {
+        return Ok(path.to_path_buf());
+    }
+    let mut candidates = Vec::new();
+    if let Some(root) = context.asset_root() {
+        candidates.push(root.join(path));
+    }
+    if let Some(dir) = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+    {
+        candidates.push(dir.join(path));
+    }
+    candidates.push(path.to_path_buf());
+
+    match candidates.iter().find(|candidate| candidate.is_file()) {
+        Some(found) => Ok(found.clone()),
+        None => Err(ContextError::AssetNotFound(path.to_path_buf(), candidates)),
+    }
+}
+
+pub fn load_bytes(context: &Context, path: impl AsRef<Path>) -> Result<Vec<u8>, ContextError> {
+    let path = resolve_path(context, path)?;
+    std::fs::read(&path)
+        .map_err(|err| ContextError::Other(format!("failed to read {}: {err}", path.display())))
+}
+
+pub fn load_string(context: &Context, path: impl AsRef<Path>) -> Result<String, ContextError> {
+    read_string(&resolve_path(context, path)?)
+}
+
+// A WGSL file found by resolve_path
+pub fn load_shader(
+    context: &Context,
+    path: impl AsRef<Path>,
+) -> Result<wgpu::ShaderModule, ContextError> {
+    let path = resolve_path(context, path)?;
+    shader_from_source(context, &path.display().to_string(), &read_string(&path)?)
+}
+
+// Compile errors come back as ContextError::ShaderCompile with the name instead of going to
+// wgpu's default handler, which panics
+pub fn shader_from_source(
+    context: &Context,
+    name: &str,
+    source: &str,
+) -> Result<wgpu::ShaderModule, ContextError> {
+    context
+        .device()
+        .push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = context
+        .device()
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+    match pollster::block_on(context.device().pop_error_scope()) {
+        Some(err) => Err(ContextError::ShaderCompile(
+            String::from(name),
+            err.to_string(),
+        )),
+        None => Ok(module),
+    }
+}
+
+// Used by include_shader: the file is read again in debug builds, so shader edits show up on
+// the next run without recompiling, and the embedded source is used when it can't be read
+#[doc(hidden)]
+pub fn included_shader(
+    context: &Context,
+    manifest_dir: &str,
+    source_file: &str,
+    path: &str,
+    embedded: &str,
+) -> Result<wgpu::ShaderModule, ContextError> {
+    let file = Path::new(manifest_dir)
+        .join(source_file)
+        .with_file_name(path);
+    let name = file.display().to_string();
+    if cfg!(debug_assertions) {
+        if let Ok(source) = std::fs::read_to_string(&file) {
+            return shader_from_source(context, &name, &source);
+        }
+    }
+    shader_from_source(context, &name, embedded)
+}
+
+// Embeds a WGSL file like include_str (the path is relative to the calling file) and compiles
+// it, returns Result<wgpu::ShaderModule, ContextError>. Release builds only use the embedded
+// source, so the shader doesn't have to ship with the executable.
+#[macro_export]
+macro_rules! include_shader {
+    ($context:expr, $path:literal) => {
+        $crate::assets::included_shader(
+            $context,
+            env!("CARGO_MANIFEST_DIR"),
+            file!(),
+            $path,
+            include_str!($path),
+        )
+    };
+}
+
+fn read_string(path: &Path) -> Result<String, ContextError> {
+    std::fs::read_to_string(path)
+        .map_err(|err| ContextError::Other(format!("failed to read {}: {err}", path.display())))
+}
@@ -0,0 +1,143 @@
+use std::{
+    cell::RefCell,
+    fs::File,
+    path::{Path, PathBuf},
+};
+
+use eframe::wgpu;
+
+use crate::context::ContextError;
+
+// A frame asked for with Context::capture_frame, saved once it has been presented. How that went
+// waits for Context::take_capture_result.
+#[derive(Default)]
+pub(crate) struct FrameCapture {
+    pending: RefCell<Option<PathBuf>>,
+    result: RefCell<Option<Result<PathBuf, ContextError>>>,
+}
+
+impl FrameCapture {
+    // Creates the file right away, a path that can't be written fails here and not a frame later
+    pub(crate) fn request(&self, path: PathBuf) -> Result<(), ContextError> {
+        File::create(&path).map_err(|err| {
+            ContextError::Other(format!("failed to create {}: {err}", path.display()))
+        })?;
+        *self.pending.borrow_mut() = Some(path);
+        Ok(())
+    }
+
+    pub(crate) fn take_pending(&self) -> Option<PathBuf> {
+        self.pending.borrow_mut().take()
+    }
+
+    // Errors are logged too, for the apps that never ask
+    pub(crate) fn finish(&self, path: PathBuf, result: Result<(), ContextError>) {
+        if let Err(err) = &result {
+            log::error!("{err}");
+        }
+        *self.result.borrow_mut() = Some(result.map(|()| path));
+    }
+
+    pub(crate) fn take_result(&self) -> Option<Result<PathBuf, ContextError>> {
+        self.result.borrow_mut().take()
+    }
+}
+
+pub(crate) fn save_png(
+    path: &Path,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> Result<(), ContextError> {
+    image::save_buffer_with_format(
+        path,
+        rgba,
+        width,
+        height,
+        image::ExtendedColorType::Rgba8,
+        image::ImageFormat::Png,
+    )
+    .map_err(ContextError::Image)?;
+    log::info!("frame saved to {}", path.display());
+    Ok(())
+}
+
+// Copies a COPY_SRC texture to a mappable buffer and waits for it, returning tightly packed
+// RGBA rows whatever the 8 bit channel order of the texture
+pub(crate) fn read_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+) -> Result<Vec<u8>, ContextError> {
+    let format = texture.format();
+    let bgra = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        _ => {
+            return Err(ContextError::UnsupportedFormat(
+                format,
+                String::from("only 8 bit RGBA and BGRA frames can be captured"),
+            ))
+        }
+    };
+
+    let (width, height) = (texture.width(), texture.height());
+    let unpadded_bytes_per_row = 4 * width as usize;
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Capture Buffer"),
+        size: (padded_bytes_per_row * height as usize) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Capture Encoder"),
+    });
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row as u32),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    map_read(device, &slice)?;
+
+    let mut rgba = Vec::with_capacity(unpadded_bytes_per_row * height as usize);
+    for row in slice.get_mapped_range().chunks_exact(padded_bytes_per_row) {
+        rgba.extend_from_slice(&row[..unpadded_bytes_per_row]);
+    }
+    buffer.unmap();
+    if bgra {
+        for pixel in rgba.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    Ok(rgba)
+}
+
+// Maps the slice for reading and blocks until it's done
+pub(crate) fn map_read(
+    device: &wgpu::Device,
+    slice: &wgpu::BufferSlice,
+) -> Result<(), ContextError> {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    slice.map_async(wgpu::MapMode::Read, move |result| {
+        let _ = sender.send(result);
+    });
+    device.poll(wgpu::Maintain::Wait);
+    // The callback always runs once polling waited for the queue
+    receiver
+        .recv()
+        .map_err(|err| ContextError::Other(err.to_string()))?
+        .map_err(ContextError::MapBuffer)
+}
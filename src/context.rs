@@ -0,0 +1,669 @@
+use eframe::{
+    egui,
+    egui_wgpu::WgpuError,
+    wgpu::{self, util::DeviceExt},
+};
+use std::{
+    cell::Cell,
+    fmt,
+    ops::{Bound, RangeBounds},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+#[cfg(feature = "gamepad")]
+use crate::gamepad::Gamepads;
+use crate::{
+    capture::{map_read, FrameCapture},
+    input::Input,
+    resources::{ResourceReport, ResourceTracker},
+    runner::FpsCounter,
+    time::Time,
+    uploader::Uploader,
+    util::render_target::RenderTarget,
+};
+
+#[derive(Debug)]
+pub enum ContextError {
+    NoAdapter,
+    AdapterNotFound(String),
+    AdapterNotSelected(String, String),
+    RequestDevice(wgpu::RequestDeviceError),
+    CreateSurface(wgpu::CreateSurfaceError),
+    MissingFeatures(wgpu::Features),
+    FeatureNotEnabled(wgpu::Features),
+    DownlevelNotSupported(wgpu::DownlevelFlags),
+    UnsupportedLimits(Vec<String>),
+    UnsupportedSampleCount(u32),
+    UnsupportedDepthFormat(wgpu::TextureFormat),
+    InvalidFrameLatency(u32),
+    InvalidFixedTimestep(f32),
+    SurfaceFormat(wgpu::TextureFormat),
+    // The format asked for, then the one the surface got
+    SurfaceFormatMismatch(wgpu::TextureFormat, wgpu::TextureFormat),
+    // A zero size, or data that doesn't match the size given
+    InvalidSize(String),
+    // A range or count past what the resource or device allows
+    OutOfRange(String),
+    InvalidHierarchy(String),
+    // The format, then what it's missing
+    UnsupportedFormat(wgpu::TextureFormat, String),
+    // A texture without the usages, dimension or sample count an operation needs
+    InvalidTexture(String),
+    BackendNotSupported(wgpu::Backend, String),
+    AlreadyRan,
+    UniformSize(usize),
+    TargetMismatch(String),
+    Image(image::ImageError),
+    Obj(tobj::LoadError),
+    Gltf(gltf::Error),
+    MapBuffer(wgpu::BufferAsyncError),
+    InvalidSampler(String),
+    ShaderCompile(String, String),
+    AssetNotFound(PathBuf, Vec<PathBuf>),
+    UnknownBinding(String),
+    Reflection(String),
+    NoWindow,
+    Other(String),
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContextError::NoAdapter => write!(f, "no compatible GPU adapter found"),
+            ContextError::AdapterNotFound(selector) => {
+                write!(f, "no GPU adapter matches {selector}")
+            }
+            ContextError::AdapterNotSelected(requested, picked) => {
+                write!(
+                    f,
+                    "adapter {requested} can't present to the window, {picked} would be used instead"
+                )
+            }
+            ContextError::RequestDevice(err) => write!(f, "failed to request device: {err}"),
+            ContextError::CreateSurface(err) => write!(f, "failed to create surface: {err}"),
+            ContextError::MissingFeatures(features) => {
+                write!(f, "features not supported by the adapter: {features:?}")
+            }
+            ContextError::FeatureNotEnabled(features) => {
+                write!(
+                    f,
+                    "features not enabled on the device: {features:?}, request them with Runner::set_features"
+                )
+            }
+            ContextError::DownlevelNotSupported(flags) => {
+                write!(f, "capabilities not supported by the adapter: {flags:?}")
+            }
+            ContextError::UnsupportedLimits(limits) => {
+                write!(
+                    f,
+                    "limits not supported by the adapter: {}",
+                    limits.join(", ")
+                )
+            }
+            ContextError::UnsupportedSampleCount(count) => {
+                write!(f, "MSAA sample count not supported: {count}")
+            }
+            ContextError::UnsupportedDepthFormat(format) => {
+                write!(f, "not a supported depth format: {format:?}")
+            }
+            ContextError::InvalidFixedTimestep(fixed_delta_time) => {
+                write!(
+                    f,
+                    "fixed timestep must be a positive number of seconds, got {fixed_delta_time}"
+                )
+            }
+            ContextError::InvalidFrameLatency(latency) => {
+                write!(f, "frame latency must be at least 1, got {latency}")
+            }
+            ContextError::SurfaceFormat(format) => {
+                write!(
+                    f,
+                    "surface format {format:?} doesn't match the requested sRGB setting"
+                )
+            }
+            ContextError::SurfaceFormatMismatch(requested, actual) => {
+                write!(
+                    f,
+                    "surface format is {actual:?}, {requested:?} was requested"
+                )
+            }
+            ContextError::InvalidSize(msg) => write!(f, "invalid size: {msg}"),
+            ContextError::OutOfRange(msg) => write!(f, "out of range: {msg}"),
+            ContextError::InvalidHierarchy(msg) => write!(f, "invalid scene hierarchy: {msg}"),
+            ContextError::UnsupportedFormat(format, msg) => {
+                write!(f, "unsupported format {format:?}: {msg}")
+            }
+            ContextError::InvalidTexture(msg) => write!(f, "invalid texture: {msg}"),
+            ContextError::BackendNotSupported(backend, msg) => {
+                write!(f, "not supported on the {backend:?} backend: {msg}")
+            }
+            ContextError::AlreadyRan => write!(f, "the runner already ran"),
+            ContextError::UniformSize(size) => {
+                write!(
+                    f,
+                    "uniform type is {size} bytes, uniform buffers need a non-zero multiple of 16"
+                )
+            }
+            ContextError::TargetMismatch(msg) => {
+                write!(f, "pipeline doesn't match the render target: {msg}")
+            }
+            ContextError::Image(err) => write!(f, "failed to load image: {err}"),
+            ContextError::Obj(err) => write!(f, "failed to load OBJ file: {err}"),
+            ContextError::Gltf(err) => write!(f, "failed to load glTF file: {err}"),
+            ContextError::MapBuffer(err) => write!(f, "failed to map buffer: {err}"),
+            ContextError::InvalidSampler(msg) => write!(f, "invalid sampler: {msg}"),
+            ContextError::ShaderCompile(name, err) => write!(f, "failed to compile {name}: {err}"),
+            ContextError::AssetNotFound(path, candidates) => {
+                let candidates: Vec<String> = candidates
+                    .iter()
+                    .map(|candidate| candidate.display().to_string())
+                    .collect();
+                write!(
+                    f,
+                    "asset {} not found, looked for {}",
+                    path.display(),
+                    candidates.join(", ")
+                )
+            }
+            ContextError::UnknownBinding(name) => {
+                write!(f, "unknown key or button \"{name}\" in the bindings")
+            }
+            ContextError::Reflection(msg) => write!(f, "failed to reflect the shader: {msg}"),
+            ContextError::NoWindow => write!(f, "failed to create a window"),
+            ContextError::Other(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ContextError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ContextError::RequestDevice(err) => Some(err),
+            ContextError::CreateSurface(err) => Some(err),
+            ContextError::Image(err) => Some(err),
+            ContextError::Obj(err) => Some(err),
+            ContextError::Gltf(err) => Some(err),
+            ContextError::MapBuffer(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<eframe::Error> for ContextError {
+    fn from(err: eframe::Error) -> Self {
+        match err {
+            eframe::Error::AppCreation(err) => match err.downcast::<ContextError>() {
+                Ok(err) => *err,
+                Err(err) => ContextError::Other(err.to_string()),
+            },
+            eframe::Error::Wgpu(WgpuError::NoSuitableAdapterFound) => ContextError::NoAdapter,
+            eframe::Error::Wgpu(WgpuError::RequestDeviceError(err)) => {
+                ContextError::RequestDevice(err)
+            }
+            eframe::Error::Wgpu(WgpuError::CreateSurfaceError(err)) => {
+                ContextError::CreateSurface(err)
+            }
+            eframe::Error::Winit(err) => {
+                log::error!("{err}");
+                ContextError::NoWindow
+            }
+            eframe::Error::WinitEventLoop(err) => {
+                log::error!("{err}");
+                ContextError::NoWindow
+            }
+            err => ContextError::Other(err.to_string()),
+        }
+    }
+}
+
+// Element types accepted by Context::create_index_buffer
+pub trait IndexType: bytemuck::Pod {
+    const FORMAT: wgpu::IndexFormat;
+}
+
+impl IndexType for u16 {
+    const FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint16;
+}
+
+impl IndexType for u32 {
+    const FORMAT: wgpu::IndexFormat = wgpu::IndexFormat::Uint32;
+}
+
+pub struct Context<'a> {
+    pub(crate) device: &'a wgpu::Device,
+    pub(crate) queue: &'a wgpu::Queue,
+    pub(crate) size: egui::Vec2,
+    pub(crate) scale_factor: f32,
+    pub(crate) format: wgpu::TextureFormat,
+    pub(crate) surface_format: Option<wgpu::TextureFormat>,
+    pub(crate) present_mode: Option<wgpu::PresentMode>,
+    pub(crate) depth_stencil_format: Option<wgpu::TextureFormat>,
+    pub(crate) sample_count: u32,
+    pub(crate) time: Time,
+    pub(crate) fps_counter: &'a FpsCounter,
+    pub(crate) input: &'a Input,
+    pub(crate) egui_ctx: Option<&'a egui::Context>,
+    pub(crate) capture: &'a FrameCapture,
+    pub(crate) clear_color: &'a Cell<wgpu::Color>,
+    pub(crate) exposure: &'a Cell<f32>,
+    pub(crate) adapter: &'a wgpu::Adapter,
+    pub(crate) adapter_info: &'a wgpu::AdapterInfo,
+    pub(crate) downlevel_flags: wgpu::DownlevelFlags,
+    pub(crate) uploader: &'a Uploader,
+    pub(crate) resources: &'a ResourceTracker,
+    pub(crate) hdr_target: Option<&'a RenderTarget>,
+    pub(crate) letterbox: Option<egui::Rect>,
+    pub(crate) asset_root: Option<&'a Path>,
+    #[cfg(feature = "gamepad")]
+    pub(crate) gamepads: &'a Gamepads,
+}
+
+// How a context was set up, returned by Context::describe to attach to bug reports.
+// Serializable with the serde feature, Runner::set_description sets up a runner the same way.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContextDescription {
+    // Its backend too
+    pub adapter: wgpu::AdapterInfo,
+    // The mode asked of the runner, None when headless like surface_format
+    pub present_mode: Option<wgpu::PresentMode>,
+    pub surface_format: Option<wgpu::TextureFormat>,
+    // What the app renders into, HDR_FORMAT with hdr
+    pub format: wgpu::TextureFormat,
+    pub hdr: bool,
+    pub depth_stencil_format: Option<wgpu::TextureFormat>,
+    pub sample_count: u32,
+    pub features: wgpu::Features,
+    pub limits: wgpu::Limits,
+}
+
+// Validation and debug info follow the build, on with debug assertions, and WGPU_VALIDATION or
+// WGPU_DEBUG override them ("0" turns one off). eframe creates the instance of the window with
+// the build defaults, so only HeadlessContext and list_adapters read the variables.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn instance_flags() -> wgpu::InstanceFlags {
+    wgpu::InstanceFlags::from_build_config().with_env()
+}
+
+impl<'a> Context<'a> {
+    pub fn device(&self) -> &wgpu::Device {
+        self.device
+    }
+
+    pub fn queue(&self) -> &wgpu::Queue {
+        self.queue
+    }
+
+    // Everything the backends can see, whether or not it can present to a window
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn list_adapters(backends: wgpu::Backends) -> Vec<wgpu::AdapterInfo> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            flags: instance_flags(),
+            ..Default::default()
+        });
+        instance
+            .enumerate_adapters(backends)
+            .iter()
+            .map(wgpu::Adapter::get_info)
+            .collect()
+    }
+
+    // GPU, backend and driver picked at startup
+    pub fn adapter_info(&self) -> &wgpu::AdapterInfo {
+        self.adapter_info
+    }
+
+    // What wgpu validates textures of the format against: the adapter's own capabilities when
+    // TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES is enabled, the ones WebGPU guarantees otherwise
+    pub fn texture_format_features(
+        &self,
+        format: wgpu::TextureFormat,
+    ) -> wgpu::TextureFormatFeatures {
+        let features = self.device.features();
+        if features.contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES) {
+            self.adapter.get_texture_format_features(format)
+        } else {
+            format.guaranteed_format_features(features)
+        }
+    }
+
+    pub fn describe(&self) -> ContextDescription {
+        ContextDescription {
+            adapter: self.adapter_info.clone(),
+            present_mode: self.present_mode,
+            surface_format: self.surface_format,
+            format: self.format,
+            hdr: self.hdr_target.is_some(),
+            depth_stencil_format: self.depth_stencil_format,
+            sample_count: self.sample_count,
+            features: self.device.features(),
+            limits: self.device.limits(),
+        }
+    }
+
+    // What the adapter can do beyond WebGL2-level hardware (compute, indirect draws...)
+    pub fn downlevel_flags(&self) -> wgpu::DownlevelFlags {
+        self.downlevel_flags
+    }
+
+    // What the device was created with, see Runner::set_limits and Runner::set_max_limits
+    pub fn limits(&self) -> wgpu::Limits {
+        self.device.limits()
+    }
+
+    // Framebuffer size in physical pixels, what viewports and render targets are sized in. The
+    // resolution given to Runner::set_fixed_resolution when there is one.
+    pub fn size(&self) -> egui::Vec2 {
+        self.size
+    }
+
+    // With Runner::set_fixed_resolution, the window area in logical points the frame is scaled
+    // into. A mouse position maps to the frame's pixels with
+    // (position - letterbox.min) / letterbox.size() * size().
+    pub fn letterbox(&self) -> Option<egui::Rect> {
+        self.letterbox
+    }
+
+    // Physical pixels per logical point: the monitor scale factor times the egui zoom (1 unless
+    // changed). It changes when the window moves to another monitor, App::resize is called then.
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    // Window size in logical points, the units of egui and of the mouse positions
+    pub fn logical_size(&self) -> egui::Vec2 {
+        self.size / self.scale_factor
+    }
+
+    // Color format of the render pass. For the surface it's egui's pick among the supported
+    // formats, Rgba8Unorm or Bgra8Unorm when available, and eframe keeps the capabilities to
+    // itself: Runner::set_srgb checks the pick, Runner::set_hdr renders in Rgba16Float instead.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn depth_stencil_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_stencil_format
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn time(&self) -> &Time {
+        &self.time
+    }
+
+    pub fn fps_counter(&self) -> &FpsCounter {
+        self.fps_counter
+    }
+
+    pub fn input(&self) -> &Input {
+        self.input
+    }
+
+    // Never connected when headless
+    #[cfg(feature = "gamepad")]
+    pub fn gamepads(&self) -> &Gamepads {
+        self.gamepads
+    }
+
+    pub fn clear_color(&self) -> wgpu::Color {
+        self.clear_color.get()
+    }
+
+    // Background of the following frames, in linear space
+    pub fn set_clear_color(&self, color: wgpu::Color) {
+        self.clear_color.set(color);
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.exposure.get()
+    }
+
+    // Exposure of the tonemapping pass from the next frame on, only used with Runner::set_hdr
+    pub fn set_exposure(&self, exposure: f32) {
+        self.exposure.set(exposure);
+    }
+
+    // With Runner::set_hdr, the target App::render draws into, sized like size(). Its texture_view
+    // holds the frame (resolved when multisampled) by the time App::post_process is called. It
+    // is recreated before App::resize.
+    pub fn hdr_target(&self) -> Option<&RenderTarget> {
+        self.hdr_target
+    }
+
+    // See Runner::set_asset_root
+    pub fn asset_root(&self) -> Option<&Path> {
+        self.asset_root
+    }
+
+    pub fn uploader(&self) -> &Uploader {
+        self.uploader
+    }
+
+    // Staged copy into target, for the encoder handed to App::prepare (see Uploader). Cheaper
+    // than queue.write_buffer for large per-frame data.
+    pub fn write_buffer<T: bytemuck::Pod>(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[T],
+    ) {
+        self.uploader
+            .write_buffer(self.device, encoder, target, offset, data);
+    }
+
+    // Submits what the frame's encoder holds so far and leaves a new one in its place, for the
+    // rare frame that needs its earlier work done before going on (read_buffer of a buffer a
+    // compute pass just wrote). Without it the runner submits the encoder once, after
+    // App::post_process. dispatch and read_buffer submit their own encoder right away, so they
+    // only see what a flush submitted before them.
+    pub fn flush(&self, encoder: &mut wgpu::CommandEncoder) {
+        let new_encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Frame Encoder"),
+            });
+        let encoder = std::mem::replace(encoder, new_encoder);
+        self.uploader.finish();
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    // Where the crate's helpers register what they allocate, track_texture and track_buffer
+    // add the app's own resources
+    pub fn resources(&self) -> &ResourceTracker {
+        self.resources
+    }
+
+    // Tracked GPU memory by category and the largest resources, see ResourceTracker
+    pub fn resource_report(&self) -> ResourceReport {
+        self.resources.report()
+    }
+
+    pub fn create_vertex_buffer<T: bytemuck::Pod>(&self, data: &[T], label: &str) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(data),
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    // Bind with T::FORMAT to match the element type
+    pub fn create_index_buffer<T: IndexType>(&self, data: &[T], label: &str) -> wgpu::Buffer {
+        self.device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(data),
+                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            })
+    }
+
+    // Bind groups are set in order starting at group 0
+    pub fn dispatch(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        bind_groups: &[&wgpu::BindGroup],
+        workgroups: (u32, u32, u32),
+    ) {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Dispatch Encoder"),
+            });
+        {
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Dispatch Compute Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(pipeline);
+            for (index, bind_group) in bind_groups.iter().enumerate() {
+                compute_pass.set_bind_group(index as u32, bind_group, &[]);
+            }
+            compute_pass.dispatch_workgroups(workgroups.0, workgroups.1, workgroups.2);
+        }
+        self.queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    // Blocks until the GPU is done with the buffer, which needs COPY_SRC usage. The range is in
+    // bytes and, like any buffer copy, must start and end on multiples of 4.
+    pub fn read_buffer<T: bytemuck::Pod>(
+        &self,
+        buffer: &wgpu::Buffer,
+        range: impl RangeBounds<wgpu::BufferAddress>,
+    ) -> Result<Vec<T>, ContextError> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end + 1,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => buffer.size(),
+        };
+        if start > end
+            || end > buffer.size()
+            || start % wgpu::COPY_BUFFER_ALIGNMENT != 0
+            || end % wgpu::COPY_BUFFER_ALIGNMENT != 0
+        {
+            return Err(ContextError::OutOfRange(format!(
+                "can't read bytes {start}..{end} of a {} bytes buffer",
+                buffer.size()
+            )));
+        }
+
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback Buffer"),
+            size: end - start,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Readback Encoder"),
+            });
+        encoder.copy_buffer_to_buffer(buffer, start, &staging, 0, end - start);
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = staging.slice(..);
+        map_read(self.device, &slice)?;
+        let data = bytemuck::pod_collect_to_vec(&slice.get_mapped_range());
+        staging.unmap();
+        Ok(data)
+    }
+
+    // egui gives no feedback when a grab mode is refused, so the mode winit supports is picked
+    // per platform: Locked only works on macOS (and Wayland), Confined everywhere but macOS
+    pub fn set_cursor_grab(&self, grab: bool) {
+        let mode = if !grab {
+            egui::CursorGrab::None
+        } else if cfg!(target_os = "macos") {
+            egui::CursorGrab::Locked
+        } else {
+            egui::CursorGrab::Confined
+        };
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.send_viewport_cmd(egui::ViewportCommand::CursorGrab(mode));
+        }
+    }
+
+    pub fn set_cursor_visible(&self, visible: bool) {
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.send_viewport_cmd(egui::ViewportCommand::CursorVisible(visible));
+        }
+    }
+
+    // Borderless on the current monitor. The size change reaches App::resize like any other,
+    // leaving fullscreen gives the window back the size it had before.
+    pub fn set_fullscreen(&self, fullscreen: bool) {
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(fullscreen));
+        }
+    }
+
+    // IME input (Input::ime_preedit and the commits in typed_chars) only reaches the app on the
+    // frames this is called, egui enables it on the window for those. cursor_rect is where the
+    // text cursor is drawn, in logical points, for the IME to place its candidate window.
+    pub fn enable_ime(&self, cursor_rect: egui::Rect) {
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.output_mut(|output| {
+                output.ime = Some(egui::output::IMEOutput {
+                    rect: cursor_rect,
+                    cursor_rect,
+                })
+            });
+        }
+    }
+
+    // Always false when headless
+    pub fn is_fullscreen(&self) -> bool {
+        self.egui_ctx.is_some_and(|egui_ctx| {
+            egui_ctx.input(|input| input.viewport().fullscreen.unwrap_or(false))
+        })
+    }
+
+    // Saved as a PNG once the frame has been presented, which can be a frame later. The file is
+    // created right away, so a path that can't be written fails here. Readback and encoding
+    // errors come after this returns, from take_capture_result.
+    pub fn capture_frame(&self, path: impl Into<PathBuf>) -> Result<(), ContextError> {
+        self.capture.request(path.into())?;
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot);
+        }
+        Ok(())
+    }
+
+    // Where the last capture_frame was saved, or why it couldn't be. None while it's pending
+    // and once taken.
+    pub fn take_capture_result(&self) -> Option<Result<PathBuf, ContextError>> {
+        self.capture.take_result()
+    }
+
+    // Draws another frame after this one with Runner::set_render_on_demand (frames are drawn
+    // continuously otherwise), does nothing when headless
+    pub fn request_redraw(&self) {
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.request_repaint();
+        }
+    }
+
+    // Same, once the delay has passed, unless something else draws a frame first
+    pub fn request_redraw_after(&self, delay: Duration) {
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.request_repaint_after(delay);
+        }
+    }
+
+    // The window closes at the end of the current frame, does nothing when headless
+    pub fn request_exit(&self) {
+        if let Some(egui_ctx) = self.egui_ctx {
+            egui_ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+        }
+    }
+}
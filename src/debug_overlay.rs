@@ -0,0 +1,105 @@
+use eframe::egui;
+
+use crate::{context::Context, resources::format_bytes};
+
+// Seconds between refreshes, 4 Hz keeps the numbers readable
+const REFRESH_INTERVAL: f32 = 0.25;
+
+// Frame stats in the top-left corner: adapter, resolution, FPS, frame times and the scopes of
+// a GpuProfiler. The runner draws one with Runner::set_debug_overlay, F3 shows or hides it.
+#[derive(Default)]
+pub struct DebugOverlay {
+    visible: bool,
+    next_refresh: f32,
+    lines: Vec<String>,
+}
+
+impl DebugOverlay {
+    pub fn new(visible: bool) -> Self {
+        Self {
+            visible,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    pub fn set_visible(&mut self, visible: bool) -> &mut Self {
+        self.visible = visible;
+        self
+    }
+
+    pub fn toggle(&mut self) -> &mut Self {
+        self.visible = !self.visible;
+        self
+    }
+
+    // Reads the stats again once REFRESH_INTERVAL has passed, call it every frame
+    pub fn refresh(&mut self, context: &Context) {
+        let elapsed = context.time().elapsed_seconds();
+        if !self.visible || (elapsed < self.next_refresh && !self.lines.is_empty()) {
+            return;
+        }
+        self.next_refresh = elapsed + REFRESH_INTERVAL;
+
+        let adapter = context.adapter_info();
+        let fps_counter = context.fps_counter();
+        let size = context.size();
+        let resources = context.resource_report();
+        self.lines = vec![
+            format!("{} ({:?})", adapter.name, adapter.backend),
+            format!("{}x{}", size.x as u32, size.y as u32),
+            format!("{:.0} FPS", fps_counter.fps()),
+            format!(
+                "frame {:.2} ms, max {:.2} ms",
+                fps_counter.avg_frame_time_ms(),
+                fps_counter.max_frame_time_ms()
+            ),
+            format!(
+                "{} resources, {}",
+                resources.count,
+                format_bytes(resources.total_bytes)
+            ),
+        ];
+        if let Some(gpu_time) = fps_counter.gpu_frame_time_ms() {
+            self.lines.push(format!("GPU {gpu_time:.2} ms"));
+            let gpu_times = fps_counter.gpu_times_ms();
+            let width = gpu_times
+                .iter()
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or(0);
+            for (name, ms) in &gpu_times {
+                self.lines.push(format!("  {name:<width$} {ms:.2} ms"));
+            }
+        }
+    }
+
+    // Draws the stats of the last refresh over everything else
+    pub fn show(&self, egui_ctx: &egui::Context) {
+        if !self.visible {
+            return;
+        }
+        egui::Area::new(egui::Id::new("wgpu_bootstrap_debug_overlay"))
+            .anchor(egui::Align2::LEFT_TOP, [8.0, 8.0])
+            .order(egui::Order::Foreground)
+            .interactable(false)
+            .show(egui_ctx, |ui| {
+                egui::Frame::none()
+                    .fill(egui::Color32::from_black_alpha(160))
+                    .rounding(4.0)
+                    .inner_margin(6.0)
+                    .show(ui, |ui| {
+                        for line in &self.lines {
+                            ui.label(
+                                egui::RichText::new(line)
+                                    .monospace()
+                                    .color(egui::Color32::WHITE),
+                            );
+                        }
+                    });
+            });
+    }
+}
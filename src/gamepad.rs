@@ -0,0 +1,133 @@
+pub use gilrs::{Axis, Button, GamepadId};
+
+// Stick values below this distance from the center read as 0
+pub const DEFAULT_DEADZONE: f32 = 0.1;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum GamepadEvent {
+    Connected(GamepadId),
+    Disconnected(GamepadId),
+}
+
+// Controller state, polled by the runner at the start of each frame. Queries go to the active
+// gamepad, the last one used (or the first connected). Without a controller, or when the
+// platform has no gamepad support, nothing is ever down and every axis is 0.
+pub struct Gamepads {
+    gilrs: Option<gilrs::Gilrs>,
+    active: Option<GamepadId>,
+    deadzone: f32,
+}
+
+impl Gamepads {
+    pub(crate) fn new(deadzone: f32) -> Self {
+        let gilrs = match gilrs::Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(err) => {
+                log::warn!("gamepads disabled: {err}");
+                None
+            }
+        };
+        Self {
+            gilrs,
+            active: None,
+            deadzone: deadzone.clamp(0.0, 0.99),
+        }
+    }
+
+    // For contexts that never see a controller (headless)
+    pub(crate) fn disabled() -> Self {
+        Self {
+            gilrs: None,
+            active: None,
+            deadzone: DEFAULT_DEADZONE,
+        }
+    }
+
+    // Updates the state from the pending events and returns the connections and disconnections
+    pub(crate) fn poll(&mut self) -> Vec<GamepadEvent> {
+        let Some(gilrs) = &mut self.gilrs else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        while let Some(gilrs::Event { id, event, .. }) = gilrs.next_event() {
+            match event {
+                gilrs::EventType::Connected => events.push(GamepadEvent::Connected(id)),
+                gilrs::EventType::Disconnected => {
+                    if self.active == Some(id) {
+                        self.active = None;
+                    }
+                    events.push(GamepadEvent::Disconnected(id));
+                }
+                _ => self.active = Some(id),
+            }
+        }
+        if self.active.is_none() {
+            self.active = gilrs.gamepads().next().map(|(id, _)| id);
+        }
+        events
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn active(&self) -> Option<GamepadId> {
+        self.active
+    }
+
+    pub fn connected(&self) -> Vec<GamepadId> {
+        match &self.gilrs {
+            Some(gilrs) => gilrs.gamepads().map(|(id, _)| id).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn name(&self, id: GamepadId) -> Option<String> {
+        self.gilrs
+            .as_ref()
+            .and_then(|gilrs| gilrs.connected_gamepad(id))
+            .map(|gamepad| String::from(gamepad.name()))
+    }
+
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    pub fn is_button_down(&self, button: Button) -> bool {
+        self.gamepad()
+            .is_some_and(|gamepad| gamepad.is_pressed(button))
+    }
+
+    // 0 to 1, for the analog triggers (LeftTrigger2, RightTrigger2)
+    pub fn button_value(&self, button: Button) -> f32 {
+        self.gamepad().map_or(0.0, |gamepad| {
+            gamepad.button_data(button).map_or(0.0, |data| data.value())
+        })
+    }
+
+    // -1 to 1, up is positive on the Y axes. The two axes of a stick share a radial deadzone,
+    // rescaled so the values still start at 0 past it.
+    pub fn axis_value(&self, axis: Axis) -> f32 {
+        let Some(gamepad) = self.gamepad() else {
+            return 0.0;
+        };
+        let pair = match axis {
+            Axis::LeftStickX | Axis::LeftStickY => (Axis::LeftStickX, Axis::LeftStickY),
+            Axis::RightStickX | Axis::RightStickY => (Axis::RightStickX, Axis::RightStickY),
+            _ => return gamepad.value(axis),
+        };
+        let x = gamepad.value(pair.0);
+        let y = gamepad.value(pair.1);
+        let length = (x * x + y * y).sqrt();
+        if length <= self.deadzone {
+            return 0.0;
+        }
+        let scale = ((length - self.deadzone) / (1.0 - self.deadzone)).min(1.0) / length;
+        gamepad.value(axis) * scale
+    }
+
+    fn gamepad(&self) -> Option<gilrs::Gamepad<'_>> {
+        let gilrs = self.gilrs.as_ref()?;
+        gilrs.connected_gamepad(self.active?)
+    }
+}
@@ -0,0 +1,380 @@
+use eframe::{egui, wgpu};
+use std::{
+    cell::Cell,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+#[cfg(feature = "gamepad")]
+use crate::gamepad::Gamepads;
+use crate::{
+    capture::{read_texture, save_png, FrameCapture},
+    context::{instance_flags, Context, ContextError},
+    input::Input,
+    pass_list::PassList,
+    resources::ResourceTracker,
+    runner::{device_lost_callback, log_adapter_info, App, DeviceOptions, FpsCounter, Runner},
+    time::Time,
+    uploader::Uploader,
+};
+
+// WGPU_BACKEND picks them, all by default
+fn backends() -> wgpu::Backends {
+    wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all())
+}
+
+// Neutral dark gray until Context::set_clear_color is called
+const DEFAULT_CLEAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.02,
+    g: 0.02,
+    b: 0.02,
+    a: 1.0,
+};
+
+pub struct HeadlessContext {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+    depth_stencil_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    // With MSAA only
+    msaa_view: Option<wgpu::TextureView>,
+    depth_view: Option<wgpu::TextureView>,
+    fps_counter: FpsCounter,
+    input: Input,
+    capture: FrameCapture,
+    clear_color: Cell<wgpu::Color>,
+    exposure: Cell<f32>,
+    adapter: wgpu::Adapter,
+    adapter_info: wgpu::AdapterInfo,
+    downlevel_flags: wgpu::DownlevelFlags,
+    uploader: Uploader,
+    resources: ResourceTracker,
+    device_lost: Arc<AtomicBool>,
+    asset_root: Option<PathBuf>,
+    #[cfg(feature = "gamepad")]
+    gamepads: Gamepads,
+}
+
+impl HeadlessContext {
+    // Depth32Float, no MSAA and the default features and limits of a Runner
+    pub fn new(width: u32, height: u32, format: wgpu::TextureFormat) -> Result<Self, ContextError> {
+        Self::create(width, height, format, backends(), DeviceOptions::default())
+    }
+
+    // Sets up the device like runner.run would: adapter, depth format, MSAA, features, limits
+    // and push constants, failing with the same errors. The window options are ignored.
+    pub fn with_runner(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        runner: &Runner,
+    ) -> Result<Self, ContextError> {
+        let backends = backends();
+        let options = runner.device_options(backends)?;
+        Self::create(width, height, format, backends, options)
+    }
+
+    fn create(
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        backends: wgpu::Backends,
+        options: DeviceOptions,
+    ) -> Result<Self, ContextError> {
+        if width == 0 || height == 0 {
+            return Err(ContextError::InvalidSize(format!(
+                "headless size must be at least 1x1, got {width}x{height}"
+            )));
+        }
+
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            flags: instance_flags(),
+            ..Default::default()
+        });
+
+        let adapter = match &options.adapter {
+            Some(info) => instance
+                .enumerate_adapters(backends)
+                .into_iter()
+                .find(|adapter| {
+                    let candidate = adapter.get_info();
+                    candidate.name == info.name && candidate.backend == info.backend
+                })
+                .ok_or_else(|| ContextError::AdapterNotFound(info.name.clone()))?,
+            None => pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::util::power_preference_from_env().unwrap_or_default(),
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            }))
+            .ok_or(ContextError::NoAdapter)?,
+        };
+        let adapter_info = adapter.get_info();
+        log_adapter_info(&adapter_info);
+        let downlevel_flags = adapter.get_downlevel_capabilities().flags;
+
+        let sample_count = options.sample_count;
+        let descriptor = (options.device_descriptor)(&adapter);
+        if let Some(err) = options.device_error.lock().unwrap().take() {
+            return Err(err);
+        }
+        // device_descriptor only checks the formats a surface would have
+        if !adapter
+            .get_texture_format_features(format)
+            .flags
+            .sample_count_supported(sample_count)
+        {
+            return Err(ContextError::UnsupportedSampleCount(sample_count));
+        }
+
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("Headless Device"),
+                ..descriptor
+            },
+            // wgpu 22 ignores a trace path, tracing is off until gfx-rs/wgpu#5974
+            None,
+        ))
+        .map_err(ContextError::RequestDevice)?;
+
+        let device_lost = Arc::new(AtomicBool::new(false));
+        device.set_device_lost_callback(device_lost_callback(device_lost.clone()));
+
+        let depth_stencil_format = options.depth_stencil_format;
+
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Headless View"),
+            ..Default::default()
+        });
+
+        // Resolved into texture at the end of the render pass
+        let msaa_view = (sample_count > 1).then(|| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Headless MSAA Texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Headless MSAA View"),
+                    ..Default::default()
+                })
+        });
+
+        let depth_view = depth_stencil_format.map(|depth_stencil_format| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Headless Depth Texture"),
+                    size,
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: depth_stencil_format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Headless Depth View"),
+                    ..Default::default()
+                })
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            width,
+            height,
+            format,
+            depth_stencil_format,
+            sample_count,
+            texture,
+            view,
+            msaa_view,
+            depth_view,
+            fps_counter: FpsCounter::default(),
+            input: Input::default(),
+            capture: FrameCapture::default(),
+            clear_color: Cell::new(DEFAULT_CLEAR_COLOR),
+            exposure: Cell::new(1.0),
+            adapter,
+            adapter_info,
+            downlevel_flags,
+            uploader: Uploader::default(),
+            resources: ResourceTracker::default(),
+            device_lost,
+            asset_root: None,
+            #[cfg(feature = "gamepad")]
+            gamepads: Gamepads::disabled(),
+        })
+    }
+
+    pub fn context(&self) -> Context<'_> {
+        Context {
+            device: &self.device,
+            queue: &self.queue,
+            size: egui::vec2(self.width as f32, self.height as f32),
+            scale_factor: 1.0,
+            format: self.format,
+            surface_format: None,
+            present_mode: None,
+            depth_stencil_format: self.depth_stencil_format,
+            sample_count: self.sample_count,
+            time: Time::default(),
+            fps_counter: &self.fps_counter,
+            input: &self.input,
+            egui_ctx: None,
+            capture: &self.capture,
+            clear_color: &self.clear_color,
+            exposure: &self.exposure,
+            adapter: &self.adapter,
+            adapter_info: &self.adapter_info,
+            downlevel_flags: self.downlevel_flags,
+            uploader: &self.uploader,
+            resources: &self.resources,
+            hdr_target: None,
+            letterbox: None,
+            asset_root: self.asset_root.as_deref(),
+            #[cfg(feature = "gamepad")]
+            gamepads: &self.gamepads,
+        }
+    }
+
+    // Same as Runner::set_asset_root
+    pub fn set_asset_root(&mut self, root: impl Into<PathBuf>) -> &mut Self {
+        self.asset_root = Some(root.into());
+        self
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    // Once lost the device can't be recovered, a new HeadlessContext is needed
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
+
+    pub fn render(&self, app: &mut dyn App) {
+        if self.is_device_lost() {
+            log::error!("GPU device lost, nothing rendered");
+            return;
+        }
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless Encoder"),
+            });
+
+        let context = self.context();
+        app.prepare(&mut encoder, &context);
+        let mut passes = PassList::new();
+        app.passes(&mut passes, &context);
+        passes.execute(&mut encoder, &context);
+
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Headless Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: self.msaa_view.as_ref().unwrap_or(&self.view),
+                    resolve_target: self.msaa_view.as_ref().map(|_| &self.view),
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(self.clear_color.get()),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: self.depth_view.as_ref().map(|depth_view| {
+                    let format = self.depth_stencil_format.unwrap();
+                    wgpu::RenderPassDepthStencilAttachment {
+                        view: depth_view,
+                        depth_ops: format.has_depth_aspect().then_some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(1.0),
+                            store: wgpu::StoreOp::Store,
+                        }),
+                        stencil_ops: format.has_stencil_aspect().then_some(wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(0),
+                            store: wgpu::StoreOp::Store,
+                        }),
+                    }
+                }),
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            app.render(&mut render_pass);
+        }
+
+        self.uploader.finish();
+        self.queue.submit(std::iter::once(encoder.finish()));
+        self.uploader.recall();
+
+        if let Some(path) = self.capture.take_pending() {
+            let result = self.capture_frame(path.clone());
+            self.capture.finish(path, result);
+        }
+    }
+
+    // Reads back what the last render left in the texture
+    pub fn capture_frame(&self, path: impl Into<PathBuf>) -> Result<(), ContextError> {
+        let rgba = read_texture(&self.device, &self.queue, &self.texture)?;
+        save_png(&path.into(), self.width, self.height, &rgba)
+    }
+
+    // Same as capture_frame, in memory, see testing::assert_image_matches
+    pub fn read_frame(&self) -> Result<image::RgbaImage, ContextError> {
+        let rgba = read_texture(&self.device, &self.queue, &self.texture)?;
+        image::RgbaImage::from_raw(self.width, self.height, rgba).ok_or_else(|| {
+            ContextError::InvalidSize(String::from("frame readback has the wrong size"))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Checked before looking for an adapter, so this runs without a GPU
+    #[test]
+    fn zero_size_is_rejected() {
+        for (width, height) in [(0, 0), (0, 16), (16, 0)] {
+            let result = HeadlessContext::new(width, height, wgpu::TextureFormat::Rgba8UnormSrgb);
+            assert!(
+                matches!(result, Err(ContextError::InvalidSize(_))),
+                "{width}x{height} was accepted"
+            );
+        }
+    }
+}
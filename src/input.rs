@@ -0,0 +1,179 @@
+use std::cell::{Ref, RefCell, RefMut};
+
+use eframe::egui::{self, InputState, Key, PointerButton};
+
+use crate::actions::{ActionMap, Binding};
+
+// Per-frame snapshot of egui's input, with the keyboard or the mouse reported idle while the
+// GUI is using it. The action map is carried from frame to frame.
+#[derive(Default)]
+pub struct Input {
+    state: InputState,
+    gui_wants_keyboard: bool,
+    gui_wants_pointer: bool,
+    actions: RefCell<ActionMap>,
+    typed_chars: String,
+    // Outlives the frame, until the composition is committed or cancelled
+    ime_preedit: Option<String>,
+}
+
+impl Input {
+    // previous is the last frame's input, for its action map and IME composition
+    pub(crate) fn new(egui_ctx: &egui::Context, previous: Input) -> Self {
+        let state = egui_ctx.input(|i| i.clone());
+        let mut typed_chars = String::new();
+        let mut ime_preedit = previous.ime_preedit;
+        for event in &state.events {
+            match event {
+                egui::Event::Text(text) => typed_chars.push_str(text),
+                egui::Event::Ime(egui::ImeEvent::Preedit(text)) => {
+                    ime_preedit = Some(text.clone()).filter(|text| !text.is_empty());
+                }
+                egui::Event::Ime(egui::ImeEvent::Commit(text)) => {
+                    typed_chars.push_str(text);
+                    ime_preedit = None;
+                }
+                egui::Event::Ime(egui::ImeEvent::Disabled) => ime_preedit = None,
+                _ => {}
+            }
+        }
+        Self {
+            state,
+            gui_wants_keyboard: egui_ctx.wants_keyboard_input(),
+            gui_wants_pointer: egui_ctx.wants_pointer_input(),
+            actions: previous.actions,
+            typed_chars,
+            ime_preedit,
+        }
+    }
+
+    pub(crate) fn with_actions(actions: ActionMap) -> Self {
+        Self {
+            actions: RefCell::new(actions),
+            ..Default::default()
+        }
+    }
+
+    pub fn state(&self) -> &InputState {
+        &self.state
+    }
+
+    pub fn is_key_down(&self, key: Key) -> bool {
+        !self.gui_wants_keyboard && self.state.key_down(key)
+    }
+
+    // Only true on the frame the key went down
+    pub fn was_key_pressed(&self, key: Key) -> bool {
+        !self.gui_wants_keyboard && self.state.key_pressed(key)
+    }
+
+    // The text typed since the last frame, in order: the characters keys produced, with the
+    // layout, shift and dead keys applied, then IME commits. Control characters and shortcuts
+    // (ctrl or cmd held) aren't included, use was_key_pressed for backspace, enter and such.
+    pub fn typed_chars(&self) -> &str {
+        if self.gui_wants_keyboard {
+            return "";
+        }
+        &self.typed_chars
+    }
+
+    // The text an IME is composing, for the app to show at the cursor until it's committed to
+    // typed_chars. None when no composition is in progress.
+    pub fn ime_preedit(&self) -> Option<&str> {
+        if self.gui_wants_keyboard {
+            return None;
+        }
+        self.ime_preedit.as_deref()
+    }
+
+    // In logical points, multiply by Context::scale_factor for physical pixels
+    pub fn mouse_position(&self) -> (f32, f32) {
+        match self.state.pointer.latest_pos() {
+            Some(pos) => (pos.x, pos.y),
+            None => (0.0, 0.0),
+        }
+    }
+
+    // Raw motion when the platform reports it, so it keeps working with a grabbed cursor
+    pub fn mouse_delta(&self) -> (f32, f32) {
+        if self.gui_wants_pointer {
+            return (0.0, 0.0);
+        }
+        let delta = self
+            .state
+            .pointer
+            .motion()
+            .unwrap_or(self.state.pointer.delta());
+        (delta.x, delta.y)
+    }
+
+    pub fn is_mouse_button_down(&self, button: PointerButton) -> bool {
+        !self.gui_wants_pointer && self.state.pointer.button_down(button)
+    }
+
+    // Any of its bindings held, false for an action that isn't in the map
+    pub fn is_action_active(&self, action: &str) -> bool {
+        self.actions
+            .borrow()
+            .bindings(action)
+            .iter()
+            .any(|binding| match *binding {
+                Binding::Key(key) => self.is_key_down(key),
+                Binding::Mouse(button) => self.is_mouse_button_down(button),
+            })
+    }
+
+    // Only true on the frame one of its bindings went down
+    pub fn was_action_pressed(&self, action: &str) -> bool {
+        self.actions
+            .borrow()
+            .bindings(action)
+            .iter()
+            .any(|binding| match *binding {
+                Binding::Key(key) => self.was_key_pressed(key),
+                Binding::Mouse(button) => {
+                    !self.gui_wants_pointer && self.state.pointer.button_pressed(button)
+                }
+            })
+    }
+
+    pub fn actions(&self) -> Ref<'_, ActionMap> {
+        self.actions.borrow()
+    }
+
+    // Rebinding applies from the next query
+    pub fn actions_mut(&self) -> RefMut<'_, ActionMap> {
+        self.actions.borrow_mut()
+    }
+
+    // Wheel and touchpad scroll since the last frame in points, positive up and left. Wheels
+    // move in lines that egui counts as 40 points (8 on the web), so a notch is one big step,
+    // while touchpads send many small pixel deltas. Shift turns vertical scrolling horizontal,
+    // and scrolling with ctrl (cmd on macOS) is reported by pinch_delta instead.
+    pub fn scroll_delta(&self) -> (f32, f32) {
+        if self.gui_wants_pointer {
+            return (0.0, 0.0);
+        }
+        let delta = wheel_scroll(&self.state);
+        (delta.x, delta.y)
+    }
+
+    // Zoom factor since the last frame, above 1 when zooming in and 1 without a gesture. From
+    // touchpad pinches (macOS only, the other platforms don't report them), two-finger touch
+    // pinches and ctrl scrolling.
+    pub fn pinch_delta(&self) -> f32 {
+        if self.gui_wants_pointer {
+            return 1.0;
+        }
+        self.state.zoom_delta()
+    }
+}
+
+// egui turns ctrl scrolling into zoom_delta but still counts it in raw_scroll_delta
+pub(crate) fn wheel_scroll(state: &InputState) -> egui::Vec2 {
+    if state.modifiers.ctrl || state.modifiers.command {
+        egui::Vec2::ZERO
+    } else {
+        state.raw_scroll_delta
+    }
+}
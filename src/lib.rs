@@ -1,8 +1,45 @@
+// Lets the derive macros' ::wgpu_bootstrap paths resolve inside this crate too
+extern crate self as wgpu_bootstrap;
+
+pub mod actions;
+pub mod app_stack;
+pub mod assets;
+mod capture;
+pub mod context;
+pub mod debug_overlay;
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod headless;
+pub mod input;
+pub mod pass_list;
+pub mod resources;
 pub mod runner;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
+pub mod time;
+pub mod uploader;
 pub mod util;
+pub use actions::ActionMap;
+pub use app_stack::AppStack;
+pub use app_stack::Transition;
+pub use bytemuck;
 pub use cgmath;
+pub use context::Context;
+pub use context::ContextDescription;
+pub use context::ContextError;
+pub use context::IndexType;
 pub use eframe::egui;
 pub use eframe::wgpu;
+#[cfg(not(target_arch = "wasm32"))]
+pub use headless::HeadlessContext;
+pub use image;
+pub use input::Input;
+pub use pass_list::PassList;
+pub use resources::ResourceTracker;
 pub use runner::App;
-pub use runner::Context;
+pub use runner::FpsCounter;
 pub use runner::Runner;
+pub use time::Time;
+pub use uploader::Uploader;
+pub use wgpu_bootstrap_derive::Vertex;
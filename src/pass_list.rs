@@ -0,0 +1,90 @@
+use eframe::wgpu;
+
+use crate::{
+    context::Context,
+    util::{depth_target::DepthTarget, render_target::RenderTarget},
+};
+
+type EncoderPass<'a> = Box<dyn FnOnce(&mut wgpu::CommandEncoder, &Context) + 'a>;
+type RenderPassFn<'a> = Box<dyn FnOnce(&mut wgpu::RenderPass<'_>, &Context) + 'a>;
+
+enum Pass<'a> {
+    Encoder(EncoderPass<'a>),
+    Color(&'a RenderTarget, Option<wgpu::Color>, RenderPassFn<'a>),
+    Depth(&'a DepthTarget, RenderPassFn<'a>),
+}
+
+// Passes run in the order they were added, all on the same encoder and submitted once. The
+// runner fills one with App::passes each frame and runs it after App::prepare, before the
+// frame is drawn. It doesn't track what a pass reads or writes: order them so a target is
+// rendered before the passes sampling it.
+//
+// Each pass is wrapped in a debug group named by its label, for GPU debuggers.
+#[derive(Default)]
+pub struct PassList<'a> {
+    passes: Vec<(&'a str, Pass<'a>)>,
+}
+
+impl<'a> PassList<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // The closure records whatever it needs (compute, copies, PostProcess::apply)
+    pub fn encoder(
+        &mut self,
+        label: &'a str,
+        pass: impl FnOnce(&mut wgpu::CommandEncoder, &Context) + 'a,
+    ) -> &mut Self {
+        self.passes.push((label, Pass::Encoder(Box::new(pass))));
+        self
+    }
+
+    // A render pass on the target, see RenderTarget::begin_pass for clear
+    pub fn color(
+        &mut self,
+        label: &'a str,
+        target: &'a RenderTarget,
+        clear: Option<wgpu::Color>,
+        pass: impl FnOnce(&mut wgpu::RenderPass<'_>, &Context) + 'a,
+    ) -> &mut Self {
+        self.passes
+            .push((label, Pass::Color(target, clear, Box::new(pass))));
+        self
+    }
+
+    // A depth only render pass on the target, cleared to 1
+    pub fn depth(
+        &mut self,
+        label: &'a str,
+        target: &'a DepthTarget,
+        pass: impl FnOnce(&mut wgpu::RenderPass<'_>, &Context) + 'a,
+    ) -> &mut Self {
+        self.passes
+            .push((label, Pass::Depth(target, Box::new(pass))));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.passes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.passes.is_empty()
+    }
+
+    // Records every pass on the encoder, the caller submits it
+    pub fn execute(self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        for (label, pass) in self.passes {
+            encoder.push_debug_group(label);
+            match pass {
+                Pass::Encoder(pass) => pass(encoder, context),
+                Pass::Color(target, clear, pass) => {
+                    pass(&mut target.begin_pass(encoder, clear), context)
+                }
+                Pass::Depth(target, pass) => pass(&mut target.begin_pass(encoder), context),
+            }
+            encoder.pop_debug_group();
+        }
+    }
+}
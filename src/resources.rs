@@ -0,0 +1,227 @@
+use eframe::wgpu;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex, Weak},
+};
+
+// Entries in ResourceReport::largest
+const LARGEST_COUNT: usize = 10;
+
+// What a resource is used for, deduced from its usages (and its format for textures)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ResourceCategory {
+    Texture,
+    RenderTarget,
+    DepthTexture,
+    VertexBuffer,
+    IndexBuffer,
+    UniformBuffer,
+    StorageBuffer,
+    IndirectBuffer,
+    OtherBuffer,
+}
+
+#[derive(Clone, Debug)]
+pub struct ResourceInfo {
+    pub label: String,
+    pub category: ResourceCategory,
+    // Estimated from the size and format for textures, mips and samples included
+    pub bytes: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CategoryUsage {
+    pub category: ResourceCategory,
+    pub count: usize,
+    pub bytes: u64,
+}
+
+// Returned by Context::resource_report, printed it lists the categories then the largest
+// resources
+#[derive(Clone, Debug, Default)]
+pub struct ResourceReport {
+    pub total_bytes: u64,
+    pub count: usize,
+    // Largest first, only the categories in use
+    pub categories: Vec<CategoryUsage>,
+    // Largest first, up to 10
+    pub largest: Vec<ResourceInfo>,
+}
+
+#[derive(Default)]
+struct Entries {
+    next_id: u64,
+    resources: HashMap<u64, ResourceInfo>,
+}
+
+// The GPU memory held by the resources created through the crate's helpers (Texture,
+// RenderTarget, DepthTarget, GpuMesh and the buffer types), not raw wgpu allocations. Each
+// helper keeps the TrackedResource of what it allocated, dropped along with it, so resources
+// piling up in the report are leaks.
+#[derive(Default)]
+pub struct ResourceTracker {
+    entries: Arc<Mutex<Entries>>,
+}
+
+// Removes its resource from the tracker when dropped
+pub struct TrackedResource {
+    id: u64,
+    entries: Weak<Mutex<Entries>>,
+}
+
+impl ResourceTracker {
+    pub fn track_texture(&self, texture: &wgpu::Texture, label: &str) -> TrackedResource {
+        let format = texture.format();
+        let category = if format.is_depth_stencil_format() {
+            ResourceCategory::DepthTexture
+        } else if texture
+            .usage()
+            .contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+        {
+            ResourceCategory::RenderTarget
+        } else {
+            ResourceCategory::Texture
+        };
+        self.track(label, category, texture_bytes(texture))
+    }
+
+    pub fn track_buffer(&self, buffer: &wgpu::Buffer, label: &str) -> TrackedResource {
+        let usage = buffer.usage();
+        let category = if usage.contains(wgpu::BufferUsages::INDIRECT) {
+            ResourceCategory::IndirectBuffer
+        } else if usage.contains(wgpu::BufferUsages::VERTEX) {
+            ResourceCategory::VertexBuffer
+        } else if usage.contains(wgpu::BufferUsages::INDEX) {
+            ResourceCategory::IndexBuffer
+        } else if usage.contains(wgpu::BufferUsages::UNIFORM) {
+            ResourceCategory::UniformBuffer
+        } else if usage.contains(wgpu::BufferUsages::STORAGE) {
+            ResourceCategory::StorageBuffer
+        } else {
+            ResourceCategory::OtherBuffer
+        };
+        self.track(label, category, buffer.size())
+    }
+
+    fn track(&self, label: &str, category: ResourceCategory, bytes: u64) -> TrackedResource {
+        let mut entries = self.entries.lock().unwrap();
+        let id = entries.next_id;
+        entries.next_id += 1;
+        entries.resources.insert(
+            id,
+            ResourceInfo {
+                label: String::from(label),
+                category,
+                bytes,
+            },
+        );
+        TrackedResource {
+            id,
+            entries: Arc::downgrade(&self.entries),
+        }
+    }
+
+    pub fn report(&self) -> ResourceReport {
+        let entries = self.entries.lock().unwrap();
+        let mut categories: HashMap<ResourceCategory, CategoryUsage> = HashMap::new();
+        for info in entries.resources.values() {
+            let usage = categories
+                .entry(info.category)
+                .or_insert_with(|| CategoryUsage {
+                    category: info.category,
+                    count: 0,
+                    bytes: 0,
+                });
+            usage.count += 1;
+            usage.bytes += info.bytes;
+        }
+        let mut categories: Vec<CategoryUsage> = categories.into_values().collect();
+        categories.sort_by_key(|usage| std::cmp::Reverse(usage.bytes));
+
+        let mut largest: Vec<ResourceInfo> = entries.resources.values().cloned().collect();
+        largest.sort_by_key(|info| std::cmp::Reverse(info.bytes));
+        largest.truncate(LARGEST_COUNT);
+
+        ResourceReport {
+            total_bytes: categories.iter().map(|usage| usage.bytes).sum(),
+            count: entries.resources.len(),
+            categories,
+            largest,
+        }
+    }
+}
+
+impl Drop for TrackedResource {
+    fn drop(&mut self) {
+        if let Some(entries) = self.entries.upgrade() {
+            entries.lock().unwrap().resources.remove(&self.id);
+        }
+    }
+}
+
+impl fmt::Display for ResourceReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} tracked resources, {}",
+            self.count,
+            format_bytes(self.total_bytes)
+        )?;
+        for usage in &self.categories {
+            writeln!(
+                f,
+                "  {:?}: {} in {}",
+                usage.category,
+                format_bytes(usage.bytes),
+                usage.count
+            )?;
+        }
+        if !self.largest.is_empty() {
+            writeln!(f, "largest:")?;
+        }
+        for info in &self.largest {
+            writeln!(
+                f,
+                "  {} ({:?}): {}",
+                info.label,
+                info.category,
+                format_bytes(info.bytes)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    let bytes_f = bytes as f64;
+    if bytes_f >= KIB * KIB {
+        format!("{:.1} MiB", bytes_f / (KIB * KIB))
+    } else if bytes_f >= KIB {
+        format!("{:.1} KiB", bytes_f / KIB)
+    } else {
+        format!("{bytes} B")
+    }
+}
+
+// Every mip level of every layer, times the sample count
+fn texture_bytes(texture: &wgpu::Texture) -> u64 {
+    let format = texture.format();
+    let (block_width, block_height) = format.block_dimensions();
+    // Combined depth stencil formats have no single block size, Depth24Plus may be 4 bytes
+    let block_size = format.block_copy_size(None).unwrap_or(match format {
+        wgpu::TextureFormat::Depth32FloatStencil8 => 8,
+        _ => 4,
+    }) as u64;
+    let size = texture.size();
+    (0..texture.mip_level_count())
+        .map(|level| {
+            let mip = size.mip_level_size(level, texture.dimension());
+            let blocks_x = mip.width.div_ceil(block_width) as u64;
+            let blocks_y = mip.height.div_ceil(block_height) as u64;
+            blocks_x * blocks_y * mip.depth_or_array_layers as u64 * block_size
+        })
+        .sum::<u64>()
+        * texture.sample_count() as u64
+}
@@ -3,59 +3,153 @@ use eframe::{
     egui_wgpu::{self, depth_format_from_bits, CallbackResources, CallbackTrait},
     wgpu,
 };
-use std::{sync::Arc, time::Instant};
-
-#[allow(dead_code)]
-pub struct Context<'a> {
-    device: &'a wgpu::Device,
-    queue: &'a wgpu::Queue,
-    size: egui::Vec2,
-    format: wgpu::TextureFormat,
-    depth_stencil_format: Option<wgpu::TextureFormat>,
-}
-
-impl<'a> Context<'a> {
-    pub fn device(&self) -> &wgpu::Device {
-        self.device
-    }
-
-    pub fn queue(&self) -> &wgpu::Queue {
-        self.queue
-    }
-
-    pub fn size(&self) -> egui::Vec2 {
-        self.size
-    }
-
-    pub fn format(&self) -> wgpu::TextureFormat {
-        self.format
-    }
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use web_time::{Instant, SystemTime, UNIX_EPOCH};
 
-    pub fn depth_stencil_format(&self) -> wgpu::TextureFormat {
-        self.depth_stencil_format.unwrap()
-    }
-}
+#[cfg(feature = "gamepad")]
+use crate::gamepad::{GamepadEvent, Gamepads, DEFAULT_DEADZONE};
+use crate::{
+    actions::ActionMap,
+    app_stack::{AppStack, Transition},
+    capture::{save_png, FrameCapture},
+    context::{Context, ContextDescription, ContextError},
+    debug_overlay::DebugOverlay,
+    input::Input,
+    pass_list::PassList,
+    resources::ResourceTracker,
+    time::Time,
+    uploader::Uploader,
+    util::{
+        post_process::PostProcess,
+        render_target::RenderTarget,
+        sampler::SamplerBuilder,
+        tonemap::{Tonemap, HDR_FORMAT},
+    },
+};
 
 pub trait App {
     fn render(&self, _render_pass: &mut wgpu::RenderPass<'_>) {}
 
+    // Called every frame to build the egui UI, drawn over the app. Events egui consumes (pointer
+    // over a window, focused text field) don't reach event or input.
     fn render_gui(&mut self, _egui_ctx: &egui::Context, _context: &Context) {}
 
     fn update(&mut self, _delta_time: f32, _context: &Context) {}
 
+    // Called zero or more times per frame, before update, when Runner::set_fixed_timestep is used
+    fn fixed_update(&mut self, _fixed_delta_time: f32, _context: &Context) {}
+
+    // Work recorded here (offscreen passes, compute, copies, Context::write_buffer uploads) is
+    // submitted before the frame is drawn. Every callback given an encoder gets the same one,
+    // submitted once with egui's commands, see Context::flush for a submit mid-frame.
+    fn prepare(&mut self, _encoder: &mut wgpu::CommandEncoder, _context: &Context) {}
+
+    // Called after prepare, the passes added (shadow map, offscreen scene, post effects) run in
+    // order on the same encoder
+    fn passes<'a>(&'a mut self, _passes: &mut PassList<'a>, _context: &Context) {}
+
+    // With Runner::set_hdr, called once render has drawn into Context::hdr_target and before it
+    // is tonemapped. Effects (bloom...) read the target here and can draw back into it.
+    fn post_process(&mut self, _encoder: &mut wgpu::CommandEncoder, _context: &Context) {}
+
     fn input(&mut self, _input: InputState, _context: &Context) {}
 
+    // Called for each event before the runner's own handling, return true to consume it
+    fn event(&mut self, _event: &egui::Event, _context: &Context) -> bool {
+        false
+    }
+
+    // Called when a controller is plugged in or removed, before input
+    #[cfg(feature = "gamepad")]
+    fn gamepad_event(&mut self, _event: &GamepadEvent, _context: &Context) {}
+
+    // The window and its surface belong to eframe, which reconfigures or recreates them itself
+    // (fullscreen, Android resume) and the runner its depth and HDR textures: only the app's own
+    // size dependent targets need rebuilding here
     fn resize(&mut self, _new_width: u32, _new_height: u32, _context: &Context) {}
+
+    // Called once if the GPU device is lost (driver timeout, GPU reset). eframe can't recreate its
+    // device so the window closes right after, GPU calls made here are ignored.
+    fn on_device_lost(&mut self, _context: &Context) {}
+
+    // Called after each update, to push, pop or replace the app's current state (see AppStack),
+    // so a menu can start the game and the game open a pause screen
+    fn transition(&mut self, _context: &Context) -> Transition {
+        Transition::None
+    }
+
+    // An overlay state (pause screen, dialog) is drawn over the states below it, which still
+    // render but are no longer updated
+    fn is_overlay(&self) -> bool {
+        false
+    }
+}
+
+enum AdapterSelector {
+    Name(String),
+    Index(usize),
+}
+
+impl std::fmt::Display for AdapterSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AdapterSelector::Name(name) => write!(f, "name \"{name}\""),
+            AdapterSelector::Index(index) => write!(f, "index {index}"),
+        }
+    }
 }
 
+#[derive(Clone)]
+enum LimitsRequest {
+    Default,
+    Custom(wgpu::Limits),
+    // Everything the adapter supports
+    Adapter,
+}
+
+pub type AppCreator = Box<dyn FnOnce(&Context) -> Arc<dyn App + Send + Sync>>;
+
 pub struct Runner {
     app_name: String,
+    title: Option<String>,
     width: u32,
     height: u32,
     bg_color: egui::Color32,
-    depth_buffer: u8,
-    stencil_buffer: u8,
-    app_creator: Option<Box<dyn FnOnce(&Context) -> Arc<dyn App + Send + Sync>>>,
+    depth_stencil_format: Option<wgpu::TextureFormat>,
+    wgpu_options: egui_wgpu::WgpuConfiguration,
+    features: wgpu::Features,
+    optional_features: wgpu::Features,
+    limits: LimitsRequest,
+    push_constant_size: u32,
+    sample_count: u32,
+    srgb: Option<bool>,
+    surface_format: Option<wgpu::TextureFormat>,
+    hdr: bool,
+    fixed_resolution: Option<(u32, u32)>,
+    fixed_resolution_filter: wgpu::FilterMode,
+    #[cfg(feature = "gamepad")]
+    gamepad_deadzone: f32,
+    show_fps: bool,
+    debug_overlay: bool,
+    fixed_timestep: Option<f32>,
+    pause_on_blur: bool,
+    render_on_demand: bool,
+    transparent: bool,
+    target_fps: u32,
+    window_icon: Option<Vec<u8>>,
+    asset_root: Option<PathBuf>,
+    actions: ActionMap,
+    adapter: Option<AdapterSelector>,
+    app_creator: Option<AppCreator>,
 }
 
 impl Runner {
@@ -66,129 +160,1254 @@ impl Runner {
         bg_color: egui::Color32,
         depth_buffer: u8,
         stencil_buffer: u8,
-        app_creator: Box<dyn FnOnce(&Context) -> Arc<dyn App + Send + Sync>>,
+        app_creator: AppCreator,
     ) -> Self {
-        env_logger::init();
+        // Only the first runner of the process sets the logger up
+        #[cfg(not(target_arch = "wasm32"))]
+        env_logger::try_init().ok();
+        #[cfg(target_arch = "wasm32")]
+        eframe::WebLogger::init(log::LevelFilter::Info).ok();
 
         Self {
             app_name: String::from(app_name),
+            title: None,
             width,
             height,
             bg_color,
             app_creator: Some(app_creator),
-            depth_buffer,
-            stencil_buffer,
+            depth_stencil_format: depth_format_from_bits(depth_buffer, stencil_buffer),
+            wgpu_options: egui_wgpu::WgpuConfiguration::default(),
+            features: wgpu::Features::empty(),
+            optional_features: wgpu::Features::empty(),
+            limits: LimitsRequest::Default,
+            push_constant_size: 0,
+            sample_count: 1,
+            srgb: None,
+            surface_format: None,
+            hdr: false,
+            fixed_resolution: None,
+            fixed_resolution_filter: wgpu::FilterMode::Nearest,
+            #[cfg(feature = "gamepad")]
+            gamepad_deadzone: DEFAULT_DEADZONE,
+            show_fps: false,
+            debug_overlay: false,
+            fixed_timestep: None,
+            pause_on_blur: true,
+            render_on_demand: false,
+            transparent: false,
+            target_fps: 0,
+            window_icon: None,
+            asset_root: None,
+            actions: ActionMap::new(),
+            adapter: None,
+        }
+    }
+
+    pub fn set_title(&mut self, title: &str) -> &mut Self {
+        self.title = Some(String::from(title));
+        self
+    }
+
+    // Any format the image crate decodes (PNG, JPEG), decoded when run is called. macOS ignores
+    // window icons, the app bundle's icon is used there.
+    pub fn set_window_icon(&mut self, bytes: &[u8]) -> &mut Self {
+        self.window_icon = Some(bytes.to_vec());
+        self
+    }
+
+    // Searched first for the relative paths given to the assets functions, before the
+    // executable's directory and the working directory
+    pub fn set_asset_root(&mut self, root: impl Into<PathBuf>) -> &mut Self {
+        self.asset_root = Some(root.into());
+        self
+    }
+
+    // The bindings the app starts with, Input::actions_mut changes them while it runs
+    pub fn set_actions(&mut self, actions: ActionMap) -> &mut Self {
+        self.actions = actions;
+        self
+    }
+
+    pub fn set_window_size(&mut self, width: u32, height: u32) -> &mut Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn set_power_preference(&mut self, power_preference: wgpu::PowerPreference) -> &mut Self {
+        self.wgpu_options.power_preference = power_preference;
+        self
+    }
+
+    pub fn set_backends(&mut self, backends: wgpu::Backends) -> &mut Self {
+        self.wgpu_options.supported_backends = backends;
+        self
+    }
+
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) -> &mut Self {
+        self.wgpu_options.present_mode = present_mode;
+        self
+    }
+
+    // How many frames the presentation engine may queue ahead, 2 by default. 1 cuts input
+    // latency at the cost of throughput, and makes the CPU wait for the GPU on backends that
+    // can't wait on present. It's a hint the backend clamps to what its swapchain supports. run
+    // fails with InvalidFrameLatency for 0. Ignored by run_web, browsers pace frames themselves.
+    pub fn set_frame_latency(&mut self, frames: u32) -> &mut Self {
+        self.wgpu_options.desired_maximum_frame_latency = Some(frames);
+        self
+    }
+
+    // First adapter whose name contains this, see Context::list_adapters for the names
+    pub fn set_adapter_name(&mut self, name: &str) -> &mut Self {
+        self.adapter = Some(AdapterSelector::Name(String::from(name)));
+        self
+    }
+
+    // Index in the list returned by Context::list_adapters for the same backends
+    pub fn set_adapter_index(&mut self, index: usize) -> &mut Self {
+        self.adapter = Some(AdapterSelector::Index(index));
+        self
+    }
+
+    pub fn set_features(&mut self, features: wgpu::Features) -> &mut Self {
+        self.features = features;
+        self
+    }
+
+    // Requests Features::POLYGON_MODE_LINE when the adapter has it, without failing when it
+    // doesn't: RenderPipelineBuilder::try_build reports it for PolygonMode::Line pipelines.
+    pub fn set_wireframe_support(&mut self, enabled: bool) -> &mut Self {
+        self.optional_features
+            .set(wgpu::Features::POLYGON_MODE_LINE, enabled);
+        self
+    }
+
+    // Checked against the adapter when the device is created, run fails with UnsupportedLimits
+    // naming each limit set too high. See Context::limits for what the device got.
+    pub fn set_limits(&mut self, limits: wgpu::Limits) -> &mut Self {
+        self.limits = LimitsRequest::Custom(limits);
+        self
+    }
+
+    // The highest limits the adapter supports (max_texture_dimension_2d, max_buffer_size...),
+    // instead of the defaults that every GPU of the backend can meet
+    pub fn set_max_limits(&mut self) -> &mut Self {
+        self.limits = LimitsRequest::Adapter;
+        self
+    }
+
+    // Enables Features::PUSH_CONSTANTS with room for max_size bytes, checked against the adapter
+    // when the device is created. Native only: WebGPU and WebGL have no push constants, run_web
+    // fails with MissingFeatures, use a uniform buffer there.
+    pub fn set_push_constants(&mut self, max_size: u32) -> &mut Self {
+        self.push_constant_size = max_size;
+        self
+    }
+
+    pub fn set_depth_format(&mut self, format: Option<wgpu::TextureFormat>) -> &mut Self {
+        self.depth_stencil_format = format;
+        self
+    }
+
+    pub fn set_msaa(&mut self, sample_count: u32) -> &mut Self {
+        self.sample_count = sample_count;
+        self
+    }
+
+    // egui already picks a linear surface format whenever the surface offers one
+    pub fn set_srgb(&mut self, srgb: bool) -> &mut Self {
+        self.srgb = Some(srgb);
+        self
+    }
+
+    // The app renders into an HDR_FORMAT target instead of the surface, Context::format reports
+    // it. The result is tonemapped (ACES, see Context::set_exposure) into the surface before
+    // egui draws over it.
+    pub fn set_hdr(&mut self, hdr: bool) -> &mut Self {
+        self.hdr = hdr;
+        self
+    }
+
+    // The app renders at this size in physical pixels whatever the window size: Context::size
+    // reports it and App::resize isn't called. The frame is scaled to fit the window, keeping
+    // its aspect ratio, with black bars on the sides left over. Context::letterbox gives the
+    // area it covers, to map the mouse. run fails for a 0 width or height.
+    pub fn set_fixed_resolution(&mut self, width: u32, height: u32) -> &mut Self {
+        self.fixed_resolution = Some((width, height));
+        self
+    }
+
+    // How set_fixed_resolution scales the frame, Nearest by default for pixel art
+    pub fn set_fixed_resolution_filter(&mut self, filter: wgpu::FilterMode) -> &mut Self {
+        self.fixed_resolution_filter = filter;
+        self
+    }
+
+    // Sets up the runner like the context Context::describe was called on: adapter, present
+    // mode, formats, MSAA, features and limits. run fails with AdapterNotFound, MissingFeatures
+    // or UnsupportedLimits when this machine can't match them, and with SurfaceFormatMismatch
+    // when the surface gets another format.
+    pub fn set_description(&mut self, description: &ContextDescription) -> &mut Self {
+        self.wgpu_options.supported_backends = description.adapter.backend.into();
+        self.adapter = Some(AdapterSelector::Name(description.adapter.name.clone()));
+        if let Some(present_mode) = description.present_mode {
+            self.wgpu_options.present_mode = present_mode;
         }
+        self.surface_format = description.surface_format;
+        self.hdr = description.hdr;
+        self.depth_stencil_format = description.depth_stencil_format;
+        self.sample_count = description.sample_count;
+        self.features = description.features;
+        self.limits = LimitsRequest::Custom(description.limits.clone());
+        self
+    }
+
+    // Radius around the center of the sticks read as 0 by Gamepads::axis_value, 0.1 by default
+    #[cfg(feature = "gamepad")]
+    pub fn set_gamepad_deadzone(&mut self, deadzone: f32) -> &mut Self {
+        self.gamepad_deadzone = deadzone;
+        self
+    }
+
+    pub fn set_show_fps(&mut self, show_fps: bool) -> &mut Self {
+        self.show_fps = show_fps;
+        self
+    }
+
+    // Whether the DebugOverlay is shown at start, F3 toggles it either way
+    pub fn set_debug_overlay(&mut self, debug_overlay: bool) -> &mut Self {
+        self.debug_overlay = debug_overlay;
+        self
+    }
+
+    // In seconds, run fails with InvalidFixedTimestep unless it's finite and positive
+    pub fn set_fixed_timestep(&mut self, fixed_delta_time: f32) -> &mut Self {
+        self.fixed_timestep = Some(fixed_delta_time);
+        self
+    }
+
+    // On by default: without focus the app isn't updated and frames are only drawn when the
+    // system asks for one. Turn it off for apps that must keep running in the background.
+    pub fn set_pause_on_blur(&mut self, pause_on_blur: bool) -> &mut Self {
+        self.pause_on_blur = pause_on_blur;
+        self
+    }
+
+    // Frames are only drawn on input events, egui animations and Context::request_redraw, the
+    // window sleeps in between. Delta time is still measured from the last frame drawn (capped
+    // like any other, see Time). Gamepads and file watchers don't wake it, poll them with
+    // Context::request_redraw_after.
+    pub fn set_render_on_demand(&mut self, render_on_demand: bool) -> &mut Self {
+        self.render_on_demand = render_on_demand;
+        self
+    }
+
+    // A window the desktop shows through wherever the clear color (give Runner::new a
+    // translucent one, egui::Color32::TRANSPARENT for none) and what the app draws aren't
+    // opaque. Colors are premultiplied by alpha. eframe picks the surface alpha mode,
+    // PreMultiplied or else PostMultiplied, and logs a warning and stays opaque when the
+    // compositor supports neither. The tonemap of set_hdr writes an opaque frame.
+    pub fn set_transparent(&mut self, transparent: bool) -> &mut Self {
+        self.transparent = transparent;
+        self
     }
 
-    pub fn run(&mut self) {
+    // Caps the frame rate even when the present mode doesn't, 0 means no cap. The wait
+    // accounts for the time already spent on the frame. Ignored on the web, where the browser
+    // paces frames.
+    pub fn set_target_fps(&mut self, target_fps: u32) -> &mut Self {
+        self.target_fps = target_fps;
+        self
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run(&mut self) -> Result<(), ContextError> {
+        let config = self.run_config()?;
+
+        let mut viewport = egui::ViewportBuilder::default()
+            .with_inner_size((self.width as f32, self.height as f32))
+            .with_transparent(self.transparent);
+        // Without a title eframe uses the app name
+        if let Some(title) = &self.title {
+            viewport = viewport.with_title(title);
+        }
+        if let Some(bytes) = &self.window_icon {
+            let icon = image::load_from_memory(bytes)
+                .map_err(ContextError::Image)?
+                .into_rgba8();
+            viewport = viewport.with_icon(egui::IconData {
+                width: icon.width(),
+                height: icon.height(),
+                rgba: icon.into_raw(),
+            });
+        }
+
         let native_options = eframe::NativeOptions {
-            viewport: egui::ViewportBuilder::default()
-                .with_inner_size((self.width as f32, self.height as f32)),
-            depth_buffer: self.depth_buffer,
-            stencil_buffer: self.stencil_buffer,
+            viewport,
+            depth_buffer: config.depth_buffer,
+            stencil_buffer: config.stencil_buffer,
+            multisampling: self.sample_count as u16,
+            wgpu_options: config.wgpu_options,
             ..Default::default()
         };
 
-        let depth_stencil_format = depth_format_from_bits(self.depth_buffer, self.stencil_buffer);
-
-        let _ = eframe::run_native(
+        let settings = self.frame_settings(config.out_of_memory);
+        let result = eframe::run_native(
             &self.app_name,
             native_options,
             Box::new(|cc| {
-                Ok(Box::new(EframeApp::new(
+                create_app(
                     cc,
-                    self.width,
-                    self.height,
-                    self.bg_color,
-                    depth_stencil_format,
-                    self.app_creator.take().unwrap(),
-                )))
+                    settings,
+                    self.srgb,
+                    self.surface_format,
+                    self.app_creator.take(),
+                )
             }),
         );
+
+        // The device descriptor callback can't fail, so a validation error found there
+        // replaces the less descriptive error returned by request_device.
+        if let Some(err) = config.device_error.lock().unwrap().take() {
+            return Err(err);
+        }
+        result?;
+
+        Ok(())
+    }
+
+    // Attaches to the <canvas> with this id and returns right away, the browser drives the frames.
+    // Errors found once started are logged. The web painter has no MSAA and no stencil buffer.
+    #[cfg(target_arch = "wasm32")]
+    pub fn run_web(&mut self, canvas_id: &str) -> Result<(), ContextError> {
+        use eframe::wasm_bindgen::JsCast;
+
+        if self.sample_count != 1 {
+            log::warn!("MSAA isn't available on the web, rendering without it");
+            self.sample_count = 1;
+        }
+        if let Some((depth_buffer, stencil_buffer @ 1..)) =
+            self.depth_stencil_format.and_then(depth_bits_from_format)
+        {
+            log::warn!("{stencil_buffer} bits stencil buffer isn't available on the web, ignored");
+            self.depth_stencil_format = depth_format_from_bits(depth_buffer, 0);
+        }
+        if self.target_fps > 0 {
+            log::warn!("target FPS ignored on the web, the browser paces frames");
+            self.target_fps = 0;
+        }
+        if self.push_constant_size > 0 || self.features.contains(wgpu::Features::PUSH_CONSTANTS) {
+            log::error!("push constants aren't available on the web, use a uniform buffer");
+            return Err(ContextError::MissingFeatures(
+                wgpu::Features::PUSH_CONSTANTS,
+            ));
+        }
+        let config = self.run_config()?;
+
+        let canvas = eframe::web_sys::window()
+            .and_then(|window| window.document())
+            .and_then(|document| document.get_element_by_id(canvas_id))
+            .and_then(|element| {
+                element
+                    .dyn_into::<eframe::web_sys::HtmlCanvasElement>()
+                    .ok()
+            })
+            .ok_or(ContextError::NoWindow)?;
+
+        let web_options = eframe::WebOptions {
+            depth_buffer: config.depth_buffer,
+            wgpu_options: config.wgpu_options,
+            ..Default::default()
+        };
+
+        let settings = self.frame_settings(config.out_of_memory);
+        let srgb = self.srgb;
+        let surface_format = self.surface_format;
+        let app_creator = self.app_creator.take();
+        let device_error = config.device_error;
+        wasm_bindgen_futures::spawn_local(async move {
+            let result = eframe::WebRunner::new()
+                .start(
+                    canvas,
+                    web_options,
+                    Box::new(move |cc| create_app(cc, settings, srgb, surface_format, app_creator)),
+                )
+                .await;
+            if let Some(err) = device_error.lock().unwrap().take() {
+                log::error!("{err}");
+            } else if let Err(err) = result {
+                log::error!("{err:?}");
+            }
+        });
+
+        Ok(())
+    }
+
+    // Shared by the native and web entry points
+    fn run_config(&self) -> Result<RunConfig, ContextError> {
+        let (depth_buffer, stencil_buffer) = self.check_device_options()?;
+        if let Some(0) = self.wgpu_options.desired_maximum_frame_latency {
+            return Err(ContextError::InvalidFrameLatency(0));
+        }
+        // The accumulator loop would never end on 0 and never run on NaN
+        if let Some(fixed_delta_time) = self.fixed_timestep {
+            if !fixed_delta_time.is_finite() || fixed_delta_time <= 0.0 {
+                return Err(ContextError::InvalidFixedTimestep(fixed_delta_time));
+            }
+        }
+        if let Some((width @ 0, height) | (width, height @ 0)) = self.fixed_resolution {
+            return Err(ContextError::InvalidSize(format!(
+                "fixed resolution must be at least 1x1, got {width}x{height}"
+            )));
+        }
+
+        let device_error = Arc::new(Mutex::new(None));
+        let out_of_memory = Arc::new(AtomicBool::new(false));
+
+        #[cfg(not(target_arch = "wasm32"))]
+        warn_ignored_debug_env();
+
+        let mut wgpu_options = self.wgpu_options.clone();
+        wgpu_options.on_surface_error = surface_error_handler(out_of_memory.clone());
+        // WGPU_BACKEND always wins so a backend can be forced without recompiling
+        if let Some(backends) = wgpu::util::backend_bits_from_env() {
+            wgpu_options.supported_backends = backends;
+        }
+        // eframe requests the adapter itself, so it is steered to the chosen one through its
+        // backend and power preference. device_descriptor reports it if another one is picked.
+        let adapter_info = self.selected_adapter(wgpu_options.supported_backends)?;
+        if let Some(info) = &adapter_info {
+            wgpu_options.supported_backends = info.backend.into();
+            match info.device_type {
+                wgpu::DeviceType::DiscreteGpu => {
+                    wgpu_options.power_preference = wgpu::PowerPreference::HighPerformance;
+                }
+                wgpu::DeviceType::IntegratedGpu => {
+                    wgpu_options.power_preference = wgpu::PowerPreference::LowPower;
+                }
+                _ => {}
+            }
+        }
+        wgpu_options.device_descriptor = device_descriptor(
+            adapter_info,
+            self.required_features(),
+            self.optional_features,
+            self.limits.clone(),
+            self.push_constant_size,
+            self.sample_count,
+            self.depth_stencil_format,
+            device_error.clone(),
+        );
+        // egui configures the surface without looking at its capabilities and wgpu rejects
+        // unsupported explicit modes, so those go through the Auto modes that fall back on Fifo.
+        wgpu_options.present_mode = match wgpu_options.present_mode {
+            mode @ (wgpu::PresentMode::Immediate | wgpu::PresentMode::Mailbox) => {
+                log::info!("{mode:?} requested, presenting with AutoNoVsync to allow a fallback");
+                wgpu::PresentMode::AutoNoVsync
+            }
+            wgpu::PresentMode::FifoRelaxed => {
+                log::info!("FifoRelaxed requested, presenting with AutoVsync to allow a fallback");
+                wgpu::PresentMode::AutoVsync
+            }
+            mode => mode,
+        };
+
+        Ok(RunConfig {
+            wgpu_options,
+            depth_buffer,
+            stencil_buffer,
+            device_error,
+            out_of_memory,
+        })
+    }
+
+    // The sample count and depth format checks of run, returns the depth and stencil bits
+    fn check_device_options(&self) -> Result<(u8, u8), ContextError> {
+        if !matches!(self.sample_count, 1 | 2 | 4 | 8) {
+            return Err(ContextError::UnsupportedSampleCount(self.sample_count));
+        }
+        match self.depth_stencil_format {
+            Some(format) => {
+                depth_bits_from_format(format).ok_or(ContextError::UnsupportedDepthFormat(format))
+            }
+            None => Ok((0, 0)),
+        }
+    }
+
+    fn required_features(&self) -> wgpu::Features {
+        let mut features = self.features;
+        if self.push_constant_size > 0 {
+            features |= wgpu::Features::PUSH_CONSTANTS;
+        }
+        features
+    }
+
+    // The device run would request and its depth buffer, checked the same way
+    #[cfg(not(target_arch = "wasm32"))]
+    pub(crate) fn device_options(
+        &self,
+        backends: wgpu::Backends,
+    ) -> Result<DeviceOptions, ContextError> {
+        self.check_device_options()?;
+        let adapter = self.selected_adapter(backends)?;
+        let device_error = Arc::new(Mutex::new(None));
+        Ok(DeviceOptions {
+            device_descriptor: device_descriptor(
+                adapter.clone(),
+                self.required_features(),
+                self.optional_features,
+                self.limits.clone(),
+                self.push_constant_size,
+                self.sample_count,
+                self.depth_stencil_format,
+                device_error.clone(),
+            ),
+            adapter,
+            depth_stencil_format: self.depth_stencil_format,
+            sample_count: self.sample_count,
+            device_error,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn selected_adapter(
+        &self,
+        backends: wgpu::Backends,
+    ) -> Result<Option<wgpu::AdapterInfo>, ContextError> {
+        let Some(selector) = &self.adapter else {
+            return Ok(None);
+        };
+        let adapters = Context::list_adapters(backends);
+        let info = match selector {
+            AdapterSelector::Name(name) => {
+                adapters.into_iter().find(|info| info.name.contains(name))
+            }
+            AdapterSelector::Index(index) => adapters.into_iter().nth(*index),
+        };
+        info.map(Some)
+            .ok_or_else(|| ContextError::AdapterNotFound(selector.to_string()))
+    }
+
+    // Browsers don't list adapters
+    #[cfg(target_arch = "wasm32")]
+    fn selected_adapter(
+        &self,
+        _backends: wgpu::Backends,
+    ) -> Result<Option<wgpu::AdapterInfo>, ContextError> {
+        if let Some(selector) = &self.adapter {
+            log::warn!("adapter {selector} ignored on the web");
+        }
+        Ok(None)
     }
+
+    fn frame_settings(&self, out_of_memory: Arc<AtomicBool>) -> FrameSettings {
+        FrameSettings {
+            width: self.width,
+            height: self.height,
+            title: self.title.clone().unwrap_or_else(|| self.app_name.clone()),
+            bg_color: self.bg_color,
+            depth_stencil_format: self.depth_stencil_format,
+            present_mode: self.wgpu_options.present_mode,
+            sample_count: self.sample_count,
+            hdr: self.hdr,
+            fixed_resolution: self.fixed_resolution,
+            fixed_resolution_filter: self.fixed_resolution_filter,
+            #[cfg(feature = "gamepad")]
+            gamepad_deadzone: self.gamepad_deadzone,
+            show_fps: self.show_fps,
+            debug_overlay: self.debug_overlay,
+            fixed_timestep: self.fixed_timestep,
+            pause_on_blur: self.pause_on_blur,
+            render_on_demand: self.render_on_demand,
+            transparent: self.transparent,
+            asset_root: self.asset_root.clone(),
+            actions: self.actions.clone(),
+            frame_duration: (self.target_fps > 0)
+                .then(|| Duration::from_secs_f64(1.0 / self.target_fps as f64)),
+            out_of_memory,
+        }
+    }
+}
+
+struct RunConfig {
+    wgpu_options: egui_wgpu::WgpuConfiguration,
+    depth_buffer: u8,
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+    stencil_buffer: u8,
+    device_error: Arc<Mutex<Option<ContextError>>>,
+    out_of_memory: Arc<AtomicBool>,
 }
 
+fn create_app(
+    cc: &eframe::CreationContext<'_>,
+    settings: FrameSettings,
+    srgb: Option<bool>,
+    surface_format: Option<wgpu::TextureFormat>,
+    app_creator: Option<AppCreator>,
+) -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync>> {
+    let app_creator = app_creator.ok_or(ContextError::AlreadyRan)?;
+    if let (Some(srgb), Some(render_state)) = (srgb, &cc.wgpu_render_state) {
+        if render_state.target_format.is_srgb() != srgb {
+            return Err(ContextError::SurfaceFormat(render_state.target_format).into());
+        }
+    }
+    if let (Some(format), Some(render_state)) = (surface_format, &cc.wgpu_render_state) {
+        if render_state.target_format != format {
+            return Err(
+                ContextError::SurfaceFormatMismatch(format, render_state.target_format).into(),
+            );
+        }
+    }
+    Ok(Box::new(EframeApp::new(cc, settings, app_creator)?))
+}
+
+// Inverse of egui_wgpu::depth_format_from_bits, eframe only takes the bit counts
+fn depth_bits_from_format(format: wgpu::TextureFormat) -> Option<(u8, u8)> {
+    match format {
+        wgpu::TextureFormat::Stencil8 => Some((0, 8)),
+        wgpu::TextureFormat::Depth16Unorm => Some((16, 0)),
+        wgpu::TextureFormat::Depth24Plus => Some((24, 0)),
+        wgpu::TextureFormat::Depth24PlusStencil8 => Some((24, 8)),
+        wgpu::TextureFormat::Depth32Float => Some((32, 0)),
+        wgpu::TextureFormat::Depth32FloatStencil8 => Some((32, 8)),
+        _ => None,
+    }
+}
+
+type SurfaceErrorFn =
+    Arc<dyn Fn(wgpu::SurfaceError) -> egui_wgpu::SurfaceErrorAction + Send + Sync>;
+
+// egui only skips the frame by default, which leaves a lost surface black for good
+fn surface_error_handler(out_of_memory: Arc<AtomicBool>) -> SurfaceErrorFn {
+    Arc::new(move |err| match err {
+        wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated => {
+            log::debug!("surface {err}, reconfiguring");
+            egui_wgpu::SurfaceErrorAction::RecreateSurface
+        }
+        wgpu::SurfaceError::OutOfMemory => {
+            log::error!("surface {err}, exiting");
+            // The window can only be closed from update, the flag is checked there
+            out_of_memory.store(true, Ordering::Relaxed);
+            egui_wgpu::SurfaceErrorAction::SkipFrame
+        }
+        wgpu::SurfaceError::Timeout => {
+            log::warn!("surface {err}, skipping frame");
+            egui_wgpu::SurfaceErrorAction::SkipFrame
+        }
+    })
+}
+
+pub(crate) fn log_adapter_info(info: &wgpu::AdapterInfo) {
+    // Either can be empty depending on the backend
+    let driver = format!("{} {}", info.driver, info.driver_info);
+    log::info!(
+        "using {} ({:?}, {:?} backend, driver: {})",
+        info.name,
+        info.device_type,
+        info.backend,
+        driver.trim()
+    );
+}
+
+// The other reasons are reported when the device is dropped or the callback replaced
+pub(crate) fn device_lost_callback(
+    device_lost: Arc<AtomicBool>,
+) -> impl Fn(wgpu::DeviceLostReason, String) + Send + 'static {
+    move |reason, message| {
+        if matches!(
+            reason,
+            wgpu::DeviceLostReason::Unknown | wgpu::DeviceLostReason::DeviceInvalid
+        ) {
+            log::error!("GPU device lost ({reason:?}): {message}");
+            device_lost.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+// eframe creates the instance and requests the device of the window itself, with validation
+// and debug info only in debug builds, so these variables would otherwise be silently ignored
+#[cfg(not(target_arch = "wasm32"))]
+fn warn_ignored_debug_env() {
+    for var in ["WGPU_VALIDATION", "WGPU_DEBUG", "WGPU_GPU_BASED_VALIDATION"] {
+        if std::env::var_os(var).is_some() {
+            log::warn!(
+                "{var} only applies to HeadlessContext, the window validates in debug builds"
+            );
+        }
+    }
+    if std::env::var_os("WGPU_TRACE").is_some() {
+        log::warn!("WGPU_TRACE is ignored, eframe requests the device without a trace path");
+    }
+}
+
+// What HeadlessContext takes from a runner, see Runner::device_options
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct DeviceOptions {
+    pub(crate) adapter: Option<wgpu::AdapterInfo>,
+    pub(crate) depth_stencil_format: Option<wgpu::TextureFormat>,
+    pub(crate) sample_count: u32,
+    pub(crate) device_descriptor: DeviceDescriptorFn,
+    // Set by device_descriptor when the adapter can't meet the options
+    pub(crate) device_error: Arc<Mutex<Option<ContextError>>>,
+}
+
+// The defaults of a runner, with a 32 bit depth buffer
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for DeviceOptions {
+    fn default() -> Self {
+        let depth_stencil_format = Some(wgpu::TextureFormat::Depth32Float);
+        let device_error = Arc::new(Mutex::new(None));
+        Self {
+            adapter: None,
+            depth_stencil_format,
+            sample_count: 1,
+            device_descriptor: device_descriptor(
+                None,
+                wgpu::Features::empty(),
+                wgpu::Features::empty(),
+                LimitsRequest::Default,
+                0,
+                1,
+                depth_stencil_format,
+                device_error.clone(),
+            ),
+            device_error,
+        }
+    }
+}
+
+type DeviceDescriptorFn =
+    Arc<dyn Fn(&wgpu::Adapter) -> wgpu::DeviceDescriptor<'static> + Send + Sync>;
+
+#[allow(clippy::too_many_arguments)]
+fn device_descriptor(
+    expected_adapter: Option<wgpu::AdapterInfo>,
+    features: wgpu::Features,
+    optional_features: wgpu::Features,
+    limits: LimitsRequest,
+    push_constant_size: u32,
+    sample_count: u32,
+    depth_stencil_format: Option<wgpu::TextureFormat>,
+    device_error: Arc<Mutex<Option<ContextError>>>,
+) -> DeviceDescriptorFn {
+    Arc::new(move |adapter| {
+        let mut limits = match &limits {
+            LimitsRequest::Default => {
+                let base_limits = if adapter.get_info().backend == wgpu::Backend::Gl {
+                    wgpu::Limits::downlevel_webgl2_defaults()
+                } else {
+                    wgpu::Limits::default()
+                };
+                // Same as egui: the depth texture must cover 4k+ displays
+                wgpu::Limits {
+                    max_texture_dimension_2d: 8192,
+                    ..base_limits
+                }
+            }
+            LimitsRequest::Custom(limits) => limits.clone(),
+            LimitsRequest::Adapter => adapter.limits(),
+        };
+        limits.max_push_constant_size = limits.max_push_constant_size.max(push_constant_size);
+
+        let missing_features = features - adapter.features();
+        let features = features | (optional_features & adapter.features());
+        let mut unsupported_limits = Vec::new();
+        limits.check_limits_with_fail_fn(&adapter.limits(), false, |name, requested, allowed| {
+            unsupported_limits.push(format!("{name} (requested {requested}, allowed {allowed})"));
+        });
+
+        // egui picks one of these for the surface when available
+        let color_supported = [
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Bgra8Unorm,
+        ]
+        .iter()
+        .any(|format| {
+            adapter
+                .get_texture_format_features(*format)
+                .flags
+                .sample_count_supported(sample_count)
+        });
+        let depth_supported = depth_stencil_format.map_or(true, |format| {
+            adapter
+                .get_texture_format_features(format)
+                .flags
+                .sample_count_supported(sample_count)
+        });
+
+        let picked = adapter.get_info();
+        let wrong_adapter = expected_adapter
+            .as_ref()
+            .filter(|expected| expected.name != picked.name || expected.backend != picked.backend);
+
+        if let Some(expected) = wrong_adapter {
+            *device_error.lock().unwrap() = Some(ContextError::AdapterNotSelected(
+                format!("{} ({:?})", expected.name, expected.backend),
+                format!("{} ({:?})", picked.name, picked.backend),
+            ));
+            // Same trick as below to stop eframe from going on with this adapter
+            limits.max_bind_groups = u32::MAX;
+        } else if !missing_features.is_empty() {
+            *device_error.lock().unwrap() = Some(ContextError::MissingFeatures(missing_features));
+        } else if !unsupported_limits.is_empty() {
+            *device_error.lock().unwrap() =
+                Some(ContextError::UnsupportedLimits(unsupported_limits));
+        } else if !color_supported || !depth_supported {
+            *device_error.lock().unwrap() =
+                Some(ContextError::UnsupportedSampleCount(sample_count));
+            // Make request_device fail, eframe would otherwise go on and panic while
+            // creating the multisampled textures.
+            limits.max_bind_groups = u32::MAX;
+        }
+
+        wgpu::DeviceDescriptor {
+            label: Some("wgpu-bootstrap device"),
+            required_features: features,
+            required_limits: limits,
+            memory_hints: wgpu::MemoryHints::default(),
+        }
+    })
+}
+
+#[derive(Default)]
+pub struct FpsCounter {
+    frame_times: VecDeque<f32>,
+    total: f32,
+    gpu_times: RefCell<Vec<(String, f32)>>,
+}
+
+impl FpsCounter {
+    pub fn fps(&self) -> f32 {
+        if self.total > 0.0 {
+            self.frame_times.len() as f32 / self.total
+        } else {
+            0.0
+        }
+    }
+
+    pub fn avg_frame_time_ms(&self) -> f32 {
+        if self.frame_times.is_empty() {
+            0.0
+        } else {
+            1000.0 * self.total / self.frame_times.len() as f32
+        }
+    }
+
+    pub fn max_frame_time_ms(&self) -> f32 {
+        1000.0 * self.frame_times.iter().copied().fold(0.0, f32::max)
+    }
+
+    // Per scope milliseconds of the last frame read by a GpuProfiler, empty without one
+    pub fn gpu_times_ms(&self) -> Vec<(String, f32)> {
+        self.gpu_times.borrow().clone()
+    }
+
+    pub fn gpu_frame_time_ms(&self) -> Option<f32> {
+        let gpu_times = self.gpu_times.borrow();
+        if gpu_times.is_empty() {
+            None
+        } else {
+            Some(gpu_times.iter().map(|(_, ms)| ms).sum())
+        }
+    }
+
+    pub(crate) fn set_gpu_times(&self, gpu_times: Vec<(String, f32)>) {
+        *self.gpu_times.borrow_mut() = gpu_times;
+    }
+
+    fn push(&mut self, frame_time: f32) {
+        self.frame_times.push_back(frame_time);
+        self.total += frame_time;
+        // Keep a rolling one second window
+        while self.total - self.frame_times[0] >= 1.0 {
+            self.total -= self.frame_times.pop_front().unwrap();
+        }
+    }
+}
+
+struct FrameSettings {
+    width: u32,
+    height: u32,
+    title: String,
+    bg_color: egui::Color32,
+    depth_stencil_format: Option<wgpu::TextureFormat>,
+    present_mode: wgpu::PresentMode,
+    sample_count: u32,
+    hdr: bool,
+    fixed_resolution: Option<(u32, u32)>,
+    fixed_resolution_filter: wgpu::FilterMode,
+    #[cfg(feature = "gamepad")]
+    gamepad_deadzone: f32,
+    show_fps: bool,
+    debug_overlay: bool,
+    fixed_timestep: Option<f32>,
+    pause_on_blur: bool,
+    render_on_demand: bool,
+    transparent: bool,
+    asset_root: Option<PathBuf>,
+    actions: ActionMap,
+    frame_duration: Option<Duration>,
+    out_of_memory: Arc<AtomicBool>,
+}
+
+// Sleeping overshoots by up to a millisecond or two, so the end of the wait is spun
+#[cfg(not(target_arch = "wasm32"))]
+const SPIN_MARGIN: Duration = Duration::from_micros(1500);
+
+#[cfg(not(target_arch = "wasm32"))]
+fn wait_until(deadline: Instant) {
+    let now = Instant::now();
+    if deadline > now + SPIN_MARGIN {
+        std::thread::sleep(deadline - now - SPIN_MARGIN);
+    }
+    while Instant::now() < deadline {
+        std::hint::spin_loop();
+    }
+}
+
+// Caps the fixed steps run in one frame so a slow fixed_update can't snowball
+const MAX_FIXED_STEPS: u32 = 8;
+
 struct EframeApp {
+    settings: FrameSettings,
+    device: Arc<wgpu::Device>,
     window_width: u32,
     window_height: u32,
-    bg_color: egui::Color32,
-    depth_stencil_format: Option<wgpu::TextureFormat>,
+    pixels_per_point: f32,
     last: Option<Instant>,
-    app: Arc<dyn App + Send + Sync>,
+    time: Time,
+    fps_counter: FpsCounter,
+    input: Input,
+    debug_overlay: DebugOverlay,
+    next_title_update: f32,
+    accumulator: f32,
+    device_lost: Arc<AtomicBool>,
+    capture: FrameCapture,
+    clear_color: Cell<wgpu::Color>,
+    exposure: Cell<f32>,
+    adapter_info: wgpu::AdapterInfo,
+    downlevel_flags: wgpu::DownlevelFlags,
+    uploader: Uploader,
+    resources: ResourceTracker,
+    // The frame's commands, egui submits them with its own from the paint callback
+    frame_commands: Arc<Mutex<Option<wgpu::CommandBuffer>>>,
+    offscreen: Option<Offscreen>,
+    #[cfg(feature = "gamepad")]
+    gamepads: Gamepads,
+    app: Arc<AppStack>,
 }
 
 impl EframeApp {
     fn new(
         cc: &eframe::CreationContext<'_>,
-        width: u32,
-        height: u32,
-        bg_color: egui::Color32,
-        depth_stencil_format: Option<wgpu::TextureFormat>,
-        app_creator: Box<dyn FnOnce(&Context) -> Arc<dyn App + Send + Sync>>,
-    ) -> Self {
-        let wgpu_render_state = cc.wgpu_render_state.as_ref().unwrap();
+        settings: FrameSettings,
+        app_creator: AppCreator,
+    ) -> Result<Self, ContextError> {
+        let wgpu_render_state = cc
+            .wgpu_render_state
+            .as_ref()
+            .ok_or(ContextError::NoAdapter)?;
         let device = wgpu_render_state.device.as_ref();
         let queue = wgpu_render_state.queue.as_ref();
         let format = wgpu_render_state.target_format;
 
-        let context = Context {
+        let adapter_info = wgpu_render_state.adapter.get_info();
+        log_adapter_info(&adapter_info);
+        let downlevel_flags = wgpu_render_state.adapter.get_downlevel_capabilities().flags;
+
+        let device_lost = Arc::new(AtomicBool::new(false));
+        device.set_device_lost_callback(device_lost_callback(device_lost.clone()));
+
+        let fps_counter = FpsCounter::default();
+        let input = Input::with_actions(settings.actions.clone());
+        let debug_overlay = DebugOverlay::new(settings.debug_overlay);
+        let capture = FrameCapture::default();
+        let uploader = Uploader::default();
+        let resources = ResourceTracker::default();
+        let bg_color = egui::Rgba::from(settings.bg_color);
+        let clear_color = Cell::new(wgpu::Color {
+            r: bg_color.r() as f64,
+            g: bg_color.g() as f64,
+            b: bg_color.b() as f64,
+            a: bg_color.a() as f64,
+        });
+        let exposure = Cell::new(1.0);
+        #[cfg(feature = "gamepad")]
+        let gamepads = Gamepads::new(settings.gamepad_deadzone);
+        // The window size is given in logical points
+        let pixels_per_point = cc.egui_ctx.pixels_per_point();
+        let window_width = (settings.width as f32 * pixels_per_point).round() as u32;
+        let window_height = (settings.height as f32 * pixels_per_point).round() as u32;
+        let mut context = Context {
             device,
             queue,
-            size: egui::vec2(width as f32, height as f32),
+            size: egui::vec2(window_width as f32, window_height as f32),
+            scale_factor: pixels_per_point,
             format,
-            depth_stencil_format,
+            surface_format: Some(format),
+            present_mode: Some(settings.present_mode),
+            depth_stencil_format: settings.depth_stencil_format,
+            sample_count: settings.sample_count,
+            time: Time::default(),
+            fps_counter: &fps_counter,
+            input: &input,
+            egui_ctx: Some(&cc.egui_ctx),
+            capture: &capture,
+            clear_color: &clear_color,
+            exposure: &exposure,
+            adapter: wgpu_render_state.adapter.as_ref(),
+            adapter_info: &adapter_info,
+            downlevel_flags,
+            uploader: &uploader,
+            resources: &resources,
+            hdr_target: None,
+            letterbox: None,
+            asset_root: settings.asset_root.as_deref(),
+            #[cfg(feature = "gamepad")]
+            gamepads: &gamepads,
         };
+        // The resolve pass is created for the surface and the window, the app for the target
+        let offscreen = (settings.hdr || settings.fixed_resolution.is_some())
+            .then(|| {
+                Offscreen::new(
+                    &context,
+                    settings.hdr,
+                    settings.fixed_resolution,
+                    settings.fixed_resolution_filter,
+                )
+            })
+            .transpose()?;
+        if settings.hdr {
+            context.format = HDR_FORMAT;
+            context.hdr_target = offscreen.as_ref().map(|offscreen| &offscreen.target);
+        }
+        if let Some((width, height)) = settings.fixed_resolution {
+            context.size = egui::vec2(width as f32, height as f32);
+            let screen = egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(settings.width as f32, settings.height as f32),
+            );
+            context.letterbox = Some(letterbox(screen, (width, height)));
+        }
+        let app = Arc::new(AppStack::new(app_creator(&context)));
 
-        Self {
-            window_width: width,
-            window_height: height,
-            bg_color,
-            depth_stencil_format,
+        Ok(Self {
+            device: wgpu_render_state.device.clone(),
+            window_width,
+            window_height,
+            pixels_per_point,
+            settings,
             last: None,
-            app: app_creator(&context),
-        }
+            time: Time::default(),
+            fps_counter,
+            input,
+            debug_overlay,
+            next_title_update: 0.0,
+            accumulator: 0.0,
+            device_lost,
+            capture,
+            clear_color,
+            exposure,
+            adapter_info,
+            downlevel_flags,
+            uploader,
+            resources,
+            frame_commands: Arc::default(),
+            offscreen,
+            #[cfg(feature = "gamepad")]
+            gamepads,
+            app,
+        })
     }
 }
 
 impl eframe::App for EframeApp {
     fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if self.settings.out_of_memory.load(Ordering::Relaxed) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+
+        // Not requesting a repaint lets eframe wait for events. The clock restarts on focus so
+        // the first frame back doesn't get the whole pause as delta time.
+        if self.settings.pause_on_blur && !ctx.input(|input| input.focused) {
+            self.last = None;
+            self.show_app(ctx);
+            self.debug_overlay.show(ctx);
+            return;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let (Some(frame_duration), Some(last)) = (self.settings.frame_duration, self.last) {
+            wait_until(last + frame_duration);
+        }
+
         let now = Instant::now();
         let delta_time = match self.last {
             Some(last) => now.duration_since(last).as_secs_f32(),
             None => 0.0,
         };
         self.last = Some(now);
+        if delta_time > 0.0 {
+            self.fps_counter.push(delta_time);
+        }
+        self.time.tick(delta_time);
+        let delta_time = self.time.delta_seconds();
+
+        let mut fixed_steps = 0;
+        if let Some(fixed_delta_time) = self.settings.fixed_timestep {
+            self.accumulator =
+                (self.accumulator + delta_time).min(fixed_delta_time * MAX_FIXED_STEPS as f32);
+            while self.accumulator >= fixed_delta_time {
+                self.accumulator -= fixed_delta_time;
+                fixed_steps += 1;
+            }
+            self.time.set_alpha(self.accumulator / fixed_delta_time);
+        }
+
+        if self.settings.show_fps && self.time.elapsed_seconds() >= self.next_title_update {
+            self.next_title_update = self.time.elapsed_seconds() + 0.5;
+            let mut title = format!(
+                "{} - {:.0} FPS",
+                self.settings.title,
+                self.fps_counter.fps()
+            );
+            if let Some(gpu_time) = self.fps_counter.gpu_frame_time_ms() {
+                title.push_str(&format!(" - GPU {gpu_time:.2} ms"));
+            }
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title));
+        }
+
+        self.input = Input::new(ctx, std::mem::take(&mut self.input));
+        #[cfg(feature = "gamepad")]
+        let gamepad_events = self.gamepads.poll();
+        self.save_screenshot();
 
         let wgpu_render_state = frame.wgpu_render_state().unwrap();
         let device = wgpu_render_state.device.clone();
         let queue = wgpu_render_state.queue.clone();
-        let format = wgpu_render_state.target_format;
+        // A frame egui didn't paint (minimized window) still has its commands submitted, before
+        // the staging buffers they copy from are recalled
+        if let Some(commands) = self.frame_commands.lock().unwrap().take() {
+            queue.submit(std::iter::once(commands));
+        }
+        self.uploader.recall();
+        if let Some(offscreen) = &mut self.offscreen {
+            offscreen.set_exposure(&queue, self.exposure.get());
+        }
+        let format = if self.settings.hdr {
+            HDR_FORMAT
+        } else {
+            wgpu_render_state.target_format
+        };
         // let app: &mut Box<dyn App> = writer.callback_resources.get_mut().unwrap();
         let mut context = Context {
             device: device.as_ref(),
             queue: queue.as_ref(),
-            size: egui::vec2(self.window_width as f32, self.window_height as f32),
+            size: self.render_size(),
+            scale_factor: self.pixels_per_point,
             format,
-            depth_stencil_format: self.depth_stencil_format,
+            surface_format: Some(wgpu_render_state.target_format),
+            present_mode: Some(self.settings.present_mode),
+            depth_stencil_format: self.settings.depth_stencil_format,
+            sample_count: self.settings.sample_count,
+            time: self.time,
+            fps_counter: &self.fps_counter,
+            input: &self.input,
+            egui_ctx: Some(ctx),
+            capture: &self.capture,
+            clear_color: &self.clear_color,
+            exposure: &self.exposure,
+            adapter: wgpu_render_state.adapter.as_ref(),
+            adapter_info: &self.adapter_info,
+            downlevel_flags: self.downlevel_flags,
+            uploader: &self.uploader,
+            resources: &self.resources,
+            hdr_target: None,
+            letterbox: self
+                .settings
+                .fixed_resolution
+                .map(|resolution| letterbox(ctx.screen_rect(), resolution)),
+            asset_root: self.settings.asset_root.as_deref(),
+            #[cfg(feature = "gamepad")]
+            gamepads: &self.gamepads,
         };
 
-        if ctx.screen_rect().width() as u32 != self.window_width
-            || ctx.screen_rect().height() as u32 != self.window_height
-        {
-            self.window_width = ctx.screen_rect().width() as u32;
-            self.window_height = ctx.screen_rect().height() as u32;
-            context.size = egui::vec2(self.window_width as f32, self.window_height as f32);
+        if self.device_lost.swap(false, Ordering::Relaxed) {
+            Arc::get_mut(&mut self.app)
+                .unwrap()
+                .on_device_lost(&context);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+        // The transition the top state asked for last frame
+        if !Arc::get_mut(&mut self.app).unwrap().apply_transition() {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+            return;
+        }
+
+        // A scale factor change (e.g. moving to another monitor) also resizes the framebuffer,
+        // even when the logical size stays the same
+        let pixels_per_point = ctx.pixels_per_point();
+        let window_width = (ctx.screen_rect().width() * pixels_per_point).round() as u32;
+        let window_height = (ctx.screen_rect().height() * pixels_per_point).round() as u32;
+        let resized = window_width != self.window_width
+            || window_height != self.window_height
+            || pixels_per_point != self.pixels_per_point;
+        if resized {
+            self.pixels_per_point = pixels_per_point;
+            self.window_width = window_width;
+            self.window_height = window_height;
+            context.size = self.render_size();
+            context.scale_factor = pixels_per_point;
+            if let (Some(offscreen), None) = (&mut self.offscreen, self.settings.fixed_resolution) {
+                offscreen.resize(&context, self.window_width, self.window_height);
+            }
+        }
+        // Set once the target is resized, so App::resize sees the new one
+        if self.settings.hdr {
+            context.hdr_target = self.offscreen.as_ref().map(|offscreen| &offscreen.target);
+        }
+        if resized && self.settings.fixed_resolution.is_none() {
             Arc::get_mut(&mut self.app).unwrap().resize(
                 self.window_width,
                 self.window_height,
                 &context,
             );
         }
-        let input = ctx.input(|i| i.clone());
-
+        #[cfg(feature = "gamepad")]
+        for event in &gamepad_events {
+            Arc::get_mut(&mut self.app)
+                .unwrap()
+                .gamepad_event(event, &context);
+        }
         if !ctx.wants_pointer_input() && !ctx.wants_keyboard_input() {
-            Arc::get_mut(&mut self.app).unwrap().input(input, &context);
+            let app = Arc::get_mut(&mut self.app).unwrap();
+            for event in &self.input.state().events {
+                if app.event(event, &context) {
+                    continue;
+                }
+                if let egui::Event::Key {
+                    key: egui::Key::F3,
+                    pressed: true,
+                    repeat: false,
+                    ..
+                } = event
+                {
+                    self.debug_overlay.toggle();
+                } else {
+                    handle_event(&context, event);
+                }
+            }
+            app.input(self.input.state().clone(), &context);
+        }
+
+        if let Some(fixed_delta_time) = self.settings.fixed_timestep {
+            let app = Arc::get_mut(&mut self.app).unwrap();
+            for _ in 0..fixed_steps {
+                app.fixed_update(fixed_delta_time, &context);
+            }
         }
 
         Arc::get_mut(&mut self.app)
@@ -198,8 +1417,82 @@ impl eframe::App for EframeApp {
         Arc::get_mut(&mut self.app)
             .unwrap()
             .render_gui(ctx, &context);
+        self.debug_overlay.refresh(&context);
+        self.debug_overlay.show(ctx);
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Frame Encoder"),
+        });
+        let app = Arc::get_mut(&mut self.app).unwrap();
+        app.prepare(&mut encoder, &context);
+        let mut passes = PassList::new();
+        app.passes(&mut passes, &context);
+        passes.execute(&mut encoder, &context);
+        if let Some(offscreen) = &self.offscreen {
+            {
+                let mut render_pass = offscreen
+                    .target
+                    .begin_pass(&mut encoder, Some(self.clear_color.get()));
+                self.app.render(&mut render_pass);
+            }
+            if self.settings.hdr {
+                Arc::get_mut(&mut self.app)
+                    .unwrap()
+                    .post_process(&mut encoder, &context);
+            }
+        }
+        self.uploader.finish();
+        *self.frame_commands.lock().unwrap() = Some(encoder.finish());
+
+        self.show_app(ctx);
+        if !self.settings.render_on_demand {
+            ctx.request_repaint();
+        }
+    }
+
+    fn clear_color(&self, _visuals: &Visuals) -> [f32; 4] {
+        if self.settings.transparent {
+            [0.0; 4]
+        } else {
+            [0.9, 0.4, 0.4, 1.0]
+        }
+    }
+}
+
+impl Drop for EframeApp {
+    fn drop(&mut self) {
+        // Let in-flight GPU work finish before the app's resources are dropped
+        self.device.poll(wgpu::Maintain::Wait);
+    }
+}
 
-        let container = egui::containers::Frame::default().fill(self.bg_color);
+impl EframeApp {
+    // Context::size, the window's or the fixed resolution
+    fn render_size(&self) -> egui::Vec2 {
+        let (width, height) = self
+            .settings
+            .fixed_resolution
+            .unwrap_or((self.window_width, self.window_height));
+        egui::vec2(width as f32, height as f32)
+    }
+
+    // The app is drawn by a paint callback covering the central panel
+    fn show_app(&self, ctx: &egui::Context) {
+        let clear_color = self.clear_color.get();
+        // Around a fixed resolution frame the panel shows as the bars
+        let fill = match (self.settings.fixed_resolution, self.settings.transparent) {
+            (Some(_), false) => egui::Color32::BLACK,
+            (Some(_), true) => egui::Color32::TRANSPARENT,
+            // Already premultiplied, it comes from a Color32
+            (None, _) => egui::Rgba::from_rgba_premultiplied(
+                clear_color.r as f32,
+                clear_color.g as f32,
+                clear_color.b as f32,
+                clear_color.a as f32,
+            )
+            .into(),
+        };
+        let container = egui::containers::Frame::default().fill(fill);
         egui::CentralPanel::default()
             .frame(container)
             .show(ctx, |ui| {
@@ -209,27 +1502,90 @@ impl eframe::App for EframeApp {
                     ctx.screen_rect().height(),
                 ));
 
+                let rect = match self.settings.fixed_resolution {
+                    Some(resolution) => letterbox(response.1, resolution),
+                    None => response.1,
+                };
                 ui.painter().add(egui_wgpu::Callback::new_paint_callback(
-                    response.1,
+                    rect,
                     WgpuCallback {
                         app: self.app.clone(),
+                        frame_commands: self.frame_commands.clone(),
+                        offscreen: self
+                            .offscreen
+                            .as_ref()
+                            .map(|offscreen| offscreen.output.clone()),
                     },
                 ));
                 // });
             });
-        ctx.request_repaint();
     }
 
-    fn clear_color(&self, _visuals: &Visuals) -> [f32; 4] {
-        return [0.9, 0.4, 0.4, 1.0];
+    // egui answers ViewportCommand::Screenshot with an event in a later frame
+    fn save_screenshot(&self) {
+        for event in &self.input.state().events {
+            if let egui::Event::Screenshot { image, .. } = event {
+                if let Some(path) = self.capture.take_pending() {
+                    let rgba: Vec<u8> = image.pixels.iter().flat_map(|c| c.to_array()).collect();
+                    let [width, height] = image.size;
+                    let result = save_png(&path, width as u32, height as u32, &rgba);
+                    self.capture.finish(path, result);
+                }
+            }
+        }
+    }
+}
+
+fn handle_event(context: &Context, event: &egui::Event) {
+    if let egui::Event::Key {
+        key,
+        pressed: true,
+        repeat: false,
+        modifiers,
+        ..
+    } = event
+    {
+        match key {
+            egui::Key::Escape => context.request_exit(),
+            egui::Key::Enter if modifiers.alt => context.set_fullscreen(!context.is_fullscreen()),
+            egui::Key::F12 => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                if let Err(err) = context.capture_frame(format!("screenshot-{timestamp}.png")) {
+                    log::error!("{err}");
+                }
+            }
+            _ => {}
+        }
     }
 }
 
 struct WgpuCallback {
     app: Arc<dyn App + Send + Sync>,
+    frame_commands: Arc<Mutex<Option<wgpu::CommandBuffer>>>,
+    offscreen: Option<Arc<OffscreenOutput>>,
 }
 
 impl CallbackTrait for WgpuCallback {
+    // egui submits the returned buffers first and its own encoder after them, in one submit
+    fn prepare(
+        &self,
+        _device: &wgpu::Device,
+        _queue: &wgpu::Queue,
+        _screen_descriptor: &egui_wgpu::ScreenDescriptor,
+        _egui_encoder: &mut wgpu::CommandEncoder,
+        _resources: &mut CallbackResources,
+    ) -> Vec<wgpu::CommandBuffer> {
+        self.frame_commands
+            .lock()
+            .unwrap()
+            .take()
+            .into_iter()
+            .collect()
+    }
+
     fn paint(
         &self,
         _info: egui::PaintCallbackInfo,
@@ -237,6 +1593,161 @@ impl CallbackTrait for WgpuCallback {
         _resources: &CallbackResources,
     ) {
         // let app: &Box<dyn App> = resources.get().unwrap();
-        self.app.render(render_pass);
+        match &self.offscreen {
+            Some(output) => output.draw(render_pass),
+            None => self.app.render(render_pass),
+        }
+    }
+}
+
+// With Runner::set_hdr or Runner::set_fixed_resolution the app is rendered into target during
+// the frame update, the paint callback then only draws the target, tonemapped with HDR
+struct Offscreen {
+    target: RenderTarget,
+    output: Arc<OffscreenOutput>,
+}
+
+// What the paint callback draws, the callback holds it until the frame is painted
+struct OffscreenOutput {
+    resolve: Resolve,
+    bind_group: wgpu::BindGroup,
+}
+
+enum Resolve {
+    Tonemap(Tonemap),
+    Copy(PostProcess),
+}
+
+// Plain copy of the fixed resolution target into the surface
+const COPY_SHADER: &str = "@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(input_texture, input_sampler, in.tex_coords);
+}";
+
+impl Offscreen {
+    // Context with the surface format and the window size. The target is sized like the window,
+    // or fixed_resolution, and its depth texture matches the depth format and the sample count.
+    fn new(
+        context: &Context,
+        hdr: bool,
+        fixed_resolution: Option<(u32, u32)>,
+        filter: wgpu::FilterMode,
+    ) -> Result<Self, ContextError> {
+        let (width, height) =
+            fixed_resolution.unwrap_or((context.size().x as u32, context.size().y as u32));
+        let format = if hdr { HDR_FORMAT } else { context.format() };
+        let mut target = RenderTarget::new(
+            context,
+            width,
+            height,
+            format,
+            context.depth_stencil_format(),
+        );
+        target.set_sample_count(context, context.sample_count());
+        let sampler = SamplerBuilder::linear_clamp()
+            .mag_filter(filter)
+            .min_filter(filter)
+            .label("Offscreen Sampler")
+            .build(context)?;
+        let resolve = if hdr {
+            let mut tonemap = Tonemap::new(context)?;
+            tonemap.set_sampler(sampler);
+            Resolve::Tonemap(tonemap)
+        } else {
+            let mut copy = PostProcess::new(context, COPY_SHADER)?;
+            copy.set_sampler(sampler);
+            Resolve::Copy(copy)
+        };
+        let bind_group = resolve.bind_group(context, target.texture_view());
+        Ok(Self {
+            target,
+            output: Arc::new(OffscreenOutput {
+                resolve,
+                bind_group,
+            }),
+        })
+    }
+
+    // Like the app, the output is only shared with the previous frame's callback, dropped by now
+    fn set_exposure(&mut self, queue: &wgpu::Queue, exposure: f32) {
+        let Resolve::Tonemap(tonemap) = &self.output.resolve else {
+            return;
+        };
+        if tonemap.exposure() != exposure {
+            let output = Arc::get_mut(&mut self.output).unwrap();
+            if let Resolve::Tonemap(tonemap) = &mut output.resolve {
+                tonemap.set_exposure(queue, exposure);
+            }
+        }
+    }
+
+    fn resize(&mut self, context: &Context, width: u32, height: u32) {
+        self.target.resize(context, width, height);
+        let output = Arc::get_mut(&mut self.output).unwrap();
+        output.bind_group = output
+            .resolve
+            .bind_group(context, self.target.texture_view());
+    }
+}
+
+impl Resolve {
+    fn bind_group(&self, context: &Context, input: &wgpu::TextureView) -> wgpu::BindGroup {
+        match self {
+            Resolve::Tonemap(tonemap) => tonemap.bind_group(context, input),
+            Resolve::Copy(copy) => copy.bind_group(context, input),
+        }
+    }
+}
+
+impl OffscreenOutput {
+    fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        match &self.resolve {
+            Resolve::Tonemap(tonemap) => tonemap.draw(render_pass, &self.bind_group),
+            Resolve::Copy(copy) => copy.draw(render_pass, &self.bind_group),
+        }
+    }
+}
+
+// Window area in points the fixed resolution frame is scaled into, centered and as large as
+// the aspect ratio allows
+fn letterbox(screen: egui::Rect, (width, height): (u32, u32)) -> egui::Rect {
+    let scale = (screen.width() / width as f32).min(screen.height() / height as f32);
+    egui::Rect::from_center_size(
+        screen.center(),
+        egui::vec2(width as f32 * scale, height as f32 * scale),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct EmptyApp;
+
+    impl App for EmptyApp {}
+
+    fn runner() -> Runner {
+        Runner::new(
+            "Test",
+            100,
+            100,
+            egui::Color32::BLACK,
+            24,
+            0,
+            Box::new(|_| Arc::new(EmptyApp)),
+        )
+    }
+
+    #[test]
+    fn fixed_timestep_must_be_positive() {
+        for fixed_delta_time in [0.0, -0.01, f32::NAN, f32::INFINITY] {
+            let result = runner().set_fixed_timestep(fixed_delta_time).run_config();
+            assert!(
+                matches!(result, Err(ContextError::InvalidFixedTimestep(_))),
+                "{fixed_delta_time} was accepted"
+            );
+        }
+        assert!(runner().set_fixed_timestep(1.0 / 60.0).run_config().is_ok());
+        assert!(runner().run_config().is_ok());
     }
 }
@@ -0,0 +1,262 @@
+use std::path::{Path, PathBuf};
+
+use eframe::wgpu;
+use image::RgbaImage;
+
+use crate::{
+    context::{Context, ContextError},
+    headless::HeadlessContext,
+    runner::App,
+};
+
+// Set to write the rendered images over the golden ones instead of comparing them, "0" leaves
+// it off like WGPU_VALIDATION
+const UPDATE_ENV: &str = "WGPU_BOOTSTRAP_UPDATE_GOLDEN";
+
+// Renders one frame of the app in a new HeadlessContext (Rgba8UnormSrgb, like a window) and
+// reads it back. The app is created with the headless context as it would be by Runner::new.
+pub fn render_to_image<A: App>(
+    create_app: impl FnOnce(&Context) -> A,
+    width: u32,
+    height: u32,
+) -> Result<RgbaImage, ContextError> {
+    let headless = HeadlessContext::new(width, height, wgpu::TextureFormat::Rgba8UnormSrgb)?;
+    let mut app = create_app(&headless.context());
+    headless.render(&mut app);
+    headless.read_frame()
+}
+
+// Panics unless every channel of every pixel is within tolerance of the golden PNG. On failure
+// the frame is saved next to it as <name>.actual.png, with <name>.diff.png showing the pixels
+// at fault in red over a dimmed copy of the frame.
+//
+// A missing golden image fails too, after saving <name>.actual.png to look at. Run with
+// WGPU_BOOTSTRAP_UPDATE_GOLDEN=1 to write the golden images instead.
+pub fn assert_image_matches(actual: &RgbaImage, golden_path: impl AsRef<Path>, tolerance: u8) {
+    let golden_path = golden_path.as_ref();
+    if update_requested(std::env::var_os(UPDATE_ENV).as_deref()) {
+        save(actual, golden_path);
+        return;
+    }
+
+    let golden = match image::open(golden_path) {
+        Ok(golden) => golden.into_rgba8(),
+        Err(err) => {
+            let actual_path = sibling(golden_path, "actual");
+            save(actual, &actual_path);
+            panic!(
+                "can't read golden image {} ({err}), frame saved to {}, run with {UPDATE_ENV}=1 \
+                 to make it the golden image",
+                golden_path.display(),
+                actual_path.display()
+            );
+        }
+    };
+    if golden.dimensions() != actual.dimensions() {
+        let actual_path = sibling(golden_path, "actual");
+        save(actual, &actual_path);
+        panic!(
+            "frame is {:?}, golden image {} is {:?}, frame saved to {}",
+            actual.dimensions(),
+            golden_path.display(),
+            golden.dimensions(),
+            actual_path.display()
+        );
+    }
+
+    let Comparison {
+        diff,
+        differing,
+        max_difference,
+    } = compare(actual, &golden, tolerance);
+    if differing > 0 {
+        let actual_path = sibling(golden_path, "actual");
+        let diff_path = sibling(golden_path, "diff");
+        save(actual, &actual_path);
+        save(&diff, &diff_path);
+        panic!(
+            "{differing} of {} pixels differ from {} by more than {tolerance} (up to \
+             {max_difference}), see {} and {}",
+            actual.width() * actual.height(),
+            golden_path.display(),
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
+
+struct Comparison {
+    diff: RgbaImage,
+    // Pixels with a channel further than the tolerance from the golden image
+    differing: u32,
+    max_difference: u8,
+}
+
+// Both images have the same size
+fn compare(actual: &RgbaImage, golden: &RgbaImage, tolerance: u8) -> Comparison {
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut differing = 0;
+    let mut max_difference = 0;
+    for ((actual_pixel, golden_pixel), diff_pixel) in
+        actual.pixels().zip(golden.pixels()).zip(diff.pixels_mut())
+    {
+        let difference = actual_pixel
+            .0
+            .iter()
+            .zip(golden_pixel.0)
+            .map(|(a, g)| a.abs_diff(g))
+            .max()
+            .unwrap_or(0);
+        max_difference = max_difference.max(difference);
+        *diff_pixel = if difference > tolerance {
+            differing += 1;
+            image::Rgba([255, 0, 0, 255])
+        } else {
+            let [r, g, b, _] = actual_pixel.0;
+            let luma = (r as u32 * 3 + g as u32 * 6 + b as u32) / 10;
+            let dimmed = (luma / 3) as u8;
+            image::Rgba([dimmed, dimmed, dimmed, 255])
+        };
+    }
+    Comparison {
+        diff,
+        differing,
+        max_difference,
+    }
+}
+
+// The value of UPDATE_ENV
+fn update_requested(value: Option<&std::ffi::OsStr>) -> bool {
+    value.is_some_and(|value| value != "0")
+}
+
+// golden.png -> golden.<suffix>.png, in the same directory
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!("{stem}.{suffix}.png"))
+}
+
+fn save(image: &RgbaImage, path: &Path) {
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Err(err) = image.save_with_format(path, image::ImageFormat::Png) {
+        panic!("can't save {}: {err}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filled(width: u32, height: u32, value: u8) -> RgbaImage {
+        RgbaImage::from_pixel(width, height, image::Rgba([value, value, value, 255]))
+    }
+
+    // A directory of its own per test, the tests run in parallel
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("wgpu-bootstrap-{name}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    // The assertions below only hold when the golden images aren't being updated
+    fn updating() -> bool {
+        update_requested(std::env::var_os(UPDATE_ENV).as_deref())
+    }
+
+    #[test]
+    fn sibling_names() {
+        assert_eq!(
+            sibling(Path::new("tests/golden/triangle.png"), "diff"),
+            Path::new("tests/golden/triangle.diff.png")
+        );
+        assert_eq!(
+            sibling(Path::new("triangle"), "actual"),
+            Path::new("triangle.actual.png")
+        );
+    }
+
+    #[test]
+    fn update_only_unless_zero() {
+        assert!(!update_requested(None));
+        assert!(!update_requested(Some("0".as_ref())));
+        assert!(update_requested(Some("1".as_ref())));
+        assert!(update_requested(Some("true".as_ref())));
+    }
+
+    #[test]
+    fn within_tolerance() {
+        let comparison = compare(&filled(4, 4, 100), &filled(4, 4, 102), 2);
+        assert_eq!(comparison.differing, 0);
+        assert_eq!(comparison.max_difference, 2);
+    }
+
+    #[test]
+    fn diff_marks_differing_pixels() {
+        let golden = filled(4, 4, 90);
+        let mut actual = golden.clone();
+        actual.put_pixel(1, 2, image::Rgba([90, 100, 90, 255]));
+        let comparison = compare(&actual, &golden, 9);
+        assert_eq!(comparison.differing, 1);
+        assert_eq!(comparison.max_difference, 10);
+        assert_eq!(comparison.diff.get_pixel(1, 2).0, [255, 0, 0, 255]);
+        // The rest is the frame dimmed to a third of its luma
+        assert_eq!(comparison.diff.get_pixel(0, 0).0, [30, 30, 30, 255]);
+    }
+
+    #[test]
+    fn mismatch_saves_actual_and_diff() {
+        if updating() {
+            return;
+        }
+        let dir = scratch_dir("mismatch");
+        let golden_path = dir.join("golden.png");
+        filled(4, 4, 0).save(&golden_path).unwrap();
+        let result =
+            std::panic::catch_unwind(|| assert_image_matches(&filled(4, 4, 255), &golden_path, 2));
+        assert!(result.is_err());
+        assert_eq!(
+            image::open(dir.join("golden.actual.png"))
+                .unwrap()
+                .into_rgba8(),
+            filled(4, 4, 255)
+        );
+        let diff = image::open(dir.join("golden.diff.png"))
+            .unwrap()
+            .into_rgba8();
+        assert!(diff.pixels().all(|pixel| pixel.0 == [255, 0, 0, 255]));
+        // Passes once within tolerance
+        assert_image_matches(&filled(4, 4, 1), &golden_path, 2);
+    }
+
+    #[test]
+    fn size_mismatch_fails() {
+        if updating() {
+            return;
+        }
+        let dir = scratch_dir("size-mismatch");
+        let golden_path = dir.join("golden.png");
+        filled(4, 4, 0).save(&golden_path).unwrap();
+        let result =
+            std::panic::catch_unwind(|| assert_image_matches(&filled(4, 2, 0), &golden_path, 255));
+        assert!(result.is_err());
+        assert!(dir.join("golden.actual.png").exists());
+        assert!(!dir.join("golden.diff.png").exists());
+    }
+
+    #[test]
+    fn missing_golden_fails() {
+        if updating() {
+            return;
+        }
+        let dir = scratch_dir("missing-golden");
+        let golden_path = dir.join("golden.png");
+        let result =
+            std::panic::catch_unwind(|| assert_image_matches(&filled(2, 2, 0), &golden_path, 0));
+        assert!(result.is_err());
+        assert!(!golden_path.exists());
+        assert!(dir.join("golden.actual.png").exists());
+    }
+}
@@ -0,0 +1,39 @@
+// Deltas above this are considered stalls (window dragged, debugger break...)
+const MAX_DELTA_SECONDS: f32 = 0.1;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Time {
+    delta_seconds: f32,
+    elapsed_seconds: f32,
+    frame_count: u64,
+    alpha: f32,
+}
+
+impl Time {
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    pub fn elapsed_seconds(&self) -> f32 {
+        self.elapsed_seconds
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    // How far between the last two fixed updates this frame is, for interpolation
+    pub fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    pub(crate) fn set_alpha(&mut self, alpha: f32) {
+        self.alpha = alpha;
+    }
+
+    pub(crate) fn tick(&mut self, delta_seconds: f32) {
+        self.delta_seconds = delta_seconds.min(MAX_DELTA_SECONDS);
+        self.elapsed_seconds += self.delta_seconds;
+        self.frame_count += 1;
+    }
+}
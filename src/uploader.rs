@@ -0,0 +1,59 @@
+use eframe::wgpu::{self, util::StagingBelt};
+use std::cell::RefCell;
+
+// Staging buffers are allocated in chunks of this size, bigger writes get their own chunk
+const DEFAULT_CHUNK_SIZE: wgpu::BufferAddress = 1 << 20;
+
+// Buffer uploads recorded as copies on an encoder, through staging buffers reused from frame to
+// frame instead of a queue.write_buffer allocation each time.
+//
+// finish must be called before the encoder is submitted and recall once it has been. The runner
+// does both around the frame encoder (and Context::flush calls finish), so apps only call
+// write_buffer (or Context::write_buffer) on the encoders they are given.
+pub struct Uploader {
+    belt: RefCell<StagingBelt>,
+}
+
+impl Uploader {
+    pub fn new(chunk_size: wgpu::BufferAddress) -> Self {
+        Self {
+            belt: RefCell::new(StagingBelt::new(chunk_size)),
+        }
+    }
+
+    // The offset and the data size must be multiples of 4 (wgpu::COPY_BUFFER_ALIGNMENT), the
+    // target needs COPY_DST usage
+    pub fn write_buffer<T: bytemuck::Pod>(
+        &self,
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::Buffer,
+        offset: wgpu::BufferAddress,
+        data: &[T],
+    ) {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        let Some(size) = wgpu::BufferSize::new(bytes.len() as wgpu::BufferAddress) else {
+            return;
+        };
+        self.belt
+            .borrow_mut()
+            .write_buffer(encoder, target, offset, size, device)
+            .copy_from_slice(bytes);
+    }
+
+    // Closes the staging buffers written this frame, before submitting the encoder
+    pub fn finish(&self) {
+        self.belt.borrow_mut().finish();
+    }
+
+    // Makes the staging buffers reusable once the GPU is done with them, after submit
+    pub fn recall(&self) {
+        self.belt.borrow_mut().recall();
+    }
+}
+
+impl Default for Uploader {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHUNK_SIZE)
+    }
+}
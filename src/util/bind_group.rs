@@ -0,0 +1,215 @@
+use eframe::wgpu;
+
+use crate::{context::Context, util::pass::CheckedBindGroup};
+
+// Layout and group are built from the same entries so their bindings can't drift apart
+pub struct BindGroupBuilder<'a> {
+    label: Option<&'a str>,
+    layout_entries: Vec<wgpu::BindGroupLayoutEntry>,
+    resources: Vec<wgpu::BindingResource<'a>>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    pub fn new() -> Self {
+        Self {
+            label: None,
+            layout_entries: Vec::new(),
+            resources: Vec::new(),
+        }
+    }
+
+    pub fn label(&mut self, label: &'a str) -> &mut Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn uniform(
+        &mut self,
+        binding: u32,
+        buffer: &'a wgpu::Buffer,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            buffer.as_entire_binding(),
+        )
+    }
+
+    pub fn storage(
+        &mut self,
+        binding: u32,
+        buffer: &'a wgpu::Buffer,
+        read_only: bool,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            buffer.as_entire_binding(),
+        )
+    }
+
+    // A filterable 2D float texture, the kind util::texture::Texture creates
+    pub fn texture(
+        &mut self,
+        binding: u32,
+        view: &'a wgpu::TextureView,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            wgpu::BindingResource::TextureView(view),
+        )
+    }
+
+    // A filterable 2D array texture, from Texture::from_layers
+    pub fn texture_array(
+        &mut self,
+        binding: u32,
+        view: &'a wgpu::TextureView,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::D2Array,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            wgpu::BindingResource::TextureView(view),
+        )
+    }
+
+    // A filterable cube texture, from Texture::cubemap_from_paths
+    pub fn texture_cube(
+        &mut self,
+        binding: u32,
+        view: &'a wgpu::TextureView,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension: wgpu::TextureViewDimension::Cube,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            wgpu::BindingResource::TextureView(view),
+        )
+    }
+
+    // A 2D storage texture of the format, declared as texture_storage_2d<format, access> in
+    // WGSL. Reading it needs StorageTexture::check_access to pass.
+    pub fn storage_texture(
+        &mut self,
+        binding: u32,
+        view: &'a wgpu::TextureView,
+        format: wgpu::TextureFormat,
+        access: wgpu::StorageTextureAccess,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::StorageTexture {
+                access,
+                format,
+                view_dimension: wgpu::TextureViewDimension::D2,
+            },
+            wgpu::BindingResource::TextureView(view),
+        )
+    }
+
+    pub fn sampler(
+        &mut self,
+        binding: u32,
+        sampler: &'a wgpu::Sampler,
+        visibility: wgpu::ShaderStages,
+    ) -> &mut Self {
+        self.entry(
+            binding,
+            visibility,
+            wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            wgpu::BindingResource::Sampler(sampler),
+        )
+    }
+
+    // For binding types the shortcuts above don't cover
+    pub fn entry(
+        &mut self,
+        binding: u32,
+        visibility: wgpu::ShaderStages,
+        ty: wgpu::BindingType,
+        resource: wgpu::BindingResource<'a>,
+    ) -> &mut Self {
+        self.layout_entries.push(wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility,
+            ty,
+            count: None,
+        });
+        self.resources.push(resource);
+        self
+    }
+
+    pub fn build(&self, context: &Context) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
+        let layout = context
+            .device()
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                entries: &self.layout_entries,
+                label: self.label,
+            });
+        let entries: Vec<wgpu::BindGroupEntry> = self
+            .layout_entries
+            .iter()
+            .zip(&self.resources)
+            .map(|(layout_entry, resource)| wgpu::BindGroupEntry {
+                binding: layout_entry.binding,
+                resource: resource.clone(),
+            })
+            .collect();
+        let group = context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &layout,
+                entries: &entries,
+                label: self.label,
+            });
+        (layout, group)
+    }
+
+    // Same as build, and the group remembers its entries for util::pass::Pass
+    pub fn build_checked(&self, context: &Context) -> (wgpu::BindGroupLayout, CheckedBindGroup) {
+        let (layout, group) = self.build(context);
+        (layout, CheckedBindGroup::new(group, &self.layout_entries))
+    }
+
+    pub fn entries(&self) -> &[wgpu::BindGroupLayoutEntry] {
+        &self.layout_entries
+    }
+}
+
+impl Default for BindGroupBuilder<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
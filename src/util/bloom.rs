@@ -0,0 +1,276 @@
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{
+        pipeline::{RenderPipelineBuilder, ADDITIVE_BLENDING},
+        render_target::RenderTarget,
+        sampler::SamplerBuilder,
+        tonemap::HDR_FORMAT,
+        uniform_buffer::UniformBuffer,
+    },
+};
+
+// Levels of the chain, fewer when the target is too small for all of them
+const MAX_LEVELS: usize = 6;
+
+// Matches Params in bloom.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    _padding: f32,
+}
+
+// Glow around what is brighter than the threshold. The bright part of the target is
+// downsampled through a chain of HDR_FORMAT textures, from half the target size down,
+// blurred back up level by level and added to the target.
+//
+// Meant for an HDR target, such as Context::hdr_target from App::post_process. The target
+// given to new (or resize) is the one apply reads and draws into.
+pub struct Bloom {
+    params: BloomParams,
+    params_buffer: UniformBuffer<BloomParams>,
+    sampler: wgpu::Sampler,
+    layout: wgpu::BindGroupLayout,
+    levels: Vec<RenderTarget>,
+    // Reads the target
+    prefilter_bind_group: wgpu::BindGroup,
+    // level_bind_groups[i] reads levels[i]
+    level_bind_groups: Vec<wgpu::BindGroup>,
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+}
+
+impl Bloom {
+    // A threshold of 1 and an intensity of 0.5. The composite pipeline takes the target's
+    // format, depth format and sample count.
+    pub fn new(context: &Context, target: &RenderTarget) -> Result<Self, ContextError> {
+        let params = BloomParams {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.5,
+            _padding: 0.0,
+        };
+        let params_buffer = UniformBuffer::new(context, params)?;
+        let sampler = SamplerBuilder::linear_clamp()
+            .label("Bloom Sampler")
+            .build(context)?;
+
+        let layout = context
+            .device()
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Bloom Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            multisampled: false,
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Bloom Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("bloom.wgsl").into()),
+            });
+        let level_pipeline = |label, entry_point, blend| {
+            RenderPipelineBuilder::new(&shader)
+                .label(label)
+                .entry_points("vs_main", entry_point)
+                .bind_group_layout(&layout)
+                .format(HDR_FORMAT)
+                .blend(blend)
+                .cull_mode(None)
+                .depth_format(None)
+                .sample_count(1)
+                .build(context)
+        };
+        let prefilter_pipeline = level_pipeline(
+            "Bloom Prefilter Pipeline",
+            "fs_prefilter",
+            Some(wgpu::BlendState::REPLACE),
+        );
+        let downsample_pipeline = level_pipeline(
+            "Bloom Downsample Pipeline",
+            "fs_downsample",
+            Some(wgpu::BlendState::REPLACE),
+        );
+        let upsample_pipeline = level_pipeline(
+            "Bloom Upsample Pipeline",
+            "fs_upsample",
+            Some(ADDITIVE_BLENDING),
+        );
+        let composite_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Bloom Composite Pipeline")
+            .entry_points("vs_main", "fs_composite")
+            .bind_group_layout(&layout)
+            .render_target(target)
+            .blend_additive()
+            .cull_mode(None)
+            .depth_write(false)
+            .depth_compare(wgpu::CompareFunction::Always)
+            .build(context);
+
+        let (levels, prefilter_bind_group, level_bind_groups) =
+            create_levels(context, target, &layout, &sampler, &params_buffer);
+
+        Ok(Self {
+            params,
+            params_buffer,
+            sampler,
+            layout,
+            levels,
+            prefilter_bind_group,
+            level_bind_groups,
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            composite_pipeline,
+        })
+    }
+
+    pub fn threshold(&self) -> f32 {
+        self.params.threshold
+    }
+
+    // Brightness (the largest of the linear RGB channels) where the glow starts, eased in over
+    // half the threshold below it
+    pub fn set_threshold(&mut self, queue: &wgpu::Queue, threshold: f32) -> &mut Self {
+        self.params.threshold = threshold.max(0.0);
+        self.params.knee = self.params.threshold * 0.5;
+        self.params_buffer.update(queue, self.params);
+        self
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.params.intensity
+    }
+
+    // Scales the glow added to the target
+    pub fn set_intensity(&mut self, queue: &wgpu::Queue, intensity: f32) -> &mut Self {
+        self.params.intensity = intensity;
+        self.params_buffer.update(queue, self.params);
+        self
+    }
+
+    // Number of textures in the chain
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    // Recreates the chain for the target's new size, call it after resizing the target
+    pub fn resize(&mut self, context: &Context, target: &RenderTarget) {
+        let (levels, prefilter_bind_group, level_bind_groups) = create_levels(
+            context,
+            target,
+            &self.layout,
+            &self.sampler,
+            &self.params_buffer,
+        );
+        self.levels = levels;
+        self.prefilter_bind_group = prefilter_bind_group;
+        self.level_bind_groups = level_bind_groups;
+    }
+
+    // Records every pass, the last one draws into target (the one given to new or resize). The
+    // target's depth is cleared, so apply it once the scene is drawn.
+    pub fn apply(&self, encoder: &mut wgpu::CommandEncoder, target: &RenderTarget) {
+        {
+            let mut render_pass = self.levels[0].begin_pass(encoder, Some(wgpu::Color::BLACK));
+            render_pass.set_pipeline(&self.prefilter_pipeline);
+            render_pass.set_bind_group(0, &self.prefilter_bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        for i in 1..self.levels.len() {
+            let mut render_pass = self.levels[i].begin_pass(encoder, Some(wgpu::Color::BLACK));
+            render_pass.set_pipeline(&self.downsample_pipeline);
+            render_pass.set_bind_group(0, &self.level_bind_groups[i - 1], &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        // Each level adds the blurred smaller one, so levels[0] ends up with all of them
+        for i in (1..self.levels.len()).rev() {
+            let mut render_pass = self.levels[i - 1].begin_pass(encoder, None);
+            render_pass.set_pipeline(&self.upsample_pipeline);
+            render_pass.set_bind_group(0, &self.level_bind_groups[i], &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+        let mut render_pass = target.begin_pass(encoder, None);
+        render_pass.set_pipeline(&self.composite_pipeline);
+        render_pass.set_bind_group(0, &self.level_bind_groups[0], &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_levels(
+    context: &Context,
+    target: &RenderTarget,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    params_buffer: &UniformBuffer<BloomParams>,
+) -> (Vec<RenderTarget>, wgpu::BindGroup, Vec<wgpu::BindGroup>) {
+    let mut levels = Vec::new();
+    let mut width = target.width() / 2;
+    let mut height = target.height() / 2;
+    while levels.len() < MAX_LEVELS && (levels.is_empty() || (width > 1 && height > 1)) {
+        levels.push(RenderTarget::new(context, width, height, HDR_FORMAT, None));
+        width /= 2;
+        height /= 2;
+    }
+
+    let bind_group = |view: &wgpu::TextureView| {
+        context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Bloom Bind Group"),
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: params_buffer.binding_resource(),
+                    },
+                ],
+            })
+    };
+    let prefilter_bind_group = bind_group(target.texture_view());
+    let level_bind_groups = levels
+        .iter()
+        .map(|level| bind_group(level.texture_view()))
+        .collect();
+    (levels, prefilter_bind_group, level_bind_groups)
+}
@@ -0,0 +1,78 @@
+struct Params {
+    threshold: f32,
+    knee: f32,
+    intensity: f32,
+    _padding: f32,
+};
+@group(0) @binding(0) var input_texture: texture_2d<f32>;
+@group(0) @binding(1) var input_sampler: sampler;
+@group(0) @binding(2) var<uniform> params: Params;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+// One triangle covering the whole target
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.tex_coords = uv;
+    return out;
+}
+
+fn input_texel() -> vec2<f32> {
+    return 1.0 / vec2<f32>(textureDimensions(input_texture));
+}
+
+// Four bilinear taps averaging a 4x4 block of the input, which is twice the output size
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+    let texel = input_texel();
+    var color = textureSample(input_texture, input_sampler, uv + texel * vec2<f32>(-1.0, -1.0)).rgb;
+    color += textureSample(input_texture, input_sampler, uv + texel * vec2<f32>(1.0, -1.0)).rgb;
+    color += textureSample(input_texture, input_sampler, uv + texel * vec2<f32>(-1.0, 1.0)).rgb;
+    color += textureSample(input_texture, input_sampler, uv + texel * vec2<f32>(1.0, 1.0)).rgb;
+    return color * 0.25;
+}
+
+// Keeps what is above the threshold, with a quadratic knee instead of a hard cut
+fn threshold(color: vec3<f32>) -> vec3<f32> {
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - params.threshold + params.knee, 0.0, 2.0 * params.knee);
+    soft = soft * soft / (4.0 * params.knee + 0.0001);
+    let contribution = max(soft, brightness - params.threshold) / max(brightness, 0.0001);
+    return color * contribution;
+}
+
+@fragment
+fn fs_prefilter(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(threshold(downsample(in.tex_coords)), 1.0);
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample(in.tex_coords), 1.0);
+}
+
+// 3x3 tent filter over the smaller level, added to the larger one by the blend state
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    let texel = input_texel();
+    var color = vec3<f32>(0.0);
+    for (var y = -1; y <= 1; y += 1) {
+        for (var x = -1; x <= 1; x += 1) {
+            let weight = f32((2 - abs(x)) * (2 - abs(y)));
+            let offset = vec2<f32>(f32(x), f32(y)) * texel;
+            color += textureSample(input_texture, input_sampler, in.tex_coords + offset).rgb * weight;
+        }
+    }
+    return vec4<f32>(color / 16.0, 1.0);
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    let bloom = textureSample(input_texture, input_sampler, in.tex_coords).rgb;
+    return vec4<f32>(bloom * params.intensity, 0.0);
+}
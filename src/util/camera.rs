@@ -0,0 +1,128 @@
+use eframe::wgpu;
+
+use crate::{
+    context::Context,
+    util::{
+        orbit_camera::{CameraUniform, OPENGL_TO_WGPU_MATRIX},
+        uniform_buffer::UniformBuffer,
+    },
+};
+
+// Same uniform layout as OrbitCamera, so shaders written for one work with the other
+pub struct Camera {
+    eye: cgmath::Point3<f32>,
+    target: cgmath::Point3<f32>,
+    up: cgmath::Vector3<f32>,
+    fovy: f32,
+    aspect: f32,
+    znear: f32,
+    zfar: f32,
+    uniform: CameraUniform,
+    buffer: UniformBuffer<CameraUniform>,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Camera {
+    pub fn new(context: &Context, fovy: f32, znear: f32, zfar: f32) -> Self {
+        let uniform = CameraUniform::new();
+        // Two 4x4 matrices, always a valid uniform size
+        let buffer = UniformBuffer::new(context, uniform).unwrap();
+        let bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let bind_group = context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.binding_resource(),
+                }],
+                label: Some("Camera Bind Group"),
+            });
+
+        // Same guard as set_aspect, for a minimized window
+        let size = context.size();
+        let aspect = if size.y > 0.0 { size.x / size.y } else { 1.0 };
+
+        let mut res = Self {
+            eye: cgmath::point3(0.0, 0.0, 1.0),
+            target: cgmath::point3(0.0, 0.0, 0.0),
+            up: cgmath::Vector3::unit_y(),
+            fovy,
+            aspect,
+            znear,
+            zfar,
+            uniform,
+            buffer,
+            bind_group,
+        };
+        res.update(context);
+        res
+    }
+
+    pub fn view_matrix(&self) -> cgmath::Matrix4<f32> {
+        cgmath::Matrix4::look_at_rh(self.eye, self.target, self.up)
+    }
+
+    pub fn proj_matrix(&self) -> cgmath::Matrix4<f32> {
+        OPENGL_TO_WGPU_MATRIX
+            * cgmath::perspective(cgmath::Deg(self.fovy), self.aspect, self.znear, self.zfar)
+    }
+
+    pub fn view_proj_matrix(&self) -> [[f32; 4]; 4] {
+        (self.proj_matrix() * self.view_matrix()).into()
+    }
+
+    pub fn update(&mut self, context: &Context) {
+        self.uniform.update_proj(self.proj_matrix());
+        self.uniform.update_view(self.view_matrix());
+        self.buffer.update(context.queue(), self.uniform);
+    }
+
+    pub fn set_eye(&mut self, eye: cgmath::Point3<f32>) -> &mut Self {
+        self.eye = eye;
+        self
+    }
+
+    pub fn eye(&self) -> cgmath::Point3<f32> {
+        self.eye
+    }
+
+    pub fn set_target(&mut self, target: cgmath::Point3<f32>) -> &mut Self {
+        self.target = target;
+        self
+    }
+
+    pub fn target(&self) -> cgmath::Point3<f32> {
+        self.target
+    }
+
+    pub fn set_up(&mut self, up: cgmath::Vector3<f32>) -> &mut Self {
+        self.up = up;
+        self
+    }
+
+    pub fn set_fovy(&mut self, fovy: f32) -> &mut Self {
+        self.fovy = fovy;
+        self
+    }
+
+    pub fn set_planes(&mut self, znear: f32, zfar: f32) -> &mut Self {
+        self.znear = znear;
+        self.zfar = zfar;
+        self
+    }
+
+    // Takes the App::resize arguments directly
+    pub fn set_aspect(&mut self, width: u32, height: u32) -> &mut Self {
+        if height > 0 {
+            self.aspect = width as f32 / height as f32;
+        }
+        self
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
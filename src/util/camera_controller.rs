@@ -0,0 +1,212 @@
+use std::f32::consts::PI;
+
+use cgmath::prelude::*;
+use eframe::egui::{Key, PointerButton};
+
+use crate::{
+    context::Context,
+    util::{camera::Camera, orbit_camera::OrbitCamera},
+};
+
+// Keeps the elevation (or pitch) off the poles, where look_at flips around the up vector
+const MAX_ELEVATION: f32 = PI / 2.0 - 0.01;
+
+// Drives an OrbitCamera from the frame's Input, with adjustable speeds, a minimum distance and
+// an inverted vertical drag. Azimuth, elevation and distance are the camera's longitude, latitude
+// and radius.
+pub struct OrbitCameraController {
+    camera: OrbitCamera,
+    min_distance: f32,
+    rotate_speed: f32,
+    zoom_speed: f32,
+    invert_y: bool,
+}
+
+impl OrbitCameraController {
+    pub fn new(mut camera: OrbitCamera, distance: f32) -> Self {
+        let min_distance = 0.1;
+        camera.set_radius(distance.max(min_distance));
+        Self {
+            camera,
+            min_distance,
+            rotate_speed: 0.01,
+            zoom_speed: 0.002,
+            invert_y: false,
+        }
+    }
+
+    // Left drag orbits around the camera target, the wheel and pinches zoom
+    pub fn update(&mut self, context: &Context) {
+        let input = context.input();
+        if input.is_mouse_button_down(PointerButton::Primary) {
+            let (dx, dy) = input.mouse_delta();
+            let dy = if self.invert_y { -dy } else { dy };
+            let azimuth = self.camera.longitude() + self.rotate_speed * dx;
+            let elevation = self.camera.latitude() + self.rotate_speed * dy;
+            self.set_angles(azimuth, elevation);
+        }
+        let (_, scroll) = input.scroll_delta();
+        let distance = self.distance() * (1.0 - self.zoom_speed * scroll) / input.pinch_delta();
+        self.set_distance(distance);
+        self.camera.update(context);
+    }
+
+    pub fn camera(&self) -> &OrbitCamera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut OrbitCamera {
+        &mut self.camera
+    }
+
+    pub fn set_angles(&mut self, azimuth: f32, elevation: f32) -> &mut Self {
+        self.camera
+            .set_longitude(azimuth)
+            .set_latitude(elevation.clamp(-MAX_ELEVATION, MAX_ELEVATION));
+        self
+    }
+
+    pub fn set_distance(&mut self, distance: f32) -> &mut Self {
+        self.camera.set_radius(distance.max(self.min_distance));
+        self
+    }
+
+    pub fn distance(&self) -> f32 {
+        self.camera.radius()
+    }
+
+    pub fn set_min_distance(&mut self, min_distance: f32) -> &mut Self {
+        self.min_distance = min_distance;
+        self
+    }
+
+    pub fn set_rotate_speed(&mut self, rotate_speed: f32) -> &mut Self {
+        self.rotate_speed = rotate_speed;
+        self
+    }
+
+    pub fn set_zoom_speed(&mut self, zoom_speed: f32) -> &mut Self {
+        self.zoom_speed = zoom_speed;
+        self
+    }
+
+    pub fn set_invert_y(&mut self, invert_y: bool) -> &mut Self {
+        self.invert_y = invert_y;
+        self
+    }
+}
+
+pub struct FpsCameraController {
+    camera: Camera,
+    yaw: f32,
+    pitch: f32,
+    move_speed: f32,
+    sprint_multiplier: f32,
+    sensitivity: f32,
+    captured: bool,
+}
+
+impl FpsCameraController {
+    // Starts looking from the camera eye toward its target
+    pub fn new(camera: Camera) -> Self {
+        let direction = camera.target() - camera.eye();
+        let yaw = direction.z.atan2(direction.x);
+        let pitch = if direction.magnitude() > 0.0 {
+            (direction.y / direction.magnitude()).asin()
+        } else {
+            0.0
+        };
+        Self {
+            camera,
+            yaw,
+            pitch: pitch.clamp(-MAX_ELEVATION, MAX_ELEVATION),
+            move_speed: 2.0,
+            sprint_multiplier: 3.0,
+            sensitivity: 0.002,
+            captured: false,
+        }
+    }
+
+    // Grabs and hides the cursor so mouse look keeps going past the window border
+    pub fn set_captured(&mut self, context: &Context, captured: bool) -> &mut Self {
+        self.captured = captured;
+        context.set_cursor_grab(captured);
+        context.set_cursor_visible(!captured);
+        self
+    }
+
+    pub fn captured(&self) -> bool {
+        self.captured
+    }
+
+    // WASD moves along the view direction, E/Q up and down, Shift sprints. The mouse looks
+    // around while the cursor is captured or the right button is held.
+    pub fn update(&mut self, context: &Context) -> cgmath::Matrix4<f32> {
+        let input = context.input();
+        if self.captured || input.is_mouse_button_down(PointerButton::Secondary) {
+            let (dx, dy) = input.mouse_delta();
+            self.yaw += self.sensitivity * dx;
+            self.pitch = (self.pitch - self.sensitivity * dy).clamp(-MAX_ELEVATION, MAX_ELEVATION);
+        }
+
+        let forward = cgmath::vec3(
+            self.pitch.cos() * self.yaw.cos(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.sin(),
+        );
+        let up = cgmath::Vector3::unit_y();
+        let right = forward.cross(up).normalize();
+
+        let mut movement = cgmath::Vector3::zero();
+        for (key, direction) in [
+            (Key::W, forward),
+            (Key::S, -forward),
+            (Key::D, right),
+            (Key::A, -right),
+            (Key::E, up),
+            (Key::Q, -up),
+        ] {
+            if input.is_key_down(key) {
+                movement += direction;
+            }
+        }
+        if movement.magnitude2() > 0.0 {
+            let mut speed = self.move_speed;
+            if input.state().modifiers.shift {
+                speed *= self.sprint_multiplier;
+            }
+            movement = movement.normalize() * speed * context.time().delta_seconds();
+        }
+
+        let eye = self.camera.eye() + movement;
+        self.camera
+            .set_eye(eye)
+            .set_target(eye + forward)
+            .set_up(up)
+            .update(context);
+        self.camera.view_matrix()
+    }
+
+    pub fn camera(&self) -> &Camera {
+        &self.camera
+    }
+
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
+    pub fn set_move_speed(&mut self, move_speed: f32) -> &mut Self {
+        self.move_speed = move_speed;
+        self
+    }
+
+    pub fn set_sprint_multiplier(&mut self, sprint_multiplier: f32) -> &mut Self {
+        self.sprint_multiplier = sprint_multiplier;
+        self
+    }
+
+    pub fn set_sensitivity(&mut self, sensitivity: f32) -> &mut Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+}
@@ -0,0 +1,162 @@
+use eframe::wgpu;
+
+use crate::{
+    context::Context,
+    util::{
+        dynamic_buffer::DynamicBuffer, orbit_camera::CameraUniform, pipeline::RenderPipelineBuilder,
+    },
+    Vertex,
+};
+
+// In vertices, two per line, before the buffer first grows
+const INITIAL_CAPACITY: usize = 2048;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct LineVertex {
+    position: [f32; 3],
+    color: [f32; 3],
+}
+
+// Lines queued with line, axes and wire_cube during the frame (update for instance), sent with
+// flush and drawn with draw in App::render. Uses the camera uniform layout at group 0, so the
+// bind group of OrbitCamera or Camera works.
+pub struct DebugDraw {
+    vertices: Vec<LineVertex>,
+    buffer: DynamicBuffer<LineVertex>,
+    depth_test: bool,
+    pipeline: wgpu::RenderPipeline,
+    overlay_pipeline: wgpu::RenderPipeline,
+}
+
+impl DebugDraw {
+    pub fn new(context: &Context) -> Self {
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Debug Draw Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("debug_draw.wgsl").into()),
+            });
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+
+        let pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Debug Draw Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(LineVertex::desc())
+            .primitive_topology(wgpu::PrimitiveTopology::LineList)
+            .cull_mode(None)
+            .build(context);
+        let overlay_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Debug Draw Overlay Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .vertex_layout(LineVertex::desc())
+            .primitive_topology(wgpu::PrimitiveTopology::LineList)
+            .cull_mode(None)
+            .depth_write(false)
+            .depth_compare(wgpu::CompareFunction::Always)
+            .build(context);
+
+        Self {
+            vertices: Vec::new(),
+            buffer: DynamicBuffer::new(
+                context,
+                "Debug Draw Vertex Buffer",
+                wgpu::BufferUsages::VERTEX,
+                INITIAL_CAPACITY,
+            ),
+            depth_test: true,
+            pipeline,
+            overlay_pipeline,
+        }
+    }
+
+    // Off draws the lines on top of everything drawn before them
+    pub fn set_depth_test(&mut self, depth_test: bool) -> &mut Self {
+        self.depth_test = depth_test;
+        self
+    }
+
+    pub fn line(
+        &mut self,
+        a: cgmath::Point3<f32>,
+        b: cgmath::Point3<f32>,
+        color: [f32; 3],
+    ) -> &mut Self {
+        self.vertices.push(LineVertex {
+            position: a.into(),
+            color,
+        });
+        self.vertices.push(LineVertex {
+            position: b.into(),
+            color,
+        });
+        self
+    }
+
+    // Unit X, Y and Z axes of the transform, in red, green and blue
+    pub fn axes(&mut self, transform: cgmath::Matrix4<f32>) -> &mut Self {
+        let origin = cgmath::Point3::from_homogeneous(transform * cgmath::vec4(0.0, 0.0, 0.0, 1.0));
+        let axes = [
+            (cgmath::vec4(1.0, 0.0, 0.0, 1.0), [1.0, 0.0, 0.0]),
+            (cgmath::vec4(0.0, 1.0, 0.0, 1.0), [0.0, 1.0, 0.0]),
+            (cgmath::vec4(0.0, 0.0, 1.0, 1.0), [0.0, 0.0, 1.0]),
+        ];
+        for (end, color) in axes {
+            self.line(
+                origin,
+                cgmath::Point3::from_homogeneous(transform * end),
+                color,
+            );
+        }
+        self
+    }
+
+    // Axis aligned, size is the length of an edge
+    pub fn wire_cube(
+        &mut self,
+        center: cgmath::Point3<f32>,
+        size: f32,
+        color: [f32; 3],
+    ) -> &mut Self {
+        let half = size / 2.0;
+        let corner = |i: usize| {
+            let sign = |bit: usize| if i & bit == 0 { -half } else { half };
+            center + cgmath::vec3(sign(1), sign(2), sign(4))
+        };
+        // Corners are numbered by their bits, edges join corners one bit apart
+        for i in 0..8 {
+            for bit in [1, 2, 4] {
+                if i & bit == 0 {
+                    self.line(corner(i), corner(i | bit), color);
+                }
+            }
+        }
+        self
+    }
+
+    // Sends the queued lines for the next draw and starts a new batch
+    pub fn flush(&mut self, context: &Context) {
+        self.buffer.write(context, &self.vertices);
+        self.vertices.clear();
+    }
+
+    pub fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(if self.depth_test {
+            &self.pipeline
+        } else {
+            &self.overlay_pipeline
+        });
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.buffer.buffer().slice(..));
+        render_pass.draw(0..self.buffer.len() as u32, 0..1);
+    }
+}
@@ -0,0 +1,207 @@
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    resources::TrackedResource,
+    util::sampler::SamplerBuilder,
+};
+
+// A depth texture rendered on its own (a shadow map from a light) and sampled by a later pass.
+// The pipelines drawing into it take its format with RenderPipelineBuilder::depth_target.
+pub struct DepthTarget {
+    label: String,
+    format: wgpu::TextureFormat,
+    width: u32,
+    height: u32,
+    texture: wgpu::Texture,
+    // Every aspect, for the pass
+    attachment_view: wgpu::TextureView,
+    view: wgpu::TextureView,
+    // Comparison sampler, see SamplerBuilder::shadow
+    sampler: wgpu::Sampler,
+    _tracked: TrackedResource,
+}
+
+impl DepthTarget {
+    pub fn new(
+        context: &Context,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, ContextError> {
+        if !format.has_depth_aspect() {
+            return Err(ContextError::UnsupportedDepthFormat(format));
+        }
+        let width = width.max(1);
+        let height = height.max(1);
+        let label = String::from("Depth Target");
+        let (texture, attachment_view, view, tracked) =
+            create_texture(context, &label, width, height, format);
+        let sampler = SamplerBuilder::shadow()
+            .label("Depth Target Sampler")
+            .build(context)?;
+        Ok(Self {
+            label,
+            format,
+            width,
+            height,
+            texture,
+            attachment_view,
+            view,
+            sampler,
+            _tracked: tracked,
+        })
+    }
+
+    // Recreates the texture, bind groups holding the previous view must be rebuilt
+    pub fn resize(&mut self, context: &Context, width: u32, height: u32) {
+        let width = width.max(1);
+        let height = height.max(1);
+        if width == self.width && height == self.height {
+            return;
+        }
+        (self.texture, self.attachment_view, self.view, self._tracked) =
+            create_texture(context, &self.label, width, height, self.format);
+        self.width = width;
+        self.height = height;
+    }
+
+    // Names the texture for graphics debuggers, recreating it like resize
+    pub fn set_label(&mut self, context: &Context, label: &str) -> &mut Self {
+        if label != self.label {
+            self.label = String::from(label);
+            (self.texture, self.attachment_view, self.view, self._tracked) =
+                create_texture(context, &self.label, self.width, self.height, self.format);
+        }
+        self
+    }
+
+    // Depth only, cleared to 1
+    pub fn begin_pass<'e>(&'e self, encoder: &'e mut wgpu::CommandEncoder) -> wgpu::RenderPass<'e> {
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Depth Target Pass"),
+            color_attachments: &[],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.attachment_view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Store,
+                }),
+                stencil_ops: None,
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    // Depth aspect only, so it binds as a texture_depth_2d
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // texture_depth_2d at binding 0 and its comparison sampler at binding 1
+    pub fn desc() -> wgpu::BindGroupLayoutDescriptor<'static> {
+        wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+            label: Some("Depth Target Bind Group Layout"),
+        }
+    }
+
+    // For a layout made from desc
+    pub fn bind_group(&self, context: &Context, layout: &wgpu::BindGroupLayout) -> wgpu::BindGroup {
+        context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Depth Target Bind Group"),
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&self.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                ],
+            })
+    }
+}
+
+fn create_texture(
+    context: &Context,
+    label: &str,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+) -> (
+    wgpu::Texture,
+    wgpu::TextureView,
+    wgpu::TextureView,
+    TrackedResource,
+) {
+    let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+        label: Some(&format!("{label} Texture")),
+        size: wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        view_formats: &[],
+    });
+    let attachment_view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some(&format!("{label} Attachment View")),
+        ..Default::default()
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor {
+        label: Some(&format!("{label} View")),
+        aspect: wgpu::TextureAspect::DepthOnly,
+        ..Default::default()
+    });
+    let tracked = context
+        .resources()
+        .track_texture(&texture, &format!("{label} Texture"));
+    (texture, attachment_view, view, tracked)
+}
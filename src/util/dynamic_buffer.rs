@@ -0,0 +1,99 @@
+use std::marker::PhantomData;
+
+use eframe::wgpu;
+
+use crate::{context::Context, resources::TrackedResource};
+
+// Elements of T rewritten from the start by each write. When the data doesn't fit, the buffer is
+// replaced by one of twice the capacity (or more) and generation goes up: bind groups built on
+// the old buffer must be rebuilt then.
+pub struct DynamicBuffer<T: bytemuck::Pod> {
+    buffer: wgpu::Buffer,
+    label: String,
+    usage: wgpu::BufferUsages,
+    capacity: usize,
+    len: usize,
+    generation: u64,
+    phantom: PhantomData<T>,
+    _tracked: TrackedResource,
+}
+
+impl<T: bytemuck::Pod> DynamicBuffer<T> {
+    // usage is VERTEX, STORAGE or UNIFORM (or several of them), COPY_DST is always added
+    pub fn new(context: &Context, label: &str, usage: wgpu::BufferUsages, capacity: usize) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        let capacity = capacity.max(1);
+        let (buffer, tracked) = create_buffer::<T>(context, label, usage, capacity);
+        Self {
+            buffer,
+            label: String::from(label),
+            usage,
+            capacity,
+            len: 0,
+            generation: 0,
+            phantom: PhantomData,
+            _tracked: tracked,
+        }
+    }
+
+    // Returns true when the buffer had to be reallocated
+    pub fn write(&mut self, context: &Context, data: &[T]) -> bool {
+        let grown = data.len() > self.capacity;
+        if grown {
+            while self.capacity < data.len() {
+                self.capacity *= 2;
+            }
+            (self.buffer, self._tracked) =
+                create_buffer::<T>(context, &self.label, self.usage, self.capacity);
+            self.generation += 1;
+        }
+        context
+            .queue()
+            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(data));
+        self.len = data.len();
+        grown
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    // Covers the whole capacity, a shader's arrayLength sees that and not len
+    pub fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+
+    // Number of elements written by the last write
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // Bumped on every reallocation
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+}
+
+fn create_buffer<T>(
+    context: &Context,
+    label: &str,
+    usage: wgpu::BufferUsages,
+    capacity: usize,
+) -> (wgpu::Buffer, TrackedResource) {
+    let buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: (capacity * std::mem::size_of::<T>()) as wgpu::BufferAddress,
+        usage,
+        mapped_at_creation: false,
+    });
+    let tracked = context.resources().track_buffer(&buffer, label);
+    (buffer, tracked)
+}
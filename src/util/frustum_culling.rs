@@ -0,0 +1,243 @@
+use cgmath::{EuclideanSpace, InnerSpace};
+use eframe::wgpu::{self, util::DrawIndexedIndirectArgs};
+
+use crate::{
+    context::{Context, ContextError},
+    resources::TrackedResource,
+    util::{
+        bind_group::BindGroupBuilder, indirect_buffer::IndirectBuffer, mesh::GpuMesh,
+        pipeline::ComputePipelineBuilder, uniform_buffer::UniformBuffer,
+    },
+};
+
+// Matches @workgroup_size in frustum_culling.wgsl
+const WORKGROUP_SIZE: u32 = 64;
+
+// Model matrices, the layout of instance_buffer::TRANSFORM_ATTRIBUTES
+type Transform = [[f32; 4]; 4];
+
+// Matches Params in frustum_culling.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct CullParams {
+    planes: [[f32; 4]; 6],
+    center: [f32; 3],
+    radius: f32,
+    count: u32,
+    _padding: [u32; 3],
+}
+
+// Left, right, bottom, top, near and far planes of the volume a view-projection matrix (such as
+// Camera::view_proj_matrix) keeps, for wgpu's 0 to 1 depth. Each plane is (normal, distance)
+// with the normal pointing inside and unit length, dot(normal, point) + distance is how far the
+// point is into that side.
+pub fn frustum_planes(view_proj: impl Into<cgmath::Matrix4<f32>>) -> [cgmath::Vector4<f32>; 6] {
+    let m = view_proj.into();
+    let row = |i: usize| cgmath::vec4(m.x[i], m.y[i], m.z[i], m.w[i]);
+    let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+    [w + x, w - x, w + y, w - y, z, w - z].map(|plane| plane / plane.truncate().magnitude())
+}
+
+// The CPU version of the culling test
+pub fn sphere_in_frustum(
+    planes: &[cgmath::Vector4<f32>; 6],
+    center: cgmath::Point3<f32>,
+    radius: f32,
+) -> bool {
+    planes
+        .iter()
+        .all(|plane| plane.truncate().dot(center.to_vec()) + plane.w >= -radius)
+}
+
+// Culls instances of a mesh on the GPU: cull tests the bounding sphere of each transform against
+// the camera's frustum and packs the visible ones into visible_buffer(), in the layout of
+// TRANSFORM_ATTRIBUTES so the pipeline for an InstanceBuffer of transforms draws it unchanged.
+// It also writes their indices in the instances given, and their count into the indirect draw
+// that draw uses.
+pub struct FrustumCuller {
+    instances: wgpu::Buffer,
+    visible: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    indirect: IndirectBuffer,
+    count: u32,
+    max_count: u32,
+    params: CullParams,
+    params_buffer: UniformBuffer<CullParams>,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::ComputePipeline,
+    _tracked: [TrackedResource; 3],
+}
+
+impl FrustumCuller {
+    // Room for max_count instances of the mesh, whose bounding sphere is centered on its origin
+    // with a radius of 1 until set_bounding_sphere
+    pub fn new(context: &Context, mesh: &GpuMesh, max_count: u32) -> Result<Self, ContextError> {
+        if !context
+            .downlevel_flags()
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return Err(ContextError::DownlevelNotSupported(
+                wgpu::DownlevelFlags::COMPUTE_SHADERS,
+            ));
+        }
+        let max_count = max_count.max(1);
+        let indirect = IndirectBuffer::with_label(context, 1, "Culled Indirect Buffer")?;
+        indirect.write(
+            context.queue(),
+            &[DrawIndexedIndirectArgs {
+                index_count: mesh.index_count(),
+                instance_count: 0,
+                first_index: 0,
+                base_vertex: 0,
+                first_instance: 0,
+            }],
+        );
+        let params = CullParams {
+            planes: [[0.0; 4]; 6],
+            center: [0.0; 3],
+            radius: 1.0,
+            count: 0,
+            _padding: [0; 3],
+        };
+        let params_buffer = UniformBuffer::with_label(context, params, "Cull Params Buffer")?;
+
+        let transform_size = std::mem::size_of::<Transform>() as wgpu::BufferAddress;
+        let create_buffer = |label, size, usage| {
+            let buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+                label: Some(label),
+                size: max_count as wgpu::BufferAddress * size,
+                usage,
+                mapped_at_creation: false,
+            });
+            let tracked = context.resources().track_buffer(&buffer, label);
+            (buffer, tracked)
+        };
+        let (instances, instances_tracked) = create_buffer(
+            "Cull Instance Buffer",
+            transform_size,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        );
+        let (visible, visible_tracked) = create_buffer(
+            "Culled Instance Buffer",
+            transform_size,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
+        );
+        let (indices, indices_tracked) = create_buffer(
+            "Culled Index Buffer",
+            std::mem::size_of::<u32>() as wgpu::BufferAddress,
+            wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_SRC,
+        );
+
+        let (layout, bind_group) = BindGroupBuilder::new()
+            .label("Frustum Culling Bind Group")
+            .uniform(0, params_buffer.buffer(), wgpu::ShaderStages::COMPUTE)
+            .storage(1, &instances, true, wgpu::ShaderStages::COMPUTE)
+            .storage(2, &visible, false, wgpu::ShaderStages::COMPUTE)
+            .storage(3, &indices, false, wgpu::ShaderStages::COMPUTE)
+            .storage(4, indirect.buffer(), false, wgpu::ShaderStages::COMPUTE)
+            .build(context);
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Frustum Culling Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("frustum_culling.wgsl").into()),
+            });
+        let pipeline = ComputePipelineBuilder::new(&shader)
+            .label("Frustum Culling Pipeline")
+            .bind_group_layout(&layout)
+            .build(context);
+
+        Ok(Self {
+            instances,
+            visible,
+            indices,
+            indirect,
+            count: 0,
+            max_count,
+            params,
+            params_buffer,
+            bind_group,
+            pipeline,
+            _tracked: [instances_tracked, visible_tracked, indices_tracked],
+        })
+    }
+
+    // In the mesh's own space, scaled along with each transform
+    pub fn set_bounding_sphere(&mut self, center: cgmath::Point3<f32>, radius: f32) -> &mut Self {
+        self.params.center = center.into();
+        self.params.radius = radius;
+        self
+    }
+
+    // Replaces the instances culled from then on, those past max_count are dropped
+    pub fn write_instances(&mut self, queue: &wgpu::Queue, transforms: &[Transform]) {
+        let transforms = if transforms.len() > self.max_count as usize {
+            log::warn!(
+                "{} instances for a culler of {}, the rest are dropped",
+                transforms.len(),
+                self.max_count
+            );
+            &transforms[..self.max_count as usize]
+        } else {
+            transforms
+        };
+        queue.write_buffer(&self.instances, 0, bytemuck::cast_slice(transforms));
+        self.count = transforms.len() as u32;
+    }
+
+    // Records the culling pass, from App::prepare. The results are read by the passes recorded
+    // after it on the encoder, or in the frame's render pass.
+    pub fn cull(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        context: &Context,
+        view_proj: impl Into<cgmath::Matrix4<f32>>,
+    ) {
+        self.params.planes = frustum_planes(view_proj).map(Into::into);
+        self.params.count = self.count;
+        self.params_buffer.update(context.queue(), self.params);
+
+        // Only instance_count, at offset 4 of DrawIndexedIndirectArgs
+        encoder.clear_buffer(self.indirect.buffer(), 4, Some(4));
+        if self.count == 0 {
+            return;
+        }
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Frustum Culling Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.pipeline);
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.dispatch_workgroups(self.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    // Binds the visible transforms at vertex slot 1, where the instance pipelines expect them,
+    // and draws them with the count cull wrote
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, mesh: &GpuMesh) {
+        render_pass.set_vertex_buffer(1, self.visible.slice(..));
+        mesh.draw_indirect(render_pass, &self.indirect, 0);
+    }
+
+    // The visible transforms, packed from the start, Context::read_buffer can read it back
+    pub fn visible_buffer(&self) -> &wgpu::Buffer {
+        &self.visible
+    }
+
+    // One u32 per visible transform, its index in write_instances
+    pub fn indices_buffer(&self) -> &wgpu::Buffer {
+        &self.indices
+    }
+
+    // A single draw, its instance_count is the number of visible instances
+    pub fn indirect_buffer(&self) -> &IndirectBuffer {
+        &self.indirect
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn max_count(&self) -> u32 {
+        self.max_count
+    }
+}
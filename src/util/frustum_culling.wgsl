@@ -0,0 +1,49 @@
+// Matches CullParams in frustum_culling.rs
+struct Params {
+    planes: array<vec4<f32>, 6>,
+    center: vec3<f32>,
+    radius: f32,
+    count: u32,
+};
+
+// Laid out as DrawIndexedIndirectArgs, only the instance count is written
+struct DrawArgs {
+    index_count: u32,
+    instance_count: atomic<u32>,
+    first_index: u32,
+    base_vertex: i32,
+    first_instance: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> instances: array<mat4x4<f32>>;
+@group(0) @binding(2)
+var<storage, read_write> visible: array<mat4x4<f32>>;
+@group(0) @binding(3)
+var<storage, read_write> indices: array<u32>;
+@group(0) @binding(4)
+var<storage, read_write> draw: DrawArgs;
+
+// The bounding sphere follows the model matrix, scaled by its largest axis
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    let model = instances[index];
+    let center = (model * vec4<f32>(params.center, 1.0)).xyz;
+    let scale = max(length(model[0].xyz), max(length(model[1].xyz), length(model[2].xyz)));
+    let radius = params.radius * scale;
+    for (var i = 0u; i < 6u; i++) {
+        let plane = params.planes[i];
+        if dot(plane.xyz, center) + plane.w < -radius {
+            return;
+        }
+    }
+    let slot = atomicAdd(&draw.instance_count, 1u);
+    visible[slot] = model;
+    indices[slot] = index;
+}
@@ -0,0 +1,146 @@
+use eframe::wgpu;
+
+use crate::{
+    capture::map_read,
+    context::{Context, ContextError},
+};
+
+// Each scope writes a begin and an end timestamp
+const QUERIES_PER_SCOPE: u32 = 2;
+const TIMESTAMP_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as wgpu::BufferAddress;
+
+// Times the passes an app begins itself (in App::prepare or on its own encoders) through their
+// timestamp_writes. The render pass handed to App::render belongs to egui and can't be timed.
+//
+// Per frame: open scopes with render_scope/compute_scope, call resolve on the last encoder, then
+// read_results once it has been submitted (at the start of the next prepare for instance).
+pub struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readback_buffer: wgpu::Buffer,
+    max_scopes: u32,
+    labels: Vec<String>,
+    resolved: usize,
+    results: Vec<(String, f32)>,
+}
+
+impl GpuProfiler {
+    pub fn new(context: &Context, max_scopes: u32) -> Result<Self, ContextError> {
+        if !context
+            .device()
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+        {
+            return Err(ContextError::FeatureNotEnabled(
+                wgpu::Features::TIMESTAMP_QUERY,
+            ));
+        }
+        let query_count = max_scopes.max(1) * QUERIES_PER_SCOPE;
+        let query_set = context
+            .device()
+            .create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("GPU Profiler Query Set"),
+                ty: wgpu::QueryType::Timestamp,
+                count: query_count,
+            });
+        let size = query_count as wgpu::BufferAddress * TIMESTAMP_SIZE;
+        let resolve_buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Profiler Resolve Buffer"),
+            size,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback_buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("GPU Profiler Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Ok(Self {
+            query_set,
+            resolve_buffer,
+            readback_buffer,
+            max_scopes: max_scopes.max(1),
+            labels: Vec::new(),
+            resolved: 0,
+            results: Vec::new(),
+        })
+    }
+
+    // Returns the query indices of a new scope, None once max_scopes are in use this frame
+    fn open_scope(&mut self, label: &str) -> Option<u32> {
+        if self.labels.len() as u32 >= self.max_scopes {
+            log::warn!(
+                "GPU profiler scope '{label}' ignored, all {} are in use",
+                self.max_scopes
+            );
+            return None;
+        }
+        self.labels.push(label.to_string());
+        Some((self.labels.len() as u32 - 1) * QUERIES_PER_SCOPE)
+    }
+
+    // Pass the result as the timestamp_writes of the render pass to time
+    pub fn render_scope(&mut self, label: &str) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let index = self.open_scope(label)?;
+        Some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        })
+    }
+
+    pub fn compute_scope(&mut self, label: &str) -> Option<wgpu::ComputePassTimestampWrites<'_>> {
+        let index = self.open_scope(label)?;
+        Some(wgpu::ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(index),
+            end_of_pass_write_index: Some(index + 1),
+        })
+    }
+
+    // Record after the timed passes, in the same or a later encoder
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        if self.labels.is_empty() {
+            return;
+        }
+        let query_count = self.labels.len() as u32 * QUERIES_PER_SCOPE;
+        let size = query_count as wgpu::BufferAddress * TIMESTAMP_SIZE;
+        encoder.resolve_query_set(&self.query_set, 0..query_count, &self.resolve_buffer, 0);
+        encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &self.readback_buffer, 0, size);
+        self.resolved = self.labels.len();
+    }
+
+    // Blocks until the resolved frame is done on the GPU, then frees the scopes for the next one.
+    // The times are also published through Context::fps_counter.
+    pub fn read_results(&mut self, context: &Context) -> Result<&[(String, f32)], ContextError> {
+        let labels = std::mem::take(&mut self.labels);
+        if self.resolved == 0 {
+            return Ok(&self.results);
+        }
+        let size = self.resolved as wgpu::BufferAddress * QUERIES_PER_SCOPE as u64 * TIMESTAMP_SIZE;
+        self.resolved = 0;
+
+        let slice = self.readback_buffer.slice(..size);
+        map_read(context.device(), &slice)?;
+        let timestamps: Vec<u64> = bytemuck::pod_collect_to_vec(&slice.get_mapped_range());
+        self.readback_buffer.unmap();
+
+        // Nanoseconds per tick
+        let period = context.queue().get_timestamp_period();
+        self.results = labels
+            .into_iter()
+            .zip(timestamps.chunks_exact(QUERIES_PER_SCOPE as usize))
+            .map(|(label, pair)| {
+                let ticks = pair[1].saturating_sub(pair[0]);
+                (label, ticks as f32 * period / 1_000_000.0)
+            })
+            .collect();
+        context.fps_counter().set_gpu_times(self.results.clone());
+        Ok(&self.results)
+    }
+
+    pub fn results(&self) -> &[(String, f32)] {
+        &self.results
+    }
+}
@@ -0,0 +1,84 @@
+use eframe::wgpu::{self, util::DrawIndexedIndirectArgs};
+
+use crate::{
+    context::{Context, ContextError},
+    resources::TrackedResource,
+};
+
+// Bytes taken by one draw, the stride between consecutive draws in the buffer
+pub const DRAW_INDEXED_ARGS_SIZE: wgpu::BufferAddress =
+    std::mem::size_of::<DrawIndexedIndirectArgs>() as wgpu::BufferAddress;
+
+// Draw parameters for draw_indexed_indirect, laid out as DrawIndexedIndirectArgs. A compute pass
+// can fill it as a storage buffer: five u32 (base_vertex is an i32) per draw. first_instance has
+// to stay 0 unless the INDIRECT_FIRST_INSTANCE feature is enabled.
+pub struct IndirectBuffer {
+    buffer: wgpu::Buffer,
+    count: u32,
+    _tracked: TrackedResource,
+}
+
+impl IndirectBuffer {
+    // Room for count draws, zeroed so nothing is drawn until they're written
+    pub fn new(context: &Context, count: u32) -> Result<Self, ContextError> {
+        Self::with_label(context, count, "Indirect Buffer")
+    }
+
+    // label names the buffer for graphics debuggers
+    pub fn with_label(context: &Context, count: u32, label: &str) -> Result<Self, ContextError> {
+        if !context
+            .downlevel_flags()
+            .contains(wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+        {
+            return Err(ContextError::DownlevelNotSupported(
+                wgpu::DownlevelFlags::INDIRECT_EXECUTION,
+            ));
+        }
+        let buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size: count.max(1) as wgpu::BufferAddress * DRAW_INDEXED_ARGS_SIZE,
+            usage: wgpu::BufferUsages::INDIRECT
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+        Ok(Self {
+            _tracked: context.resources().track_buffer(&buffer, label),
+            buffer,
+            count,
+        })
+    }
+
+    // Writes draws from the first one, those past the count are dropped
+    pub fn write(&self, queue: &wgpu::Queue, draws: &[DrawIndexedIndirectArgs]) {
+        let draws = if draws.len() > self.count as usize {
+            log::warn!(
+                "{} draws for an indirect buffer of {}, the rest are dropped",
+                draws.len(),
+                self.count
+            );
+            &draws[..self.count as usize]
+        } else {
+            draws
+        };
+        let bytes: Vec<u8> = draws
+            .iter()
+            .flat_map(|draw| draw.as_bytes())
+            .copied()
+            .collect();
+        queue.write_buffer(&self.buffer, 0, &bytes);
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    // Byte offset of a draw, for draw_indirect
+    pub fn offset(&self, index: u32) -> wgpu::BufferAddress {
+        index as wgpu::BufferAddress * DRAW_INDEXED_ARGS_SIZE
+    }
+}
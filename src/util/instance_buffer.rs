@@ -0,0 +1,108 @@
+use std::marker::PhantomData;
+
+use eframe::wgpu;
+
+use crate::{context::Context, resources::TrackedResource};
+
+// A model matrix per instance, as four vec4 columns starting at shader location 5
+pub const TRANSFORM_ATTRIBUTES: [wgpu::VertexAttribute; 4] =
+    wgpu::vertex_attr_array![5 => Float32x4, 6 => Float32x4, 7 => Float32x4, 8 => Float32x4];
+
+pub struct InstanceBuffer<T: bytemuck::Pod> {
+    buffer: wgpu::Buffer,
+    capacity: usize,
+    count: u32,
+    phantom: PhantomData<T>,
+    _tracked: TrackedResource,
+}
+
+impl<T: bytemuck::Pod> InstanceBuffer<T> {
+    pub fn new(context: &Context, instances: &[T]) -> Self {
+        Self::with_label(context, instances, "Instance Buffer")
+    }
+
+    // label names the buffer for graphics debuggers
+    pub fn with_label(context: &Context, instances: &[T], label: &str) -> Self {
+        let buffer = context.create_vertex_buffer(instances, label);
+        Self {
+            _tracked: context.resources().track_buffer(&buffer, label),
+            buffer,
+            capacity: instances.len(),
+            count: instances.len() as u32,
+            phantom: PhantomData,
+        }
+    }
+
+    // Room for capacity instances, none drawn until update is called
+    pub fn with_capacity(context: &Context, capacity: usize) -> Self {
+        let buffer = context.device().create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Instance Buffer"),
+            size: (capacity * std::mem::size_of::<T>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        Self {
+            _tracked: context.resources().track_buffer(&buffer, "Instance Buffer"),
+            buffer,
+            capacity,
+            count: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    // The buffer doesn't grow, instances past the capacity are dropped
+    pub fn update(&mut self, queue: &wgpu::Queue, instances: &[T]) {
+        let instances = self.fit(instances);
+        queue.write_buffer(&self.buffer, 0, bytemuck::cast_slice(instances));
+        self.count = instances.len() as u32;
+    }
+
+    // Same as update through Context::write_buffer, for the encoder of App::prepare
+    pub fn upload(
+        &mut self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        instances: &[T],
+    ) {
+        let instances = self.fit(instances);
+        context.write_buffer(encoder, &self.buffer, 0, instances);
+        self.count = instances.len() as u32;
+    }
+
+    fn fit<'b>(&self, instances: &'b [T]) -> &'b [T] {
+        if instances.len() > self.capacity {
+            log::warn!(
+                "{} instances for a buffer of {}, the rest are dropped",
+                instances.len(),
+                self.capacity
+            );
+            &instances[..self.capacity]
+        } else {
+            instances
+        }
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    // Number of instances written by the last new or update, for draw_indexed's instance range
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn vertex_buffer_layout<'a>(
+        &self,
+        attributes: &'a [wgpu::VertexAttribute],
+    ) -> wgpu::VertexBufferLayout<'a> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<T>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes,
+        }
+    }
+}
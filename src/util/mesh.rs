@@ -0,0 +1,269 @@
+use std::{ops::Range, path::Path};
+
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    resources::TrackedResource,
+    util::{
+        geometry::compute_triangle_normal,
+        indirect_buffer::{IndirectBuffer, DRAW_INDEXED_ARGS_SIZE},
+    },
+    Vertex,
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+}
+
+// Vertex and index buffers with what draw_indexed needs to use them
+pub struct GpuMesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_count: u32,
+    index_format: wgpu::IndexFormat,
+    _tracked: [TrackedResource; 2],
+}
+
+impl GpuMesh {
+    // Indices are stored as u16 when every vertex fits in that range
+    pub fn new<V: bytemuck::Pod>(context: &Context, vertices: &[V], indices: &[u32]) -> Self {
+        Self::with_label(context, vertices, indices, "Mesh")
+    }
+
+    // label names the buffers for graphics debuggers, "{label} Vertex Buffer" and so on
+    pub fn with_label<V: bytemuck::Pod>(
+        context: &Context,
+        vertices: &[V],
+        indices: &[u32],
+        label: &str,
+    ) -> Self {
+        let (index_buffer, index_format) = if vertices.len() <= u16::MAX as usize + 1 {
+            let indices: Vec<u16> = indices.iter().map(|&index| index as u16).collect();
+            (
+                context.create_index_buffer(&indices, &format!("{label} Index Buffer")),
+                wgpu::IndexFormat::Uint16,
+            )
+        } else {
+            (
+                context.create_index_buffer(indices, &format!("{label} Index Buffer")),
+                wgpu::IndexFormat::Uint32,
+            )
+        };
+        let vertex_label = format!("{label} Vertex Buffer");
+        let vertex_buffer = context.create_vertex_buffer(vertices, &vertex_label);
+        let resources = context.resources();
+        Self {
+            _tracked: [
+                resources.track_buffer(&vertex_buffer, &vertex_label),
+                resources.track_buffer(&index_buffer, &format!("{label} Index Buffer")),
+            ],
+            vertex_buffer,
+            index_buffer,
+            index_count: indices.len() as u32,
+            index_format,
+        }
+    }
+
+    pub fn vertex_buffer(&self) -> &wgpu::Buffer {
+        &self.vertex_buffer
+    }
+
+    pub fn index_buffer(&self) -> &wgpu::Buffer {
+        &self.index_buffer
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+
+    pub fn index_format(&self) -> wgpu::IndexFormat {
+        self.index_format
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.draw_instanced(render_pass, 0..1);
+    }
+
+    // Binds the vertex buffer at slot 0, instance data can go in the following slots
+    pub fn draw_instanced(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        render_pass.draw_indexed(0..self.index_count, 0, instances);
+    }
+
+    // The index and instance counts come from the draw at offset bytes in the indirect buffer
+    pub fn draw_indirect(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        indirect_buffer: &IndirectBuffer,
+        offset: wgpu::BufferAddress,
+    ) {
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), self.index_format);
+        render_pass.draw_indexed_indirect(indirect_buffer.buffer(), offset);
+    }
+}
+
+pub struct SubMesh {
+    name: String,
+    mesh: GpuMesh,
+    material_id: Option<usize>,
+}
+
+impl SubMesh {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    // Index into the materials of the Mesh it belongs to
+    pub fn material_id(&self) -> Option<usize> {
+        self.material_id
+    }
+
+    pub fn mesh(&self) -> &GpuMesh {
+        &self.mesh
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.mesh.draw(render_pass);
+    }
+}
+
+pub struct Mesh {
+    submeshes: Vec<SubMesh>,
+    materials: Vec<tobj::Material>,
+}
+
+impl Mesh {
+    // tobj already splits the models on material changes, each one becomes a SubMesh
+    pub fn load_obj(context: &Context, path: impl AsRef<Path>) -> Result<Self, ContextError> {
+        let (models, materials) = tobj::load_obj(
+            path.as_ref(),
+            &tobj::LoadOptions {
+                single_index: true,
+                triangulate: true,
+                ..Default::default()
+            },
+        )
+        .map_err(ContextError::Obj)?;
+        // A missing .mtl shouldn't prevent drawing the geometry
+        let materials = materials.unwrap_or_else(|err| {
+            log::warn!(
+                "failed to load materials for {}: {err}",
+                path.as_ref().display()
+            );
+            Vec::new()
+        });
+
+        let submeshes = models
+            .into_iter()
+            .filter(|model| !model.mesh.indices.is_empty())
+            .map(|model| {
+                let (vertices, indices) = mesh_vertices(&model.mesh);
+                SubMesh {
+                    mesh: GpuMesh::with_label(context, &vertices, &indices, &model.name),
+                    material_id: model.mesh.material_id,
+                    name: model.name,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            submeshes,
+            materials,
+        })
+    }
+
+    pub fn submeshes(&self) -> &[SubMesh] {
+        &self.submeshes
+    }
+
+    pub fn materials(&self) -> &[tobj::Material] {
+        &self.materials
+    }
+
+    // Expects MeshVertex::desc() at vertex buffer slot 0
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        self.draw_instanced(render_pass, 0..1);
+    }
+
+    pub fn draw_instanced(&self, render_pass: &mut wgpu::RenderPass<'_>, instances: Range<u32>) {
+        for submesh in &self.submeshes {
+            submesh.mesh.draw_instanced(render_pass, instances.clone());
+        }
+    }
+
+    // One draw per submesh, in order, starting at offset bytes in the indirect buffer
+    pub fn draw_indirect(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        indirect_buffer: &IndirectBuffer,
+        offset: wgpu::BufferAddress,
+    ) {
+        for (index, submesh) in self.submeshes.iter().enumerate() {
+            let offset = offset + index as wgpu::BufferAddress * DRAW_INDEXED_ARGS_SIZE;
+            submesh
+                .mesh
+                .draw_indirect(render_pass, indirect_buffer, offset);
+        }
+    }
+}
+
+// Without normals in the file the triangles are unwelded so each one gets its flat face normal
+fn mesh_vertices(mesh: &tobj::Mesh) -> (Vec<MeshVertex>, Vec<u32>) {
+    let position = |i: usize| {
+        [
+            mesh.positions[3 * i],
+            mesh.positions[3 * i + 1],
+            mesh.positions[3 * i + 2],
+        ]
+    };
+    let uv = |i: usize| {
+        if mesh.texcoords.is_empty() {
+            [0.0, 0.0]
+        } else {
+            [mesh.texcoords[2 * i], mesh.texcoords[2 * i + 1]]
+        }
+    };
+
+    if !mesh.normals.is_empty() {
+        let vertices = (0..mesh.positions.len() / 3)
+            .map(|i| MeshVertex {
+                position: position(i),
+                normal: [
+                    mesh.normals[3 * i],
+                    mesh.normals[3 * i + 1],
+                    mesh.normals[3 * i + 2],
+                ],
+                uv: uv(i),
+            })
+            .collect();
+        return (vertices, mesh.indices.clone());
+    }
+
+    let vertices: Vec<MeshVertex> = mesh
+        .indices
+        .chunks_exact(3)
+        .flat_map(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|corner| triangle[corner] as usize);
+            let normal: [f32; 3] = compute_triangle_normal(
+                &position(a).into(),
+                &position(b).into(),
+                &position(c).into(),
+            )
+            .into();
+            [a, b, c].map(|i| MeshVertex {
+                position: position(i),
+                normal,
+                uv: uv(i),
+            })
+        })
+        .collect();
+    let indices = (0..vertices.len() as u32).collect();
+    (vertices, indices)
+}
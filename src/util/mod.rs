@@ -1,2 +1,35 @@
+pub mod bind_group;
+pub mod bloom;
+pub mod camera;
+pub mod camera_controller;
+pub mod debug_draw;
+pub mod depth_target;
+pub mod dynamic_buffer;
+pub mod frustum_culling;
 pub mod geometry;
+pub mod gpu_profiler;
+pub mod indirect_buffer;
+pub mod instance_buffer;
+pub mod mesh;
+pub mod model;
 pub mod orbit_camera;
+pub mod ortho_camera;
+pub mod particles;
+pub mod pass;
+pub mod pipeline;
+pub mod pipeline_cache;
+pub mod post_process;
+pub mod reflect;
+pub mod render_target;
+pub mod sampler;
+pub mod scene;
+#[cfg(feature = "hot-reload")]
+pub mod shader_watcher;
+pub mod skybox;
+pub mod sprite_batch;
+pub mod storage_texture;
+pub mod text;
+pub mod texture;
+pub mod texture_ops;
+pub mod tonemap;
+pub mod uniform_buffer;
@@ -0,0 +1,295 @@
+use std::path::Path;
+
+use cgmath::prelude::*;
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{
+        geometry::compute_normal_vectors,
+        mesh::{GpuMesh, MeshVertex},
+        texture::Texture,
+        uniform_buffer::UniformBuffer,
+    },
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct MaterialUniform {
+    base_color_factor: [f32; 4],
+}
+
+pub struct Material {
+    base_color_factor: [f32; 4],
+    bind_group: wgpu::BindGroup,
+}
+
+impl Material {
+    pub fn base_color_factor(&self) -> [f32; 4] {
+        self.base_color_factor
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    // Base color texture at binding 0, its sampler at 1 and the base color factor at 2
+    pub fn desc() -> wgpu::BindGroupLayoutDescriptor<'static> {
+        wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("Material Bind Group Layout"),
+        }
+    }
+}
+
+pub struct Primitive {
+    mesh: GpuMesh,
+    transform: cgmath::Matrix4<f32>,
+    material: usize,
+}
+
+impl Primitive {
+    pub fn mesh(&self) -> &GpuMesh {
+        &self.mesh
+    }
+
+    // Already baked into the vertices, kept for picking or bounds
+    pub fn transform(&self) -> cgmath::Matrix4<f32> {
+        self.transform
+    }
+
+    pub fn material(&self) -> usize {
+        self.material
+    }
+}
+
+pub struct Model {
+    primitives: Vec<Primitive>,
+    materials: Vec<Material>,
+    textures: Vec<Texture>,
+    material_layout: wgpu::BindGroupLayout,
+}
+
+impl Model {
+    // gltf::import takes .gltf (with external or embedded buffers and images) and .glb alike
+    pub fn load_gltf(context: &Context, path: impl AsRef<Path>) -> Result<Self, ContextError> {
+        let (document, buffers, images) = gltf::import(path).map_err(ContextError::Gltf)?;
+
+        let textures = images
+            .iter()
+            .enumerate()
+            .map(|(index, data)| {
+                let label = format!("glTF Image {index}");
+                match image_from_gltf(data) {
+                    Some(image) => Texture::from_image(context, &image, &label, true).map(Some),
+                    None => {
+                        log::warn!("{label} has an unsupported format {:?}", data.format);
+                        Ok(None)
+                    }
+                }
+            })
+            .collect::<Result<Vec<Option<Texture>>, ContextError>>()?;
+        // Stands in for missing or unsupported base color textures
+        let white = Texture::from_image(
+            context,
+            &image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(
+                1,
+                1,
+                image::Rgba([255, 255, 255, 255]),
+            )),
+            "glTF White Texture",
+            false,
+        )?;
+
+        let material_layout = context.device().create_bind_group_layout(&Material::desc());
+        let material = |base_color_factor: [f32; 4], texture: &Texture| {
+            // A vec4 is always a valid uniform size
+            let buffer =
+                UniformBuffer::new(context, MaterialUniform { base_color_factor }).unwrap();
+            let bind_group = context
+                .device()
+                .create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &material_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: wgpu::BindingResource::TextureView(texture.view()),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::Sampler(texture.sampler()),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: buffer.binding_resource(),
+                        },
+                    ],
+                    label: Some("Material Bind Group"),
+                });
+            Material {
+                base_color_factor,
+                bind_group,
+            }
+        };
+
+        let mut materials: Vec<Material> = document
+            .materials()
+            .map(|gltf_material| {
+                let pbr = gltf_material.pbr_metallic_roughness();
+                let texture = pbr
+                    .base_color_texture()
+                    .and_then(|info| textures[info.texture().source().index()].as_ref())
+                    .unwrap_or(&white);
+                material(pbr.base_color_factor(), texture)
+            })
+            .collect();
+        // Primitives without a material use the glTF default, plain white
+        let default_material = materials.len();
+        materials.push(material([1.0, 1.0, 1.0, 1.0], &white));
+
+        let mut primitives = Vec::new();
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next());
+        let mut nodes: Vec<(gltf::Node, cgmath::Matrix4<f32>)> = scene
+            .into_iter()
+            .flat_map(|scene| scene.nodes())
+            .map(|node| (node, cgmath::Matrix4::identity()))
+            .collect();
+        while let Some((node, parent_transform)) = nodes.pop() {
+            let transform = parent_transform * cgmath::Matrix4::from(node.transform().matrix());
+            nodes.extend(node.children().map(|child| (child, transform)));
+
+            let Some(mesh) = node.mesh() else {
+                continue;
+            };
+            for primitive in mesh.primitives() {
+                if primitive.mode() != gltf::mesh::Mode::Triangles {
+                    log::warn!("skipping a {:?} primitive", primitive.mode());
+                    continue;
+                }
+                let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+                let Some(positions) = reader.read_positions() else {
+                    continue;
+                };
+                let mut positions: Vec<cgmath::Vector3<f32>> =
+                    positions.map(cgmath::Vector3::from).collect();
+                let indices: Vec<u32> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..positions.len() as u32).collect(),
+                };
+                let normals: Vec<cgmath::Vector3<f32>> = match reader.read_normals() {
+                    Some(normals) => normals.map(cgmath::Vector3::from).collect(),
+                    None => compute_normal_vectors(&mut positions, &indices),
+                };
+                let uvs: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+                    Some(uvs) => uvs.into_f32().collect(),
+                    None => vec![[0.0, 0.0]; positions.len()],
+                };
+
+                let normal_matrix = transform
+                    .invert()
+                    .unwrap_or(cgmath::Matrix4::identity())
+                    .transpose();
+                let vertices: Vec<MeshVertex> = positions
+                    .iter()
+                    .zip(&normals)
+                    .zip(&uvs)
+                    .map(|((position, normal), uv)| MeshVertex {
+                        position: transform
+                            .transform_point(cgmath::Point3::from_vec(*position))
+                            .into(),
+                        normal: normal_matrix.transform_vector(*normal).normalize().into(),
+                        uv: *uv,
+                    })
+                    .collect();
+
+                let label = mesh.name().unwrap_or("glTF Mesh");
+                primitives.push(Primitive {
+                    mesh: GpuMesh::with_label(context, &vertices, &indices, label),
+                    transform,
+                    material: primitive.material().index().unwrap_or(default_material),
+                });
+            }
+        }
+
+        Ok(Self {
+            primitives,
+            materials,
+            textures: textures.into_iter().flatten().chain([white]).collect(),
+            material_layout,
+        })
+    }
+
+    pub fn primitives(&self) -> &[Primitive] {
+        &self.primitives
+    }
+
+    pub fn materials(&self) -> &[Material] {
+        &self.materials
+    }
+
+    pub fn textures(&self) -> &[Texture] {
+        &self.textures
+    }
+
+    pub fn material_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.material_layout
+    }
+
+    // Expects MeshVertex::desc() at vertex buffer slot 0 and binds each primitive's material at
+    // material_group
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, material_group: u32) {
+        for primitive in &self.primitives {
+            let material = &self.materials[primitive.material];
+            render_pass.set_bind_group(material_group, material.bind_group(), &[]);
+            primitive.mesh.draw(render_pass);
+        }
+    }
+}
+
+// 16 bit and float images aren't base color data in practice, those aren't converted
+fn image_from_gltf(data: &gltf::image::Data) -> Option<image::DynamicImage> {
+    let (width, height, pixels) = (data.width, data.height, data.pixels.clone());
+    match data.format {
+        gltf::image::Format::R8 => {
+            image::GrayImage::from_raw(width, height, pixels).map(image::DynamicImage::ImageLuma8)
+        }
+        gltf::image::Format::R8G8 => image::GrayAlphaImage::from_raw(width, height, pixels)
+            .map(image::DynamicImage::ImageLumaA8),
+        gltf::image::Format::R8G8B8 => {
+            image::RgbImage::from_raw(width, height, pixels).map(image::DynamicImage::ImageRgb8)
+        }
+        gltf::image::Format::R8G8B8A8 => {
+            image::RgbaImage::from_raw(width, height, pixels).map(image::DynamicImage::ImageRgba8)
+        }
+        _ => None,
+    }
+}
@@ -3,10 +3,13 @@ use std::f32::consts::PI;
 use cgmath::prelude::*;
 use eframe::{
     egui::{self, PointerButton},
-    wgpu::{self, util::DeviceExt},
+    wgpu,
 };
 
-use crate::runner::Context;
+use crate::{context::Context, input::wheel_scroll, util::uniform_buffer::UniformBuffer};
+
+// Radius change per point of wheel scroll, a 40 points wheel notch is about 8%
+const ZOOM_SPEED: f32 = 0.002;
 
 #[rustfmt::skip]
 pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = cgmath::Matrix4::new(
@@ -73,7 +76,7 @@ pub struct OrbitCamera {
     target: cgmath::Point3<f32>,
     up: cgmath::Vector3<f32>,
     uniform: CameraUniform,
-    buffer: wgpu::Buffer,
+    buffer: UniformBuffer<CameraUniform>,
     bind_group: wgpu::BindGroup,
     orbiting: bool,
 }
@@ -81,13 +84,8 @@ pub struct OrbitCamera {
 impl OrbitCamera {
     pub fn new(context: &Context, fovy: f32, aspect: f32, near: f32, far: f32) -> Self {
         let uniform = CameraUniform::new();
-        let buffer = context
-            .device()
-            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Camera Buffer"),
-                contents: bytemuck::cast_slice(&[uniform]),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
+        // Two 4x4 matrices, always a valid uniform size
+        let buffer = UniformBuffer::new(context, uniform).unwrap();
         let bind_group_layout = context
             .device()
             .create_bind_group_layout(&CameraUniform::desc());
@@ -97,7 +95,7 @@ impl OrbitCamera {
                 layout: &bind_group_layout,
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: buffer.as_entire_binding(),
+                    resource: buffer.binding_resource(),
                 }],
                 label: Some("Camera Bind Group"),
             });
@@ -134,9 +132,7 @@ impl OrbitCamera {
         let projection_matrix = OPENGL_TO_WGPU_MATRIX * proj;
         self.uniform.update_proj(projection_matrix);
         self.uniform.update_view(view);
-        context
-            .queue()
-            .write_buffer(&self.buffer, 0, bytemuck::cast_slice(&[self.uniform]));
+        self.buffer.update(context.queue(), self.uniform);
     }
 
     pub fn set_target(&mut self, target: cgmath::Point3<f32>) -> &mut Self {
@@ -212,6 +208,11 @@ impl OrbitCamera {
         }
     }
 
+    // The CameraUniform buffer, to bind it in other pipelines (compute culling for instance)
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        self.buffer.buffer()
+    }
+
     pub fn bind_group(&self) -> &wgpu::BindGroup {
         &self.bind_group
     }
@@ -227,5 +228,13 @@ impl OrbitCamera {
         if let Some(delta) = delta {
             self.delta_angles(context, (delta.x, delta.y));
         }
+        // Same as Input::scroll_delta and Input::pinch_delta
+        let scroll = wheel_scroll(&input).y;
+        let zoom = input.zoom_delta();
+        if scroll != 0.0 || zoom != 1.0 {
+            let radius = self.radius();
+            self.set_radius(radius * (-ZOOM_SPEED * scroll).exp() / zoom)
+                .update(context);
+        }
     }
 }
@@ -0,0 +1,176 @@
+use eframe::wgpu;
+
+use crate::{
+    context::Context,
+    util::{orbit_camera::CameraUniform, uniform_buffer::UniformBuffer},
+};
+
+// Where (0, 0) is. Positions name the top-left corner of a quad with TopLeft, where y goes
+// down, and the bottom-left one with Center, where y goes up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Origin {
+    TopLeft,
+    Center,
+}
+
+// Maps physical pixels from the origin to clip space, z is kept as the depth (0 to 1)
+pub fn pixel_projection(width: f32, height: f32, origin: Origin) -> cgmath::Matrix4<f32> {
+    let (width, height) = (width.max(1.0), height.max(1.0));
+    #[rustfmt::skip]
+    let matrix = match origin {
+        Origin::TopLeft => cgmath::Matrix4::new(
+            2.0 / width, 0.0, 0.0, 0.0,
+            0.0, -2.0 / height, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            -1.0, 1.0, 0.0, 1.0,
+        ),
+        Origin::Center => cgmath::Matrix4::new(
+            2.0 / width, 0.0, 0.0, 0.0,
+            0.0, 2.0 / height, 0.0, 0.0,
+            0.0, 0.0, 1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        ),
+    };
+    matrix
+}
+
+// 2D camera in pixels: at zoom 1 a world unit is a physical pixel, position is the world point
+// shown at the origin and zoom scales around it. Same uniform layout as Camera and OrbitCamera,
+// and SpriteBatch::set_camera draws through it.
+pub struct OrthoCamera {
+    origin: Origin,
+    viewport: [f32; 2],
+    position: cgmath::Vector2<f32>,
+    zoom: f32,
+    uniform: CameraUniform,
+    buffer: UniformBuffer<CameraUniform>,
+    bind_group: wgpu::BindGroup,
+}
+
+impl OrthoCamera {
+    // Sized like the window
+    pub fn new(context: &Context, origin: Origin) -> Self {
+        let uniform = CameraUniform::new();
+        // Two 4x4 matrices, always a valid uniform size
+        let buffer = UniformBuffer::new(context, uniform).unwrap();
+        let bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let bind_group = context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.binding_resource(),
+                }],
+                label: Some("Ortho Camera Bind Group"),
+            });
+
+        let mut res = Self {
+            origin,
+            viewport: context.size().into(),
+            position: cgmath::vec2(0.0, 0.0),
+            zoom: 1.0,
+            uniform,
+            buffer,
+            bind_group,
+        };
+        res.update(context);
+        res
+    }
+
+    pub fn view_matrix(&self) -> cgmath::Matrix4<f32> {
+        cgmath::Matrix4::from_nonuniform_scale(self.zoom, self.zoom, 1.0)
+            * cgmath::Matrix4::from_translation(cgmath::vec3(
+                -self.position.x,
+                -self.position.y,
+                0.0,
+            ))
+    }
+
+    pub fn proj_matrix(&self) -> cgmath::Matrix4<f32> {
+        pixel_projection(self.viewport[0], self.viewport[1], self.origin)
+    }
+
+    pub fn view_proj_matrix(&self) -> [[f32; 4]; 4] {
+        (self.proj_matrix() * self.view_matrix()).into()
+    }
+
+    pub fn update(&mut self, context: &Context) {
+        self.uniform.update_proj(self.proj_matrix());
+        self.uniform.update_view(self.view_matrix());
+        self.buffer.update(context.queue(), self.uniform);
+    }
+
+    // World position under a pixel counted from the top-left corner of the window whatever the
+    // origin, Input::mouse_position times Context::scale_factor for the cursor
+    pub fn screen_to_world(&self, pixel: [f32; 2]) -> cgmath::Vector2<f32> {
+        let from_origin = match self.origin {
+            Origin::TopLeft => cgmath::vec2(pixel[0], pixel[1]),
+            Origin::Center => cgmath::vec2(
+                pixel[0] - self.viewport[0] / 2.0,
+                self.viewport[1] / 2.0 - pixel[1],
+            ),
+        };
+        from_origin / self.zoom + self.position
+    }
+
+    // The other way, for TextRenderer::draw_text which takes pixels from the top-left corner
+    pub fn world_to_screen(&self, world: cgmath::Vector2<f32>) -> [f32; 2] {
+        let from_origin = (world - self.position) * self.zoom;
+        match self.origin {
+            Origin::TopLeft => [from_origin.x, from_origin.y],
+            Origin::Center => [
+                from_origin.x + self.viewport[0] / 2.0,
+                self.viewport[1] / 2.0 - from_origin.y,
+            ],
+        }
+    }
+
+    pub fn set_origin(&mut self, origin: Origin) -> &mut Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    // Takes the App::resize arguments directly
+    pub fn set_viewport(&mut self, width: u32, height: u32) -> &mut Self {
+        if width > 0 && height > 0 {
+            self.viewport = [width as f32, height as f32];
+        }
+        self
+    }
+
+    pub fn viewport(&self) -> [f32; 2] {
+        self.viewport
+    }
+
+    pub fn set_position(&mut self, position: cgmath::Vector2<f32>) -> &mut Self {
+        self.position = position;
+        self
+    }
+
+    pub fn position(&self) -> cgmath::Vector2<f32> {
+        self.position
+    }
+
+    // Above 1 zooms in, ignored unless positive
+    pub fn set_zoom(&mut self, zoom: f32) -> &mut Self {
+        if zoom > 0.0 {
+            self.zoom = zoom;
+        }
+        self
+    }
+
+    pub fn zoom(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
@@ -0,0 +1,66 @@
+struct Particle {
+    position: vec3<f32>,
+    // Negative until the particle is first emitted
+    age: f32,
+    velocity: vec3<f32>,
+    padding: f32,
+};
+
+struct Params {
+    emitter: vec3<f32>,
+    dt: f32,
+    gravity: vec3<f32>,
+    lifetime: f32,
+    color: vec4<f32>,
+    speed: f32,
+    spread: f32,
+    size: f32,
+    seed: u32,
+    count: u32,
+};
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> src: array<Particle>;
+@group(0) @binding(2) var<storage, read_write> dst: array<Particle>;
+
+fn pcg(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn random(value: u32) -> f32 {
+    return f32(pcg(value)) / 4294967295.0;
+}
+
+// Leaves the emitter in a cone around +Y, spread is the cone half angle in radians
+fn emit(index: u32, particle: ptr<function, Particle>) {
+    let key = index * 3u + params.seed * 2654435761u;
+    let angle = random(key) * 6.2831853;
+    let tilt = random(key + 1u) * params.spread;
+    let speed = params.speed * (0.75 + 0.5 * random(key + 2u));
+    let direction = vec3<f32>(sin(tilt) * cos(angle), cos(tilt), sin(tilt) * sin(angle));
+    (*particle).position = params.emitter;
+    (*particle).velocity = direction * speed;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.count {
+        return;
+    }
+    var particle = src[index];
+    let previous_age = particle.age;
+    particle.age += params.dt;
+    if particle.age >= params.lifetime {
+        particle.age -= params.lifetime;
+        emit(index, &particle);
+    } else if previous_age < 0.0 && particle.age >= 0.0 {
+        emit(index, &particle);
+    } else if particle.age >= 0.0 {
+        particle.velocity += params.gravity * params.dt;
+        particle.position += particle.velocity * params.dt;
+    }
+    dst[index] = particle;
+}
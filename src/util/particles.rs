@@ -0,0 +1,273 @@
+use eframe::wgpu::{self, util::DeviceExt};
+
+use crate::{
+    context::{Context, ContextError},
+    util::{
+        bind_group::BindGroupBuilder,
+        orbit_camera::CameraUniform,
+        pipeline::{ComputePipelineBuilder, RenderPipelineBuilder},
+        uniform_buffer::UniformBuffer,
+    },
+    Vertex,
+};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Particle {
+    position: [f32; 3],
+    age: f32,
+    velocity: [f32; 3],
+    padding: f32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ParticleParams {
+    emitter: [f32; 3],
+    dt: f32,
+    gravity: [f32; 3],
+    lifetime: f32,
+    color: [f32; 4],
+    speed: f32,
+    spread: f32,
+    size: f32,
+    seed: u32,
+    count: u32,
+    _padding: [u32; 3],
+}
+
+// Particles emitted from a point in a cone around +Y, then falling under gravity until their
+// lifetime runs out and they are emitted again. The state lives in two storage buffers: each
+// update integrates one into the other on the GPU and draw uses the one just written, as
+// camera facing quads. Uses the camera uniform layout at group 0 like DebugDraw.
+pub struct ParticleSystem {
+    buffers: [wgpu::Buffer; 2],
+    bind_groups: [wgpu::BindGroup; 2],
+    current: usize,
+    max_count: u32,
+    params: ParticleParams,
+    params_buffer: UniformBuffer<ParticleParams>,
+    params_bind_group: wgpu::BindGroup,
+    update_pipeline: wgpu::ComputePipeline,
+    render_pipeline: wgpu::RenderPipeline,
+}
+
+impl ParticleSystem {
+    // Room for max_count particles, all of them spawned until set_spawn_count says otherwise
+    pub fn new(context: &Context, max_count: u32) -> Result<Self, ContextError> {
+        if !context
+            .downlevel_flags()
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return Err(ContextError::DownlevelNotSupported(
+                wgpu::DownlevelFlags::COMPUTE_SHADERS,
+            ));
+        }
+        let max_count = max_count.max(1);
+        let params = ParticleParams {
+            emitter: [0.0, 0.0, 0.0],
+            dt: 0.0,
+            gravity: [0.0, -9.81, 0.0],
+            lifetime: 2.0,
+            color: [1.0, 0.6, 0.2, 1.0],
+            speed: 5.0,
+            spread: 0.3,
+            size: 0.05,
+            seed: 0,
+            count: max_count,
+            _padding: [0; 3],
+        };
+        let params_buffer = UniformBuffer::new(context, params)?;
+
+        let particles = initial_particles(max_count, &params);
+        let buffers = [0, 1].map(|_| {
+            context
+                .device()
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Particle Buffer"),
+                    contents: bytemuck::cast_slice(&particles),
+                    usage: wgpu::BufferUsages::STORAGE
+                        | wgpu::BufferUsages::VERTEX
+                        | wgpu::BufferUsages::COPY_DST,
+                })
+        });
+
+        // bind_groups[i] reads buffers[i] and writes the other one
+        let (update_layout, forward) = update_bind_group(context, &params_buffer, &buffers, 0);
+        let (_, backward) = update_bind_group(context, &params_buffer, &buffers, 1);
+
+        let update_shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Particle Update Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("particle_update.wgsl").into()),
+            });
+        let update_pipeline = ComputePipelineBuilder::new(&update_shader)
+            .label("Particle Update Pipeline")
+            .bind_group_layout(&update_layout)
+            .build(context);
+
+        let (params_layout, params_bind_group) = BindGroupBuilder::new()
+            .label("Particle Params Bind Group")
+            .uniform(
+                0,
+                params_buffer.buffer(),
+                wgpu::ShaderStages::VERTEX_FRAGMENT,
+            )
+            .build(context);
+        let camera_bind_group_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Particle Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("particles.wgsl").into()),
+            });
+        let render_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Particle Pipeline")
+            .bind_group_layout(&camera_bind_group_layout)
+            .bind_group_layout(&params_layout)
+            .vertex_layout(Particle::desc())
+            .blend_alpha()
+            .cull_mode(None)
+            .depth_write(false)
+            .build(context);
+
+        Ok(Self {
+            buffers,
+            bind_groups: [forward, backward],
+            current: 0,
+            max_count,
+            params,
+            params_buffer,
+            params_bind_group,
+            update_pipeline,
+            render_pipeline,
+        })
+    }
+
+    pub fn set_emitter(&mut self, emitter: cgmath::Point3<f32>) -> &mut Self {
+        self.params.emitter = emitter.into();
+        self
+    }
+
+    pub fn set_gravity(&mut self, gravity: cgmath::Vector3<f32>) -> &mut Self {
+        self.params.gravity = gravity.into();
+        self
+    }
+
+    // Average emission speed, each particle gets up to 25% more or less
+    pub fn set_speed(&mut self, speed: f32) -> &mut Self {
+        self.params.speed = speed;
+        self
+    }
+
+    // Half angle of the emission cone in radians
+    pub fn set_spread(&mut self, spread: f32) -> &mut Self {
+        self.params.spread = spread;
+        self
+    }
+
+    // In seconds, takes effect as particles are emitted again
+    pub fn set_lifetime(&mut self, lifetime: f32) -> &mut Self {
+        self.params.lifetime = lifetime.max(f32::EPSILON);
+        self
+    }
+
+    // Quad side in world units
+    pub fn set_size(&mut self, size: f32) -> &mut Self {
+        self.params.size = size;
+        self
+    }
+
+    // Linear RGBA, alpha fades out over the lifetime
+    pub fn set_color(&mut self, color: [f32; 4]) -> &mut Self {
+        self.params.color = color;
+        self
+    }
+
+    // How many of the particles are simulated and drawn, up to the count given to new
+    pub fn set_spawn_count(&mut self, count: u32) -> &mut Self {
+        self.params.count = count.min(self.max_count);
+        self
+    }
+
+    pub fn spawn_count(&self) -> u32 {
+        self.params.count
+    }
+
+    pub fn max_count(&self) -> u32 {
+        self.max_count
+    }
+
+    // Back to no particle emitted, they come out again spread over one lifetime
+    pub fn reset(&mut self, context: &Context) {
+        let particles = initial_particles(self.max_count, &self.params);
+        for buffer in &self.buffers {
+            context
+                .queue()
+                .write_buffer(buffer, 0, bytemuck::cast_slice(&particles));
+        }
+    }
+
+    // Steps the simulation by the frame's delta time, from App::prepare
+    pub fn update(&mut self, encoder: &mut wgpu::CommandEncoder, context: &Context) {
+        self.params.dt = context.time().delta_seconds();
+        self.params.seed = self.params.seed.wrapping_add(1);
+        self.params_buffer.update(context.queue(), self.params);
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Update Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.update_pipeline);
+        compute_pass.set_bind_group(0, &self.bind_groups[self.current], &[]);
+        compute_pass.dispatch_workgroups(self.params.count.div_ceil(WORKGROUP_SIZE), 1, 1);
+        self.current = 1 - self.current;
+    }
+
+    pub fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        if self.params.count == 0 {
+            return;
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.params_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.buffers[self.current].slice(..));
+        render_pass.draw(0..6, 0..self.params.count);
+    }
+}
+
+// Ages spread over minus one lifetime so emission starts evenly instead of all at once
+fn initial_particles(count: u32, params: &ParticleParams) -> Vec<Particle> {
+    (0..count)
+        .map(|i| Particle {
+            position: params.emitter,
+            age: -params.lifetime * i as f32 / count as f32,
+            velocity: [0.0; 3],
+            padding: 0.0,
+        })
+        .collect()
+}
+
+fn update_bind_group(
+    context: &Context,
+    params_buffer: &UniformBuffer<ParticleParams>,
+    buffers: &[wgpu::Buffer; 2],
+    src: usize,
+) -> (wgpu::BindGroupLayout, wgpu::BindGroup) {
+    BindGroupBuilder::new()
+        .label("Particle Update Bind Group")
+        .uniform(0, params_buffer.buffer(), wgpu::ShaderStages::COMPUTE)
+        .storage(1, &buffers[src], true, wgpu::ShaderStages::COMPUTE)
+        .storage(2, &buffers[1 - src], false, wgpu::ShaderStages::COMPUTE)
+        .build(context)
+}
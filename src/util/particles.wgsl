@@ -0,0 +1,64 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+struct Params {
+    emitter: vec3<f32>,
+    dt: f32,
+    gravity: vec3<f32>,
+    lifetime: f32,
+    color: vec4<f32>,
+    speed: f32,
+    spread: f32,
+    size: f32,
+    seed: u32,
+    count: u32,
+};
+@group(1) @binding(0) var<uniform> params: Params;
+
+struct ParticleInput {
+    @location(0) position: vec3<f32>,
+    @location(1) age: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) alpha: f32,
+};
+
+// Camera facing quad, two triangles built from the vertex index
+@vertex
+fn vs_main(@builtin(vertex_index) vertex_index: u32, particle: ParticleInput) -> VertexOutput {
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0),
+        vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex_index];
+    let right = vec3<f32>(camera.view[0][0], camera.view[1][0], camera.view[2][0]);
+    let up = vec3<f32>(camera.view[0][1], camera.view[1][1], camera.view[2][1]);
+    // Particles not emitted yet collapse to a point
+    let size = select(0.0, params.size * 0.5, particle.age >= 0.0);
+    let position = particle.position + (right * corner.x + up * corner.y) * size;
+
+    var out: VertexOutput;
+    out.clip_position = camera.proj * camera.view * vec4<f32>(position, 1.0);
+    out.uv = corner;
+    out.alpha = 1.0 - clamp(particle.age / params.lifetime, 0.0, 1.0);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let falloff = 1.0 - dot(in.uv, in.uv);
+    if falloff <= 0.0 {
+        discard;
+    }
+    return vec4<f32>(params.color.rgb, params.color.a * falloff * in.alpha);
+}
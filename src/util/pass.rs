@@ -0,0 +1,250 @@
+use std::ops::Range;
+
+use eframe::wgpu;
+
+use crate::util::reflect::ShaderLayout;
+
+// A render pipeline with the bind group entries its shader expects, see Pass
+pub struct CheckedPipeline {
+    pipeline: wgpu::RenderPipeline,
+    #[cfg(debug_assertions)]
+    label: String,
+    #[cfg(debug_assertions)]
+    groups: Vec<Vec<wgpu::BindGroupLayoutEntry>>,
+}
+
+impl CheckedPipeline {
+    // layout is what the shader declares, ShaderLayout::from_wgsl on its source
+    pub fn new(label: &str, pipeline: wgpu::RenderPipeline, layout: &ShaderLayout) -> Self {
+        #[cfg(not(debug_assertions))]
+        let _ = (label, layout);
+        Self {
+            pipeline,
+            #[cfg(debug_assertions)]
+            label: String::from(label),
+            #[cfg(debug_assertions)]
+            groups: (0..layout.group_count() as u32)
+                .map(|group| layout.entries(group).to_vec())
+                .collect(),
+        }
+    }
+
+    pub fn pipeline(&self) -> &wgpu::RenderPipeline {
+        &self.pipeline
+    }
+}
+
+// A bind group with the layout entries it was created with, BindGroupBuilder::build_checked
+// makes one
+pub struct CheckedBindGroup {
+    bind_group: wgpu::BindGroup,
+    #[cfg(debug_assertions)]
+    entries: Vec<wgpu::BindGroupLayoutEntry>,
+}
+
+impl CheckedBindGroup {
+    pub fn new(bind_group: wgpu::BindGroup, entries: &[wgpu::BindGroupLayoutEntry]) -> Self {
+        #[cfg(not(debug_assertions))]
+        let _ = entries;
+        Self {
+            bind_group,
+            #[cfg(debug_assertions)]
+            entries: entries.to_vec(),
+        }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
+
+// Wraps a render pass to check, in debug builds, that the bind groups set before a draw are
+// what the pipeline's shader expects. A mismatch panics with the group and binding at fault
+// ("group 1 expects a uniform buffer at binding 0, got a texture") instead of a wgpu
+// validation error. Release builds make the raw calls and keep nothing.
+//
+// Draws made through raw (GpuMesh::draw...) aren't checked, call check before them.
+pub struct Pass<'r, 'p> {
+    render_pass: &'r mut wgpu::RenderPass<'p>,
+    #[cfg(debug_assertions)]
+    pipeline: Option<(String, Vec<Vec<wgpu::BindGroupLayoutEntry>>)>,
+    #[cfg(debug_assertions)]
+    bind_groups: Vec<Option<Vec<wgpu::BindGroupLayoutEntry>>>,
+}
+
+impl<'r, 'p> Pass<'r, 'p> {
+    pub fn new(render_pass: &'r mut wgpu::RenderPass<'p>) -> Self {
+        Self {
+            render_pass,
+            #[cfg(debug_assertions)]
+            pipeline: None,
+            #[cfg(debug_assertions)]
+            bind_groups: Vec::new(),
+        }
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: &CheckedPipeline) -> &mut Self {
+        self.render_pass.set_pipeline(&pipeline.pipeline);
+        #[cfg(debug_assertions)]
+        {
+            self.pipeline = Some((pipeline.label.clone(), pipeline.groups.clone()));
+        }
+        self
+    }
+
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &CheckedBindGroup,
+        offsets: &[wgpu::DynamicOffset],
+    ) -> &mut Self {
+        self.render_pass
+            .set_bind_group(index, &bind_group.bind_group, offsets);
+        #[cfg(debug_assertions)]
+        {
+            let index = index as usize;
+            if self.bind_groups.len() <= index {
+                self.bind_groups.resize(index + 1, None);
+            }
+            self.bind_groups[index] = Some(bind_group.entries.clone());
+        }
+        self
+    }
+
+    pub fn set_vertex_buffer(
+        &mut self,
+        slot: u32,
+        buffer_slice: wgpu::BufferSlice<'_>,
+    ) -> &mut Self {
+        self.render_pass.set_vertex_buffer(slot, buffer_slice);
+        self
+    }
+
+    pub fn set_index_buffer(
+        &mut self,
+        buffer_slice: wgpu::BufferSlice<'_>,
+        index_format: wgpu::IndexFormat,
+    ) -> &mut Self {
+        self.render_pass
+            .set_index_buffer(buffer_slice, index_format);
+        self
+    }
+
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) -> &mut Self {
+        self.check();
+        self.render_pass.draw(vertices, instances);
+        self
+    }
+
+    pub fn draw_indexed(
+        &mut self,
+        indices: Range<u32>,
+        base_vertex: i32,
+        instances: Range<u32>,
+    ) -> &mut Self {
+        self.check();
+        self.render_pass
+            .draw_indexed(indices, base_vertex, instances);
+        self
+    }
+
+    // For the calls the wrapper doesn't cover
+    pub fn raw(&mut self) -> &mut wgpu::RenderPass<'p> {
+        self.render_pass
+    }
+
+    // Panics when a group of the pipeline has no bind group or one with other bindings, does
+    // nothing in release builds
+    pub fn check(&self) {
+        #[cfg(debug_assertions)]
+        {
+            let Some((label, groups)) = &self.pipeline else {
+                panic!("draw without a pipeline, call set_pipeline first");
+            };
+            for (group, expected) in groups.iter().enumerate() {
+                let set = self.bind_groups.get(group).and_then(Option::as_ref);
+                if let Err(msg) = check_group(group, expected, set) {
+                    panic!("{msg} (pipeline \"{label}\")");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+fn check_group(
+    group: usize,
+    expected: &[wgpu::BindGroupLayoutEntry],
+    set: Option<&Vec<wgpu::BindGroupLayoutEntry>>,
+) -> Result<(), String> {
+    if expected.is_empty() {
+        return Ok(());
+    }
+    let Some(set) = set else {
+        return Err(format!(
+            "group {group} has no bind group, call set_bind_group({group}, ...)"
+        ));
+    };
+    for entry in expected {
+        let expected_kind = binding_kind(&entry.ty);
+        match set.iter().find(|set_entry| set_entry.binding == entry.binding) {
+            None => {
+                return Err(format!(
+                    "group {group} expects {expected_kind} at binding {}, the bind group has nothing there",
+                    entry.binding
+                ))
+            }
+            Some(set_entry) => {
+                let set_kind = binding_kind(&set_entry.ty);
+                if set_kind != expected_kind {
+                    return Err(format!(
+                        "group {group} expects {expected_kind} at binding {}, got {set_kind}",
+                        entry.binding
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Coarse on purpose: reflection can't tell a filterable texture or sampler from a
+// non-filtering one, so those aren't compared
+#[cfg(debug_assertions)]
+fn binding_kind(ty: &wgpu::BindingType) -> String {
+    match ty {
+        wgpu::BindingType::Buffer { ty, .. } => match ty {
+            wgpu::BufferBindingType::Uniform => String::from("a uniform buffer"),
+            wgpu::BufferBindingType::Storage { read_only: true } => {
+                String::from("a read-only storage buffer")
+            }
+            wgpu::BufferBindingType::Storage { read_only: false } => {
+                String::from("a storage buffer")
+            }
+        },
+        wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison) => {
+            String::from("a comparison sampler")
+        }
+        wgpu::BindingType::Sampler(_) => String::from("a sampler"),
+        wgpu::BindingType::Texture {
+            sample_type,
+            view_dimension,
+            multisampled,
+        } => {
+            let sample_type = match sample_type {
+                wgpu::TextureSampleType::Float { .. } => "float",
+                wgpu::TextureSampleType::Depth => "depth",
+                wgpu::TextureSampleType::Sint => "sint",
+                wgpu::TextureSampleType::Uint => "uint",
+            };
+            let multisampled = if *multisampled { " multisampled" } else { "" };
+            format!("a {view_dimension:?}{multisampled} {sample_type} texture")
+        }
+        wgpu::BindingType::StorageTexture {
+            format,
+            view_dimension,
+            ..
+        } => format!("a {view_dimension:?} {format:?} storage texture"),
+        wgpu::BindingType::AccelerationStructure => String::from("an acceleration structure"),
+    }
+}
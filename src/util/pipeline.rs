@@ -0,0 +1,550 @@
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{depth_target::DepthTarget, reflect::ShaderLayout, render_target::RenderTarget},
+};
+
+// Adds the fragment color to the target as is, alpha doesn't weight it: fade by scaling the
+// color in the shader. For glows, fire and light accumulation. Alpha composites like OVER.
+pub const ADDITIVE_BLENDING: wgpu::BlendState = wgpu::BlendState {
+    color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+    },
+    alpha: wgpu::BlendComponent::OVER,
+};
+
+// Defaults match the surface render pass: context color format, depth format and sample count
+pub struct RenderPipelineBuilder<'a> {
+    label: Option<&'a str>,
+    shader: &'a wgpu::ShaderModule,
+    vertex_entry_point: &'a str,
+    fragment_entry_point: &'a str,
+    vertex_layouts: Vec<wgpu::VertexBufferLayout<'a>>,
+    bind_group_layouts: Vec<&'a wgpu::BindGroupLayout>,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+    // Replaces bind_group_layouts when set
+    shader_layout: Option<&'a ShaderLayout>,
+    // None for the context color format
+    formats: Option<Vec<wgpu::TextureFormat>>,
+    blend: Option<wgpu::BlendState>,
+    topology: wgpu::PrimitiveTopology,
+    cull_mode: Option<wgpu::Face>,
+    polygon_mode: wgpu::PolygonMode,
+    depth_stencil_format: Option<Option<wgpu::TextureFormat>>,
+    depth_write_enabled: bool,
+    depth_compare: wgpu::CompareFunction,
+    depth_bias: wgpu::DepthBiasState,
+    sample_count: Option<u32>,
+}
+
+impl<'a> RenderPipelineBuilder<'a> {
+    pub fn new(shader: &'a wgpu::ShaderModule) -> Self {
+        Self {
+            label: None,
+            shader,
+            vertex_entry_point: "vs_main",
+            fragment_entry_point: "fs_main",
+            vertex_layouts: Vec::new(),
+            bind_group_layouts: Vec::new(),
+            push_constant_ranges: Vec::new(),
+            shader_layout: None,
+            formats: None,
+            blend: Some(wgpu::BlendState::REPLACE),
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            depth_stencil_format: None,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::Less,
+            depth_bias: wgpu::DepthBiasState::default(),
+            sample_count: None,
+        }
+    }
+
+    pub fn label(&mut self, label: &'a str) -> &mut Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn entry_points(&mut self, vertex: &'a str, fragment: &'a str) -> &mut Self {
+        self.vertex_entry_point = vertex;
+        self.fragment_entry_point = fragment;
+        self
+    }
+
+    pub fn vertex_layout(&mut self, layout: wgpu::VertexBufferLayout<'a>) -> &mut Self {
+        self.vertex_layouts.push(layout);
+        self
+    }
+
+    pub fn bind_group_layout(&mut self, layout: &'a wgpu::BindGroupLayout) -> &mut Self {
+        self.bind_group_layouts.push(layout);
+        self
+    }
+
+    // The next groups, in order, such as the layouts of reflect::ShaderLayout::build
+    pub fn bind_group_layouts(&mut self, layouts: &'a [wgpu::BindGroupLayout]) -> &mut Self {
+        self.bind_group_layouts.extend(layouts);
+        self
+    }
+
+    // The bind group layouts are built from the reflected layout of the shader, ShaderLayout::
+    // from_wgsl on its source with its overrides, instead of the ones given to bind_group_layout.
+    // Bind groups take theirs from get_bind_group_layout on the pipeline, or from
+    // ShaderLayout::build, and fit every pipeline built from the same entries. Pass the same
+    // layout to CheckedPipeline::new so it checks against those entries.
+    pub fn auto_layout(&mut self, layout: &'a ShaderLayout) -> &mut Self {
+        self.shader_layout = Some(layout);
+        self
+    }
+
+    // A T at offset 0 for the given stages, see set_push_constants
+    pub fn push_constants<T: bytemuck::Pod>(&mut self, stages: wgpu::ShaderStages) -> &mut Self {
+        self.push_constant_ranges
+            .push(push_constant_range::<T>(stages));
+        self
+    }
+
+    pub fn format(&mut self, format: wgpu::TextureFormat) -> &mut Self {
+        self.formats = Some(vec![format]);
+        self
+    }
+
+    // One fragment target per format, for a pass with as many color attachments. They all use
+    // the blend state. Empty builds a depth only pipeline, without fragment stage.
+    pub fn formats(&mut self, formats: &[wgpu::TextureFormat]) -> &mut Self {
+        self.formats = Some(formats.to_vec());
+        self
+    }
+
+    // Targets, depth format and sample count of a render target
+    pub fn render_target(&mut self, target: &RenderTarget) -> &mut Self {
+        self.formats = Some(target.formats().to_vec());
+        self.depth_stencil_format = Some(target.depth_format());
+        self.sample_count = Some(target.sample_count());
+        self
+    }
+
+    // Depth only, no fragment stage: the vertex entry point is all the shader needs
+    pub fn depth_target(&mut self, target: &DepthTarget) -> &mut Self {
+        self.formats = Some(Vec::new());
+        self.depth_stencil_format = Some(Some(target.format()));
+        self.sample_count = Some(1);
+        self
+    }
+
+    pub fn blend(&mut self, blend: Option<wgpu::BlendState>) -> &mut Self {
+        self.blend = blend;
+        self
+    }
+
+    // Opaque, the fragment replaces the target. The default.
+    pub fn blend_none(&mut self) -> &mut Self {
+        self.blend(Some(wgpu::BlendState::REPLACE))
+    }
+
+    // Straight alpha: the shader returns the plain color with its opacity in alpha, as sampled
+    // from most PNGs. Returning a color already multiplied by alpha here multiplies it twice and
+    // darkens the edges, use blend_premultiplied for those.
+    pub fn blend_alpha(&mut self) -> &mut Self {
+        self.blend(Some(wgpu::BlendState::ALPHA_BLENDING))
+    }
+
+    // Premultiplied alpha: the shader returns the color already multiplied by alpha, as egui
+    // does. Filtering premultiplied textures keeps their edges from going dark.
+    pub fn blend_premultiplied(&mut self) -> &mut Self {
+        self.blend(Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING))
+    }
+
+    // See ADDITIVE_BLENDING
+    pub fn blend_additive(&mut self) -> &mut Self {
+        self.blend(Some(ADDITIVE_BLENDING))
+    }
+
+    pub fn primitive_topology(&mut self, topology: wgpu::PrimitiveTopology) -> &mut Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn cull_mode(&mut self, cull_mode: Option<wgpu::Face>) -> &mut Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    // Line needs Features::POLYGON_MODE_LINE (see Runner::set_wireframe_support) and Point
+    // Features::POLYGON_MODE_POINT, try_build reports a missing one
+    pub fn polygon_mode(&mut self, polygon_mode: wgpu::PolygonMode) -> &mut Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    // None renders without depth test, the render pass must have no depth attachment either
+    pub fn depth_format(&mut self, format: Option<wgpu::TextureFormat>) -> &mut Self {
+        self.depth_stencil_format = Some(format);
+        self
+    }
+
+    pub fn depth_write(&mut self, enabled: bool) -> &mut Self {
+        self.depth_write_enabled = enabled;
+        self
+    }
+
+    pub fn depth_compare(&mut self, compare: wgpu::CompareFunction) -> &mut Self {
+        self.depth_compare = compare;
+        self
+    }
+
+    // Pushes the written depth away, constant in depth units and slope_scale times the slope of
+    // the triangle. Against the acne of shadow maps rendered with this pipeline.
+    pub fn depth_bias(&mut self, constant: i32, slope_scale: f32) -> &mut Self {
+        self.depth_bias = wgpu::DepthBiasState {
+            constant,
+            slope_scale,
+            clamp: 0.0,
+        };
+        self
+    }
+
+    pub fn sample_count(&mut self, sample_count: u32) -> &mut Self {
+        self.sample_count = Some(sample_count);
+        self
+    }
+
+    pub fn build(&self, context: &Context) -> wgpu::RenderPipeline {
+        self.create(context, self.polygon_mode)
+    }
+
+    // Same as build, but features the pipeline relies on are checked first so a missing one is
+    // an error instead of a wgpu validation panic
+    pub fn try_build(&self, context: &Context) -> Result<wgpu::RenderPipeline, ContextError> {
+        check_features(context, self.required_features(self.polygon_mode))?;
+        Ok(self.build(context))
+    }
+
+    // Same as try_build, and the fragment targets, depth format and sample count must match the
+    // attachments of target, in order, instead of failing when the pipeline is first used
+    pub fn try_build_for(
+        &self,
+        context: &Context,
+        target: &RenderTarget,
+    ) -> Result<wgpu::RenderPipeline, ContextError> {
+        let formats = self.color_formats(context);
+        if formats != target.formats() {
+            return Err(ContextError::TargetMismatch(format!(
+                "fragment targets {formats:?}, color attachments {:?}",
+                target.formats()
+            )));
+        }
+        let depth_format = self.resolved_depth_format(context);
+        if depth_format != target.depth_format() {
+            return Err(ContextError::TargetMismatch(format!(
+                "depth format {depth_format:?}, depth attachment {:?}",
+                target.depth_format()
+            )));
+        }
+        let sample_count = self.resolved_sample_count(context);
+        if sample_count != target.sample_count() {
+            return Err(ContextError::TargetMismatch(format!(
+                "sample count {sample_count}, attachments sample count {}",
+                target.sample_count()
+            )));
+        }
+        self.try_build(context)
+    }
+
+    // A Fill and a Line variant of the pipeline, to switch to wireframe at runtime
+    pub fn build_wireframe(&self, context: &Context) -> Result<WireframePipeline, ContextError> {
+        check_features(context, self.required_features(wgpu::PolygonMode::Line))?;
+        Ok(WireframePipeline {
+            fill: self.create(context, wgpu::PolygonMode::Fill),
+            line: self.create(context, wgpu::PolygonMode::Line),
+            wireframe: false,
+        })
+    }
+
+    fn required_features(&self, polygon_mode: wgpu::PolygonMode) -> wgpu::Features {
+        let mut features = match polygon_mode {
+            wgpu::PolygonMode::Fill => wgpu::Features::empty(),
+            wgpu::PolygonMode::Line => wgpu::Features::POLYGON_MODE_LINE,
+            wgpu::PolygonMode::Point => wgpu::Features::POLYGON_MODE_POINT,
+        };
+        if !self.push_constant_ranges.is_empty() {
+            features |= wgpu::Features::PUSH_CONSTANTS;
+        }
+        features
+    }
+
+    fn color_formats(&self, context: &Context) -> Vec<wgpu::TextureFormat> {
+        self.formats
+            .clone()
+            .unwrap_or_else(|| vec![context.format()])
+    }
+
+    fn resolved_depth_format(&self, context: &Context) -> Option<wgpu::TextureFormat> {
+        self.depth_stencil_format
+            .unwrap_or(context.depth_stencil_format())
+    }
+
+    fn resolved_sample_count(&self, context: &Context) -> u32 {
+        self.sample_count.unwrap_or(context.sample_count())
+    }
+
+    fn create(&self, context: &Context, polygon_mode: wgpu::PolygonMode) -> wgpu::RenderPipeline {
+        self.create_on(context.device(), &self.targets(context), polygon_mode)
+    }
+
+    // What building takes from the context, resolved up front for PipelineCache's threads
+    pub(crate) fn targets(&self, context: &Context) -> PipelineTargets {
+        PipelineTargets {
+            formats: self.color_formats(context),
+            depth_stencil_format: self.resolved_depth_format(context),
+            sample_count: self.resolved_sample_count(context),
+        }
+    }
+
+    pub(crate) fn check_features(&self, context: &Context) -> Result<(), ContextError> {
+        check_features(context, self.required_features(self.polygon_mode))
+    }
+
+    // Same as build once the targets are resolved, needs no context so it can run on any thread
+    pub(crate) fn build_on(
+        &self,
+        device: &wgpu::Device,
+        targets: &PipelineTargets,
+    ) -> wgpu::RenderPipeline {
+        self.create_on(device, targets, self.polygon_mode)
+    }
+
+    fn create_on(
+        &self,
+        device: &wgpu::Device,
+        targets: &PipelineTargets,
+        polygon_mode: wgpu::PolygonMode,
+    ) -> wgpu::RenderPipeline {
+        let pipeline_layout = create_pipeline_layout(
+            device,
+            self.label,
+            self.shader_layout,
+            &self.bind_group_layouts,
+            &self.push_constant_ranges,
+        );
+
+        let depth_stencil_format = targets.depth_stencil_format;
+        let color_targets: Vec<_> = targets
+            .formats
+            .iter()
+            .map(|&format| {
+                Some(wgpu::ColorTargetState {
+                    format,
+                    blend: self.blend,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })
+            })
+            .collect();
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: self.label,
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: self.shader,
+                entry_point: self.vertex_entry_point,
+                buffers: &self.vertex_layouts,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: (!color_targets.is_empty()).then(|| wgpu::FragmentState {
+                module: self.shader,
+                entry_point: self.fragment_entry_point,
+                targets: &color_targets,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: self.topology,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: self.cull_mode,
+                polygon_mode,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: depth_stencil_format.map(|format| wgpu::DepthStencilState {
+                format,
+                depth_write_enabled: self.depth_write_enabled,
+                depth_compare: self.depth_compare,
+                stencil: wgpu::StencilState::default(),
+                bias: self.depth_bias,
+            }),
+            multisample: wgpu::MultisampleState {
+                count: targets.sample_count,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview: None,
+            cache: None,
+        })
+    }
+}
+
+pub(crate) struct PipelineTargets {
+    formats: Vec<wgpu::TextureFormat>,
+    depth_stencil_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
+}
+
+pub struct WireframePipeline {
+    fill: wgpu::RenderPipeline,
+    line: wgpu::RenderPipeline,
+    wireframe: bool,
+}
+
+impl WireframePipeline {
+    pub fn set_wireframe(&mut self, wireframe: bool) -> &mut Self {
+        self.wireframe = wireframe;
+        self
+    }
+
+    pub fn toggle(&mut self) -> &mut Self {
+        self.wireframe = !self.wireframe;
+        self
+    }
+
+    pub fn is_wireframe(&self) -> bool {
+        self.wireframe
+    }
+
+    // The Line pipeline in wireframe mode, the Fill one otherwise
+    pub fn pipeline(&self) -> &wgpu::RenderPipeline {
+        if self.wireframe {
+            &self.line
+        } else {
+            &self.fill
+        }
+    }
+}
+
+pub struct ComputePipelineBuilder<'a> {
+    label: Option<&'a str>,
+    shader: &'a wgpu::ShaderModule,
+    entry_point: &'a str,
+    bind_group_layouts: Vec<&'a wgpu::BindGroupLayout>,
+    push_constant_ranges: Vec<wgpu::PushConstantRange>,
+    // Replaces bind_group_layouts when set
+    shader_layout: Option<&'a ShaderLayout>,
+}
+
+impl<'a> ComputePipelineBuilder<'a> {
+    pub fn new(shader: &'a wgpu::ShaderModule) -> Self {
+        Self {
+            label: None,
+            shader,
+            entry_point: "cs_main",
+            bind_group_layouts: Vec::new(),
+            push_constant_ranges: Vec::new(),
+            shader_layout: None,
+        }
+    }
+
+    pub fn label(&mut self, label: &'a str) -> &mut Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn entry_point(&mut self, entry_point: &'a str) -> &mut Self {
+        self.entry_point = entry_point;
+        self
+    }
+
+    pub fn bind_group_layout(&mut self, layout: &'a wgpu::BindGroupLayout) -> &mut Self {
+        self.bind_group_layouts.push(layout);
+        self
+    }
+
+    // The next groups, in order, such as the layouts of reflect::ShaderLayout::build
+    pub fn bind_group_layouts(&mut self, layouts: &'a [wgpu::BindGroupLayout]) -> &mut Self {
+        self.bind_group_layouts.extend(layouts);
+        self
+    }
+
+    // Same as RenderPipelineBuilder::auto_layout
+    pub fn auto_layout(&mut self, layout: &'a ShaderLayout) -> &mut Self {
+        self.shader_layout = Some(layout);
+        self
+    }
+
+    pub fn push_constants<T: bytemuck::Pod>(&mut self) -> &mut Self {
+        self.push_constant_ranges
+            .push(push_constant_range::<T>(wgpu::ShaderStages::COMPUTE));
+        self
+    }
+
+    pub fn build(&self, context: &Context) -> wgpu::ComputePipeline {
+        let pipeline_layout = create_pipeline_layout(
+            context.device(),
+            self.label,
+            self.shader_layout,
+            &self.bind_group_layouts,
+            &self.push_constant_ranges,
+        );
+
+        context
+            .device()
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: self.label,
+                layout: Some(&pipeline_layout),
+                module: self.shader,
+                entry_point: self.entry_point,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+    }
+}
+
+fn create_pipeline_layout(
+    device: &wgpu::Device,
+    label: Option<&str>,
+    shader_layout: Option<&ShaderLayout>,
+    bind_group_layouts: &[&wgpu::BindGroupLayout],
+    push_constant_ranges: &[wgpu::PushConstantRange],
+) -> wgpu::PipelineLayout {
+    // The pipeline layout keeps the reflected ones alive
+    let reflected = shader_layout.map(|layout| layout.build_on(device));
+    let reflected_refs: Vec<_> = reflected.iter().flatten().collect();
+    device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label,
+        bind_group_layouts: if reflected.is_some() {
+            &reflected_refs
+        } else {
+            bind_group_layouts
+        },
+        push_constant_ranges,
+    })
+}
+
+fn check_features(context: &Context, required: wgpu::Features) -> Result<(), ContextError> {
+    let missing = required - context.device().features();
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(ContextError::FeatureNotEnabled(missing))
+    }
+}
+
+// Push constants need Runner::set_push_constants with at least size_of::<T>() bytes, they
+// aren't available on the web
+fn push_constant_range<T: bytemuck::Pod>(stages: wgpu::ShaderStages) -> wgpu::PushConstantRange {
+    wgpu::PushConstantRange {
+        stages,
+        range: 0..std::mem::size_of::<T>() as u32,
+    }
+}
+
+// Writes value at offset 0, to match a pipeline built with push_constants::<T>(stages)
+pub fn set_push_constants<T: bytemuck::Pod>(
+    render_pass: &mut wgpu::RenderPass<'_>,
+    stages: wgpu::ShaderStages,
+    value: &T,
+) {
+    render_pass.set_push_constants(stages, 0, bytemuck::bytes_of(value));
+}
@@ -0,0 +1,181 @@
+use std::collections::{HashMap, VecDeque};
+
+use eframe::wgpu;
+use web_time::{Duration, Instant};
+
+use crate::{
+    context::{Context, ContextError},
+    util::pipeline::RenderPipelineBuilder,
+};
+
+// Builds a pipeline for PipelineCache::queue. It owns what it needs, typically creating the
+// shader module and bind group layouts itself before building, since it runs frames later.
+// Send and Sync so that the app holding the cache still is.
+pub type PipelineJob =
+    Box<dyn FnOnce(&Context) -> Result<wgpu::RenderPipeline, ContextError> + Send + Sync>;
+
+// Render pipelines compiled ahead of their first use, by name, so the driver's shader compile
+// doesn't stall a frame mid-game. build_batch compiles a batch right away, spread over threads.
+// queue and compile spread the jobs over frames, to keep a loading screen drawing while they
+// compile. Each compile time is logged at the info level.
+#[derive(Default)]
+pub struct PipelineCache {
+    pipelines: HashMap<String, wgpu::RenderPipeline>,
+    compile_times: Vec<(String, Duration)>,
+    pending: VecDeque<(String, PipelineJob)>,
+    // Jobs queued and done since the queue was last empty, for progress
+    queued: usize,
+    done: usize,
+}
+
+impl PipelineCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Every builder is checked (like RenderPipelineBuilder::try_build) before any is compiled.
+    // On native, the pipelines are compiled in parallel on as many threads as there are cores,
+    // one after the other on the web. A name already in the cache is replaced.
+    pub fn build_batch(
+        &mut self,
+        context: &Context,
+        builders: &[(&str, &RenderPipelineBuilder)],
+    ) -> Result<(), ContextError> {
+        for (_, builder) in builders {
+            builder.check_features(context)?;
+        }
+        let jobs: Vec<_> = builders
+            .iter()
+            .map(|&(name, builder)| (name, builder, builder.targets(context)))
+            .collect();
+        let start = Instant::now();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let compiled: Vec<_> = {
+            let device = context.device();
+            let threads = std::thread::available_parallelism()
+                .map_or(1, usize::from)
+                .min(jobs.len())
+                .max(1);
+            let next = std::sync::atomic::AtomicUsize::new(0);
+            let mut compiled = std::thread::scope(|scope| {
+                let workers: Vec<_> = (0..threads)
+                    .map(|_| {
+                        scope.spawn(|| {
+                            let mut compiled = Vec::new();
+                            loop {
+                                let index = next.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                                let Some((_, builder, targets)) = jobs.get(index) else {
+                                    break compiled;
+                                };
+                                let start = Instant::now();
+                                let pipeline = builder.build_on(device, targets);
+                                compiled.push((index, pipeline, start.elapsed()));
+                            }
+                        })
+                    })
+                    .collect();
+                // A worker's panic (a wgpu validation error in build_on) is raised again here
+                // as is, instead of as a failed join
+                workers
+                    .into_iter()
+                    .flat_map(|worker| {
+                        worker
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect::<Vec<_>>()
+            });
+            compiled.sort_by_key(|&(index, _, _)| index);
+            compiled
+        };
+        #[cfg(target_arch = "wasm32")]
+        let compiled: Vec<_> = jobs
+            .iter()
+            .enumerate()
+            .map(|(index, (_, builder, targets))| {
+                let start = Instant::now();
+                let pipeline = builder.build_on(context.device(), targets);
+                (index, pipeline, start.elapsed())
+            })
+            .collect();
+
+        for (index, pipeline, time) in compiled {
+            self.insert(jobs[index].0, pipeline, time);
+        }
+        log::info!(
+            "{} pipelines compiled in {:.1} ms",
+            jobs.len(),
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+        Ok(())
+    }
+
+    // Compiled by the next calls to compile
+    pub fn queue(&mut self, name: &str, job: PipelineJob) -> &mut Self {
+        if self.pending.is_empty() {
+            self.queued = 0;
+            self.done = 0;
+        }
+        self.pending.push_back((String::from(name), job));
+        self.queued += 1;
+        self
+    }
+
+    // Runs queued jobs on this thread until budget is spent, always at least one, so calling it
+    // every frame from App::update leaves the frame time to draw a loading screen. A job that
+    // fails is dropped and its error returned, the next call goes on with the rest.
+    pub fn compile(&mut self, context: &Context, budget: Duration) -> Result<(), ContextError> {
+        let start = Instant::now();
+        while let Some((name, job)) = self.pending.pop_front() {
+            let job_start = Instant::now();
+            self.done += 1;
+            let pipeline = job(context)?;
+            self.insert(&name, pipeline, job_start.elapsed());
+            if start.elapsed() >= budget {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // From 0 to 1 over the jobs queued since the queue was last empty, 1 with nothing queued
+    pub fn progress(&self) -> f32 {
+        if self.queued == 0 {
+            1.0
+        } else {
+            self.done as f32 / self.queued as f32
+        }
+    }
+
+    // No queued job left to compile
+    pub fn is_ready(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&wgpu::RenderPipeline> {
+        self.pipelines.get(name)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.pipelines.contains_key(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<wgpu::RenderPipeline> {
+        self.pipelines.remove(name)
+    }
+
+    // Every pipeline compiled so far with how long it took, in the order they were compiled
+    pub fn compile_times(&self) -> &[(String, Duration)] {
+        &self.compile_times
+    }
+
+    fn insert(&mut self, name: &str, pipeline: wgpu::RenderPipeline, time: Duration) {
+        log::info!(
+            "pipeline {name} compiled in {:.1} ms",
+            time.as_secs_f64() * 1000.0
+        );
+        self.compile_times.push((String::from(name), time));
+        self.pipelines.insert(String::from(name), pipeline);
+    }
+}
@@ -0,0 +1,200 @@
+use eframe::wgpu::{self, util::DeviceExt};
+
+use crate::{
+    context::{Context, ContextError},
+    util::{
+        pipeline::RenderPipelineBuilder, sampler::SamplerBuilder,
+        uniform_buffer::check_uniform_size,
+    },
+};
+
+// Declares VertexOutput, vs_main, input_texture and input_sampler for the user shader
+const PRELUDE: &str = include_str!("post_process.wgsl");
+
+// Runs the fs_main of a WGSL shader over a full-screen triangle. The input is bound at group 0,
+// texture at binding 0 and sampler at binding 1; params, if any, go at binding 2.
+pub struct PostProcess {
+    layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params: Option<wgpu::Buffer>,
+    // RenderTarget outputs in the context color format
+    pipeline: wgpu::RenderPipeline,
+    // Main render pass, with its depth buffer and sample count
+    screen_pipeline: wgpu::RenderPipeline,
+}
+
+impl PostProcess {
+    pub fn new(context: &Context, wgsl_source: &str) -> Result<Self, ContextError> {
+        Self::create(context, wgsl_source, None)
+    }
+
+    // P is the WGSL params struct, declared by the shader as var<uniform> at binding 2
+    pub fn with_params<P: bytemuck::Pod>(
+        context: &Context,
+        wgsl_source: &str,
+        params: P,
+    ) -> Result<Self, ContextError> {
+        check_uniform_size::<P>()?;
+        let buffer = context
+            .device()
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Post Process Params Buffer"),
+                contents: bytemuck::bytes_of(&params),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        Self::create(context, wgsl_source, Some(buffer))
+    }
+
+    fn create(
+        context: &Context,
+        wgsl_source: &str,
+        params: Option<wgpu::Buffer>,
+    ) -> Result<Self, ContextError> {
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Post Process Shader"),
+                source: wgpu::ShaderSource::Wgsl(format!("{PRELUDE}\n{wgsl_source}").into()),
+            });
+
+        let sampler = SamplerBuilder::linear_clamp()
+            .label("Post Process Sampler")
+            .build(context)?;
+
+        let mut entries = vec![
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    multisampled: false,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ];
+        if params.is_some() {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            });
+        }
+        let layout = context
+            .device()
+            .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Post Process Bind Group Layout"),
+                entries: &entries,
+            });
+
+        let pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Post Process Pipeline")
+            .bind_group_layout(&layout)
+            .cull_mode(None)
+            .depth_format(None)
+            .sample_count(1)
+            .build(context);
+
+        let screen_pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Post Process Screen Pipeline")
+            .bind_group_layout(&layout)
+            .cull_mode(None)
+            .depth_write(false)
+            .depth_compare(wgpu::CompareFunction::Always)
+            .build(context);
+
+        Ok(Self {
+            layout,
+            sampler,
+            params,
+            pipeline,
+            screen_pipeline,
+        })
+    }
+
+    // Does nothing when created without params
+    pub fn set_params<P: bytemuck::Pod>(&self, queue: &wgpu::Queue, params: P) {
+        if let Some(buffer) = &self.params {
+            queue.write_buffer(buffer, 0, bytemuck::bytes_of(&params));
+        }
+    }
+
+    // Linear clamp by default, bind groups made afterwards sample with this one instead
+    pub fn set_sampler(&mut self, sampler: wgpu::Sampler) -> &mut Self {
+        self.sampler = sampler;
+        self
+    }
+
+    // For draw, rebuild it when the input view is recreated
+    pub fn bind_group(&self, context: &Context, input: &wgpu::TextureView) -> wgpu::BindGroup {
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(input),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&self.sampler),
+            },
+        ];
+        if let Some(buffer) = &self.params {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 2,
+                resource: buffer.as_entire_binding(),
+            });
+        }
+        context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Post Process Bind Group"),
+                layout: &self.layout,
+                entries: &entries,
+            })
+    }
+
+    // The output must be in the context color format, a RenderTarget created with it works
+    pub fn apply(
+        &self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        let bind_group = self.bind_group(context, input);
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Post Process Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    // Last pass of the chain, straight into the App::render pass
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, bind_group: &wgpu::BindGroup) {
+        render_pass.set_pipeline(&self.screen_pipeline);
+        render_pass.set_bind_group(0, bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -0,0 +1,272 @@
+use std::num::NonZeroU32;
+
+use eframe::wgpu;
+
+use crate::context::{Context, ContextError};
+
+// Bind group layout entries read from the @group/@binding declarations of a WGSL shader, with
+// the visibility of the stages whose entry points use each binding. What the source can't tell
+// has a default that entry_mut overrides before build:
+// - buffers have no dynamic offset and no minimum binding size
+// - float textures are filterable, samplers are filtering (comparison for sampler_comparison)
+// - a binding no entry point uses is visible to every stage of the shader
+pub struct ShaderLayout {
+    groups: Vec<Vec<wgpu::BindGroupLayoutEntry>>,
+}
+
+impl ShaderLayout {
+    pub fn from_wgsl(source: &str) -> Result<Self, ContextError> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|err| ContextError::Reflection(err.emit_to_string(source)))?;
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|err| ContextError::Reflection(err.emit_to_string(source)))?;
+
+        let all_stages = module
+            .entry_points
+            .iter()
+            .fold(wgpu::ShaderStages::NONE, |stages, entry_point| {
+                stages | shader_stage(entry_point.stage)
+            });
+
+        let mut groups: Vec<Vec<wgpu::BindGroupLayoutEntry>> = Vec::new();
+        for (handle, variable) in module.global_variables.iter() {
+            let Some(binding) = &variable.binding else {
+                continue;
+            };
+            let name = variable.name.as_deref().unwrap_or("unnamed");
+
+            let mut visibility = wgpu::ShaderStages::NONE;
+            for (index, entry_point) in module.entry_points.iter().enumerate() {
+                if !info.get_entry_point(index)[handle].is_empty() {
+                    visibility |= shader_stage(entry_point.stage);
+                }
+            }
+            if visibility.is_empty() {
+                visibility = all_stages;
+            }
+
+            let (ty, count) = match module.types[variable.ty].inner {
+                naga::TypeInner::BindingArray { base, size } => match size {
+                    naga::ArraySize::Constant(size) => (base, NonZeroU32::new(size.get())),
+                    naga::ArraySize::Dynamic => {
+                        return Err(ContextError::Reflection(format!(
+                            "{name} is a binding array without a size"
+                        )))
+                    }
+                },
+                _ => (variable.ty, None),
+            };
+            let ty = binding_type(name, variable.space, &module.types[ty].inner)?;
+
+            let group = binding.group as usize;
+            if groups.len() <= group {
+                groups.resize_with(group + 1, Vec::new);
+            }
+            groups[group].push(wgpu::BindGroupLayoutEntry {
+                binding: binding.binding,
+                visibility,
+                ty,
+                count,
+            });
+        }
+        for entries in &mut groups {
+            entries.sort_by_key(|entry| entry.binding);
+        }
+        Ok(Self { groups })
+    }
+
+    // Including the empty groups below the highest one used
+    pub fn group_count(&self) -> usize {
+        self.groups.len()
+    }
+
+    // Empty past group_count
+    pub fn entries(&self, group: u32) -> &[wgpu::BindGroupLayoutEntry] {
+        self.groups.get(group as usize).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn entry_mut(
+        &mut self,
+        group: u32,
+        binding: u32,
+    ) -> Option<&mut wgpu::BindGroupLayoutEntry> {
+        self.groups
+            .get_mut(group as usize)?
+            .iter_mut()
+            .find(|entry| entry.binding == binding)
+    }
+
+    // Shorthand for the common override, does nothing unless the binding is a buffer
+    pub fn dynamic_offset(&mut self, group: u32, binding: u32) -> &mut Self {
+        if let Some(wgpu::BindGroupLayoutEntry {
+            ty: wgpu::BindingType::Buffer {
+                has_dynamic_offset, ..
+            },
+            ..
+        }) = self.entry_mut(group, binding)
+        {
+            *has_dynamic_offset = true;
+        }
+        self
+    }
+
+    // One layout per group, in order, for RenderPipelineBuilder::bind_group_layout and the
+    // bind groups of the pipeline
+    pub fn build(&self, context: &Context) -> Vec<wgpu::BindGroupLayout> {
+        self.build_on(context.device())
+    }
+
+    // For the pipeline builders, which can run on PipelineCache's threads
+    pub(crate) fn build_on(&self, device: &wgpu::Device) -> Vec<wgpu::BindGroupLayout> {
+        self.groups
+            .iter()
+            .map(|entries| {
+                device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Reflected Bind Group Layout"),
+                    entries,
+                })
+            })
+            .collect()
+    }
+}
+
+fn shader_stage(stage: naga::ShaderStage) -> wgpu::ShaderStages {
+    match stage {
+        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+    }
+}
+
+fn binding_type(
+    name: &str,
+    space: naga::AddressSpace,
+    inner: &naga::TypeInner,
+) -> Result<wgpu::BindingType, ContextError> {
+    let ty = match (space, inner) {
+        (naga::AddressSpace::Uniform, _) => wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        (naga::AddressSpace::Storage { access }, _) => wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage {
+                read_only: !access.contains(naga::StorageAccess::STORE),
+            },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        (naga::AddressSpace::Handle, naga::TypeInner::Sampler { comparison }) => {
+            wgpu::BindingType::Sampler(if *comparison {
+                wgpu::SamplerBindingType::Comparison
+            } else {
+                wgpu::SamplerBindingType::Filtering
+            })
+        }
+        (
+            naga::AddressSpace::Handle,
+            naga::TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            },
+        ) => {
+            let view_dimension = view_dimension(*dim, *arrayed);
+            match *class {
+                naga::ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+                    sample_type: match kind {
+                        naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                        naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                        _ => wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    view_dimension,
+                    multisampled: multi,
+                },
+                naga::ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension,
+                    multisampled: multi,
+                },
+                naga::ImageClass::Storage { format, access } => wgpu::BindingType::StorageTexture {
+                    access: if !access.contains(naga::StorageAccess::LOAD) {
+                        wgpu::StorageTextureAccess::WriteOnly
+                    } else if !access.contains(naga::StorageAccess::STORE) {
+                        wgpu::StorageTextureAccess::ReadOnly
+                    } else {
+                        wgpu::StorageTextureAccess::ReadWrite
+                    },
+                    format: storage_format(format),
+                    view_dimension,
+                },
+            }
+        }
+        _ => {
+            return Err(ContextError::Reflection(format!(
+                "{name} has a binding type that can't be reflected"
+            )))
+        }
+    };
+    Ok(ty)
+}
+
+fn view_dimension(dim: naga::ImageDimension, arrayed: bool) -> wgpu::TextureViewDimension {
+    match (dim, arrayed) {
+        (naga::ImageDimension::D1, _) => wgpu::TextureViewDimension::D1,
+        (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+        (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+        (naga::ImageDimension::D3, _) => wgpu::TextureViewDimension::D3,
+        (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+        (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+    }
+}
+
+fn storage_format(format: naga::StorageFormat) -> wgpu::TextureFormat {
+    use naga::StorageFormat as S;
+    use wgpu::TextureFormat as T;
+    match format {
+        S::R8Unorm => T::R8Unorm,
+        S::R8Snorm => T::R8Snorm,
+        S::R8Uint => T::R8Uint,
+        S::R8Sint => T::R8Sint,
+        S::R16Uint => T::R16Uint,
+        S::R16Sint => T::R16Sint,
+        S::R16Float => T::R16Float,
+        S::Rg8Unorm => T::Rg8Unorm,
+        S::Rg8Snorm => T::Rg8Snorm,
+        S::Rg8Uint => T::Rg8Uint,
+        S::Rg8Sint => T::Rg8Sint,
+        S::R32Uint => T::R32Uint,
+        S::R32Sint => T::R32Sint,
+        S::R32Float => T::R32Float,
+        S::Rg16Uint => T::Rg16Uint,
+        S::Rg16Sint => T::Rg16Sint,
+        S::Rg16Float => T::Rg16Float,
+        S::Rgba8Unorm => T::Rgba8Unorm,
+        S::Rgba8Snorm => T::Rgba8Snorm,
+        S::Rgba8Uint => T::Rgba8Uint,
+        S::Rgba8Sint => T::Rgba8Sint,
+        S::Bgra8Unorm => T::Bgra8Unorm,
+        S::Rgb10a2Uint => T::Rgb10a2Uint,
+        S::Rgb10a2Unorm => T::Rgb10a2Unorm,
+        S::Rg11b10Float => T::Rg11b10Float,
+        S::Rg32Uint => T::Rg32Uint,
+        S::Rg32Sint => T::Rg32Sint,
+        S::Rg32Float => T::Rg32Float,
+        S::Rgba16Uint => T::Rgba16Uint,
+        S::Rgba16Sint => T::Rgba16Sint,
+        S::Rgba16Float => T::Rgba16Float,
+        S::Rgba32Uint => T::Rgba32Uint,
+        S::Rgba32Sint => T::Rgba32Sint,
+        S::Rgba32Float => T::Rgba32Float,
+        S::R16Unorm => T::R16Unorm,
+        S::R16Snorm => T::R16Snorm,
+        S::Rg16Unorm => T::Rg16Unorm,
+        S::Rg16Snorm => T::Rg16Snorm,
+        S::Rgba16Unorm => T::Rgba16Unorm,
+        S::Rgba16Snorm => T::Rgba16Snorm,
+    }
+}
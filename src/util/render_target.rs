@@ -0,0 +1,311 @@
+use eframe::wgpu;
+
+use crate::{context::Context, resources::TrackedResource};
+
+// Offscreen color textures, with an optional depth texture, that can be sampled once rendered.
+// Pipelines drawing into it need its formats and sample count, see
+// RenderPipelineBuilder::try_build_for.
+pub struct RenderTarget {
+    label: String,
+    formats: Vec<wgpu::TextureFormat>,
+    depth_format: Option<wgpu::TextureFormat>,
+    sample_count: u32,
+    width: u32,
+    height: u32,
+    colors: Vec<ColorTexture>,
+    depth_view: Option<wgpu::TextureView>,
+    _tracked: Vec<TrackedResource>,
+}
+
+impl RenderTarget {
+    pub fn new(
+        context: &Context,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        Self::with_formats(context, width, height, &[format], depth_format)
+    }
+
+    // One color attachment per format, in order: the fragment shader writes them at
+    // @location(0), @location(1) and so on
+    pub fn with_formats(
+        context: &Context,
+        width: u32,
+        height: u32,
+        formats: &[wgpu::TextureFormat],
+        depth_format: Option<wgpu::TextureFormat>,
+    ) -> Self {
+        let width = width.max(1);
+        let height = height.max(1);
+        let label = String::from("Render Target");
+        let textures = Textures::new(context, &label, width, height, formats, depth_format, 1);
+        Self {
+            label,
+            formats: formats.to_vec(),
+            depth_format,
+            sample_count: 1,
+            width,
+            height,
+            colors: textures.colors,
+            depth_view: textures.depth_view,
+            _tracked: textures.tracked,
+        }
+    }
+
+    // Above 1 the pass renders into multisampled textures and resolves into the texture views,
+    // the depth is multisampled too and never resolved. The previous textures are dropped.
+    pub fn set_sample_count(&mut self, context: &Context, sample_count: u32) -> &mut Self {
+        if sample_count != self.sample_count {
+            self.sample_count = sample_count;
+            self.recreate(context, self.width, self.height);
+        }
+        self
+    }
+
+    // Names the textures for graphics debuggers ("Shadow" gives "Shadow Texture" and "Shadow
+    // Depth Texture"), recreating them like set_sample_count
+    pub fn set_label(&mut self, context: &Context, label: &str) -> &mut Self {
+        if label != self.label {
+            self.label = String::from(label);
+            self.recreate(context, self.width, self.height);
+        }
+        self
+    }
+
+    fn recreate(&mut self, context: &Context, width: u32, height: u32) {
+        let textures = Textures::new(
+            context,
+            &self.label,
+            width,
+            height,
+            &self.formats,
+            self.depth_format,
+            self.sample_count,
+        );
+        self.width = width;
+        self.height = height;
+        self.colors = textures.colors;
+        self.depth_view = textures.depth_view;
+        self._tracked = textures.tracked;
+    }
+
+    // Recreates the textures, bind groups holding the previous views must be rebuilt
+    pub fn resize(&mut self, context: &Context, width: u32, height: u32) {
+        let width = width.max(1);
+        let height = height.max(1);
+        if width == self.width && height == self.height {
+            return;
+        }
+        self.recreate(context, width, height);
+    }
+
+    // None keeps the previous content, otherwise every color attachment is cleared to the
+    // color. Depth is always cleared.
+    pub fn begin_pass<'e>(
+        &'e self,
+        encoder: &'e mut wgpu::CommandEncoder,
+        clear: Option<wgpu::Color>,
+    ) -> wgpu::RenderPass<'e> {
+        let color_attachments: Vec<_> = self
+            .colors
+            .iter()
+            .map(|color| {
+                Some(wgpu::RenderPassColorAttachment {
+                    view: color.msaa_view.as_ref().unwrap_or(&color.view),
+                    resolve_target: color.msaa_view.as_ref().map(|_| &color.view),
+                    ops: wgpu::Operations {
+                        load: match clear {
+                            Some(color) => wgpu::LoadOp::Clear(color),
+                            None => wgpu::LoadOp::Load,
+                        },
+                        store: wgpu::StoreOp::Store,
+                    },
+                })
+            })
+            .collect();
+        encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Target Pass"),
+            color_attachments: &color_attachments,
+            depth_stencil_attachment: self.depth_view.as_ref().map(|view| {
+                wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }
+            }),
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        })
+    }
+
+    // The first color attachment
+    pub fn texture(&self) -> &wgpu::Texture {
+        self.color_texture(0)
+    }
+
+    pub fn texture_view(&self) -> &wgpu::TextureView {
+        self.color_view(0)
+    }
+
+    // Panics when index is not below color_count
+    pub fn color_texture(&self, index: usize) -> &wgpu::Texture {
+        &self.colors[index].texture
+    }
+
+    pub fn color_view(&self, index: usize) -> &wgpu::TextureView {
+        &self.colors[index].view
+    }
+
+    pub fn color_count(&self) -> usize {
+        self.colors.len()
+    }
+
+    pub fn depth_view(&self) -> Option<&wgpu::TextureView> {
+        self.depth_view.as_ref()
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.formats[0]
+    }
+
+    pub fn formats(&self) -> &[wgpu::TextureFormat] {
+        &self.formats
+    }
+
+    pub fn depth_format(&self) -> Option<wgpu::TextureFormat> {
+        self.depth_format
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+}
+
+struct ColorTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    // Rendered into and resolved to view when multisampled
+    msaa_view: Option<wgpu::TextureView>,
+}
+
+struct Textures {
+    colors: Vec<ColorTexture>,
+    depth_view: Option<wgpu::TextureView>,
+    tracked: Vec<TrackedResource>,
+}
+
+impl Textures {
+    fn new(
+        context: &Context,
+        label: &str,
+        width: u32,
+        height: u32,
+        formats: &[wgpu::TextureFormat],
+        depth_format: Option<wgpu::TextureFormat>,
+        sample_count: u32,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        let mut tracked = Vec::new();
+        let mut track = |texture: &wgpu::Texture, suffix: &str| {
+            tracked.push(
+                context
+                    .resources()
+                    .track_texture(texture, &format!("{label} {suffix}")),
+            );
+        };
+        let colors = formats
+            .iter()
+            .map(|&format| {
+                let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+                    label: Some(&format!("{label} Texture")),
+                    size,
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING
+                        | wgpu::TextureUsages::COPY_SRC,
+                    view_formats: &[],
+                });
+                track(&texture, "Texture");
+                let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some(&format!("{label} View")),
+                    ..Default::default()
+                });
+
+                let msaa_view = (sample_count > 1).then(|| {
+                    let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+                        label: Some(&format!("{label} Multisampled Texture")),
+                        size,
+                        mip_level_count: 1,
+                        sample_count,
+                        dimension: wgpu::TextureDimension::D2,
+                        format,
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                        view_formats: &[],
+                    });
+                    track(&texture, "Multisampled Texture");
+                    texture.create_view(&wgpu::TextureViewDescriptor {
+                        label: Some(&format!("{label} Multisampled View")),
+                        ..Default::default()
+                    })
+                });
+
+                ColorTexture {
+                    texture,
+                    view,
+                    msaa_view,
+                }
+            })
+            .collect();
+
+        // Sampleable too when single sampled, so depth can be read by a later pass. Multisampled,
+        // the GL backend fails to resolve the color next to a sampleable depth texture.
+        let depth_usage = if sample_count > 1 {
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+        } else {
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING
+        };
+        let depth_view = depth_format.map(|format| {
+            let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+                label: Some(&format!("{label} Depth Texture")),
+                size,
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage: depth_usage,
+                view_formats: &[],
+            });
+            track(&texture, "Depth Texture");
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some(&format!("{label} Depth View")),
+                ..Default::default()
+            })
+        });
+
+        Self {
+            colors,
+            depth_view,
+            tracked,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use eframe::wgpu;
+
+use crate::context::{Context, ContextError};
+
+pub struct SamplerBuilder<'a> {
+    label: Option<&'a str>,
+    mag_filter: wgpu::FilterMode,
+    min_filter: wgpu::FilterMode,
+    mipmap_filter: wgpu::FilterMode,
+    address_mode: wgpu::AddressMode,
+    anisotropy: u16,
+    compare: Option<wgpu::CompareFunction>,
+}
+
+impl<'a> SamplerBuilder<'a> {
+    fn preset(filter: wgpu::FilterMode, address_mode: wgpu::AddressMode) -> Self {
+        Self {
+            label: None,
+            mag_filter: filter,
+            min_filter: filter,
+            mipmap_filter: filter,
+            address_mode,
+            anisotropy: 1,
+            compare: None,
+        }
+    }
+
+    pub fn linear_repeat() -> Self {
+        Self::preset(wgpu::FilterMode::Linear, wgpu::AddressMode::Repeat)
+    }
+
+    pub fn linear_clamp() -> Self {
+        Self::preset(wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge)
+    }
+
+    pub fn nearest_repeat() -> Self {
+        Self::preset(wgpu::FilterMode::Nearest, wgpu::AddressMode::Repeat)
+    }
+
+    pub fn nearest_clamp() -> Self {
+        Self::preset(wgpu::FilterMode::Nearest, wgpu::AddressMode::ClampToEdge)
+    }
+
+    // Comparison sampler for shadow maps: textureSampleCompare returns the fraction of the 2x2
+    // nearest texels that pass LessEqual, outside the map counts as lit
+    pub fn shadow() -> Self {
+        let mut builder = Self::preset(wgpu::FilterMode::Linear, wgpu::AddressMode::ClampToEdge);
+        builder.mipmap_filter = wgpu::FilterMode::Nearest;
+        builder.compare = Some(wgpu::CompareFunction::LessEqual);
+        builder
+    }
+
+    pub fn label(&mut self, label: &'a str) -> &mut Self {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn mag_filter(&mut self, filter: wgpu::FilterMode) -> &mut Self {
+        self.mag_filter = filter;
+        self
+    }
+
+    pub fn min_filter(&mut self, filter: wgpu::FilterMode) -> &mut Self {
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn mipmap_filter(&mut self, filter: wgpu::FilterMode) -> &mut Self {
+        self.mipmap_filter = filter;
+        self
+    }
+
+    // Same mode on the three axes
+    pub fn address_mode(&mut self, address_mode: wgpu::AddressMode) -> &mut Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    // Checked by build: from 1 to 16, and above 1 every filter must be Linear
+    pub fn anisotropy(&mut self, anisotropy: u16) -> &mut Self {
+        self.anisotropy = anisotropy;
+        self
+    }
+
+    // For depth textures sampled with textureSampleCompare
+    pub fn compare(&mut self, compare: wgpu::CompareFunction) -> &mut Self {
+        self.compare = Some(compare);
+        self
+    }
+
+    pub fn build(&self, context: &Context) -> Result<wgpu::Sampler, ContextError> {
+        if !(1..=16).contains(&self.anisotropy) {
+            return Err(ContextError::InvalidSampler(format!(
+                "anisotropy must be between 1 and 16, got {}",
+                self.anisotropy
+            )));
+        }
+        let all_linear = [self.mag_filter, self.min_filter, self.mipmap_filter]
+            .iter()
+            .all(|filter| *filter == wgpu::FilterMode::Linear);
+        if self.anisotropy > 1 && !all_linear {
+            return Err(ContextError::InvalidSampler(format!(
+                "anisotropy {} needs Linear mag, min and mipmap filters",
+                self.anisotropy
+            )));
+        }
+
+        Ok(context.device().create_sampler(&wgpu::SamplerDescriptor {
+            label: self.label,
+            address_mode_u: self.address_mode,
+            address_mode_v: self.address_mode,
+            address_mode_w: self.address_mode,
+            mag_filter: self.mag_filter,
+            min_filter: self.min_filter,
+            mipmap_filter: self.mipmap_filter,
+            anisotropy_clamp: self.anisotropy,
+            compare: self.compare,
+            ..Default::default()
+        }))
+    }
+}
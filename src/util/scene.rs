@@ -0,0 +1,389 @@
+use std::sync::OnceLock;
+
+use cgmath::{One, SquareMatrix};
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{instance_buffer::InstanceBuffer, mesh::GpuMesh},
+};
+
+// A node of a Scene, stays valid until the node is removed
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId(usize);
+
+// Applied scale first, then rotation, then translation, relative to the parent node
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: cgmath::Vector3<f32>,
+    pub rotation: cgmath::Quaternion<f32>,
+    pub scale: cgmath::Vector3<f32>,
+}
+
+impl Transform {
+    pub fn from_translation(translation: cgmath::Vector3<f32>) -> Self {
+        Self {
+            translation,
+            ..Default::default()
+        }
+    }
+
+    pub fn matrix(&self) -> cgmath::Matrix4<f32> {
+        cgmath::Matrix4::from_translation(self.translation)
+            * cgmath::Matrix4::from(self.rotation)
+            * cgmath::Matrix4::from_nonuniform_scale(self.scale.x, self.scale.y, self.scale.z)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: cgmath::vec3(0.0, 0.0, 0.0),
+            rotation: cgmath::Quaternion::one(),
+            scale: cgmath::vec3(1.0, 1.0, 1.0),
+        }
+    }
+}
+
+struct Node {
+    transform: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    mesh: Option<usize>,
+    // Cleared for the node and its descendants whenever a transform above them changes
+    world: OnceLock<cgmath::Matrix4<f32>>,
+}
+
+// A hierarchy of nodes, each with a transform relative to its parent and optionally one of the
+// scene's meshes. update uploads the world matrices of the nodes with a mesh into an instance
+// buffer, grouped by mesh, and draw issues one instanced draw per mesh: the pipeline takes the
+// matrices at vertex buffer slot 1 with TRANSFORM_ATTRIBUTES, like an InstanceBuffer of them.
+#[derive(Default)]
+pub struct Scene {
+    nodes: Vec<Option<Node>>,
+    meshes: Vec<GpuMesh>,
+    instances: Option<InstanceBuffer<[[f32; 4]; 4]>>,
+    // Instance range of each mesh in the last update
+    draws: Vec<(usize, std::ops::Range<u32>)>,
+    changed: bool,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Returns the index set_mesh takes
+    pub fn add_mesh(&mut self, mesh: GpuMesh) -> usize {
+        self.meshes.push(mesh);
+        self.meshes.len() - 1
+    }
+
+    pub fn mesh(&self, index: usize) -> &GpuMesh {
+        &self.meshes[index]
+    }
+
+    // A root node when parent is None
+    pub fn add_node(&mut self, parent: Option<NodeId>, transform: Transform) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        if let Some(parent) = parent {
+            self.node_mut(parent).children.push(id);
+        }
+        self.nodes.push(Some(Node {
+            transform,
+            parent,
+            children: Vec::new(),
+            mesh: None,
+            world: OnceLock::new(),
+        }));
+        self.changed = true;
+        id
+    }
+
+    // Removes the node along with its descendants
+    pub fn remove(&mut self, node: NodeId) {
+        if let Some(parent) = self.node(node).parent {
+            self.node_mut(parent)
+                .children
+                .retain(|&child| child != node);
+        }
+        let mut stack = vec![node];
+        while let Some(id) = stack.pop() {
+            if let Some(removed) = self.nodes[id.0].take() {
+                stack.extend(removed.children);
+            }
+        }
+        self.changed = true;
+    }
+
+    pub fn contains(&self, node: NodeId) -> bool {
+        self.nodes.get(node.0).is_some_and(Option::is_some)
+    }
+
+    pub fn set_transform(&mut self, node: NodeId, transform: Transform) -> &mut Self {
+        self.node_mut(node).transform = transform;
+        self.invalidate(node);
+        self
+    }
+
+    pub fn transform(&self, node: NodeId) -> Transform {
+        self.node(node).transform
+    }
+
+    // An index from add_mesh, or None for a node that only groups its children
+    pub fn set_mesh(&mut self, node: NodeId, mesh: Option<usize>) -> &mut Self {
+        if let Some(mesh) = mesh {
+            assert!(mesh < self.meshes.len(), "no mesh {mesh} in the scene");
+        }
+        self.node_mut(node).mesh = mesh;
+        self.changed = true;
+        self
+    }
+
+    pub fn node_mesh(&self, node: NodeId) -> Option<usize> {
+        self.node(node).mesh
+    }
+
+    // Keeps the node's local transform, so it moves along with its new parent. Fails when the
+    // parent is the node itself or one of its descendants.
+    pub fn set_parent(&mut self, node: NodeId, parent: Option<NodeId>) -> Result<(), ContextError> {
+        let mut ancestor = parent;
+        while let Some(id) = ancestor {
+            if id == node {
+                return Err(ContextError::InvalidHierarchy(format!(
+                    "{parent:?} can't be the parent of {node:?}, it's in its subtree"
+                )));
+            }
+            ancestor = self.node(id).parent;
+        }
+        if let Some(old) = self.node(node).parent {
+            self.node_mut(old).children.retain(|&child| child != node);
+        }
+        if let Some(parent) = parent {
+            self.node_mut(parent).children.push(node);
+        }
+        self.node_mut(node).parent = parent;
+        self.invalidate(node);
+        Ok(())
+    }
+
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.node(node).parent
+    }
+
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        &self.node(node).children
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.ids().filter(|&id| self.node(id).parent.is_none())
+    }
+
+    // The local transforms from the root down to the node, composed. Cached until a transform
+    // or parent along the way changes.
+    pub fn world_transform(&self, node: NodeId) -> cgmath::Matrix4<f32> {
+        // Walk up to the first cached ancestor, then compose back down, without recursing
+        let mut chain = Vec::new();
+        let mut current = Some(node);
+        let mut world = cgmath::Matrix4::identity();
+        while let Some(id) = current {
+            let node = self.node(id);
+            if let Some(&cached) = node.world.get() {
+                world = cached;
+                break;
+            }
+            chain.push(id);
+            current = node.parent;
+        }
+        for id in chain.into_iter().rev() {
+            let node = self.node(id);
+            world = world * node.transform.matrix();
+            // Already set when another thread got there first, with the same matrix
+            let _ = node.world.set(world);
+        }
+        world
+    }
+
+    // Uploads the world matrices of the nodes with a mesh, from App::prepare. Does nothing when no
+    // node, transform or mesh changed since the last call.
+    pub fn update(&mut self, context: &Context) {
+        if !self.changed {
+            return;
+        }
+        self.changed = false;
+
+        let mut by_mesh: Vec<Vec<[[f32; 4]; 4]>> = vec![Vec::new(); self.meshes.len()];
+        for id in self.ids() {
+            if let Some(mesh) = self.node(id).mesh {
+                by_mesh[mesh].push(self.world_transform(id).into());
+            }
+        }
+        self.draws.clear();
+        let mut transforms = Vec::new();
+        for (mesh, matrices) in by_mesh.into_iter().enumerate() {
+            if matrices.is_empty() {
+                continue;
+            }
+            let start = transforms.len() as u32;
+            transforms.extend(matrices);
+            self.draws.push((mesh, start..transforms.len() as u32));
+        }
+
+        let fits = self
+            .instances
+            .as_ref()
+            .is_some_and(|instances| instances.capacity() >= transforms.len());
+        if !fits {
+            self.instances = Some(InstanceBuffer::with_capacity(
+                context,
+                transforms.len().next_power_of_two(),
+            ));
+        }
+        if let Some(instances) = &mut self.instances {
+            instances.update(context.queue(), &transforms);
+        }
+    }
+
+    // Expects the pipeline set, with the camera's layout at group 0 (CameraUniform::desc), the
+    // mesh vertices at slot 0 and the model matrices at slot 1
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, camera: &wgpu::BindGroup) {
+        let Some(instances) = &self.instances else {
+            return;
+        };
+        render_pass.set_bind_group(0, camera, &[]);
+        render_pass.set_vertex_buffer(1, instances.buffer().slice(..));
+        for (mesh, range) in &self.draws {
+            self.meshes[*mesh].draw_instanced(render_pass, range.clone());
+        }
+    }
+
+    fn ids(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.is_some())
+            .map(|(index, _)| NodeId(index))
+    }
+
+    fn node(&self, id: NodeId) -> &Node {
+        self.nodes[id.0]
+            .as_ref()
+            .unwrap_or_else(|| panic!("{id:?} was removed from the scene"))
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node {
+        self.nodes[id.0]
+            .as_mut()
+            .unwrap_or_else(|| panic!("{id:?} was removed from the scene"))
+    }
+
+    fn invalidate(&mut self, node: NodeId) {
+        let mut stack = vec![node];
+        while let Some(id) = stack.pop() {
+            let node = self.node_mut(id);
+            node.world.take();
+            stack.extend_from_slice(&node.children);
+        }
+        self.changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Sums of small integers, exact in f32
+    fn translation(scene: &Scene, node: NodeId) -> cgmath::Vector3<f32> {
+        scene.world_transform(node).w.truncate()
+    }
+
+    // root > middle > leaf, with a scale on the root
+    fn chain() -> (Scene, [NodeId; 3]) {
+        let mut scene = Scene::new();
+        let root = scene.add_node(
+            None,
+            Transform {
+                translation: cgmath::vec3(1.0, 0.0, 0.0),
+                scale: cgmath::vec3(2.0, 2.0, 2.0),
+                ..Default::default()
+            },
+        );
+        let middle = scene.add_node(
+            Some(root),
+            Transform::from_translation(cgmath::vec3(0.0, 1.0, 0.0)),
+        );
+        let leaf = scene.add_node(
+            Some(middle),
+            Transform::from_translation(cgmath::vec3(0.0, 0.0, 1.0)),
+        );
+        (scene, [root, middle, leaf])
+    }
+
+    #[test]
+    fn world_transform_of_a_chain() {
+        let (scene, [root, middle, leaf]) = chain();
+        assert_eq!(translation(&scene, root), cgmath::vec3(1.0, 0.0, 0.0));
+        assert_eq!(translation(&scene, middle), cgmath::vec3(1.0, 2.0, 0.0));
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(1.0, 2.0, 2.0));
+        // Same again from the cache
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(1.0, 2.0, 2.0));
+        assert_eq!(
+            scene.world_transform(leaf).x.truncate(),
+            cgmath::vec3(2.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn set_transform_invalidates_descendants() {
+        let (mut scene, [root, middle, leaf]) = chain();
+        translation(&scene, leaf);
+        scene.set_transform(
+            root,
+            Transform::from_translation(cgmath::vec3(5.0, 0.0, 0.0)),
+        );
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(5.0, 1.0, 1.0));
+
+        scene.set_transform(middle, Transform::default());
+        assert_eq!(translation(&scene, middle), cgmath::vec3(5.0, 0.0, 0.0));
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(5.0, 0.0, 1.0));
+        assert_eq!(translation(&scene, root), cgmath::vec3(5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn set_parent_invalidates_descendants() {
+        let (mut scene, [root, middle, leaf]) = chain();
+        let other = scene.add_node(
+            None,
+            Transform::from_translation(cgmath::vec3(0.0, 0.0, 10.0)),
+        );
+        translation(&scene, leaf);
+
+        scene.set_parent(middle, Some(other)).unwrap();
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(0.0, 1.0, 11.0));
+        assert_eq!(scene.children(root), &[]);
+        assert_eq!(scene.children(other), &[middle]);
+
+        scene.set_parent(middle, None).unwrap();
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(0.0, 1.0, 1.0));
+        assert_eq!(scene.roots().count(), 3);
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let (mut scene, [root, middle, leaf]) = chain();
+        for (node, parent) in [(root, leaf), (root, middle), (middle, middle)] {
+            assert!(
+                matches!(
+                    scene.set_parent(node, Some(parent)),
+                    Err(ContextError::InvalidHierarchy(_))
+                ),
+                "{parent:?} was accepted as the parent of {node:?}"
+            );
+        }
+        // Left as it was
+        assert_eq!(scene.parent(root), None);
+        assert_eq!(scene.parent(middle), Some(root));
+        assert_eq!(scene.children(middle), &[leaf]);
+        assert_eq!(translation(&scene, leaf), cgmath::vec3(1.0, 2.0, 2.0));
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+use eframe::wgpu;
+use notify::Watcher;
+
+use crate::{
+    assets,
+    context::{Context, ContextError},
+};
+
+pub struct ShaderWatcher {
+    path: PathBuf,
+    module: wgpu::ShaderModule,
+    // Kept alive for the events, dropping it stops the watch
+    _watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+}
+
+impl ShaderWatcher {
+    pub fn new(context: &Context, path: impl AsRef<Path>) -> Result<Self, ContextError> {
+        let path = path.as_ref().to_path_buf();
+        let module = compile(context, &path)?;
+
+        let (sender, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender)
+            .map_err(|err| ContextError::Other(err.to_string()))?;
+        // Editors often save by replacing the file, which would end a watch on the file itself
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        watcher
+            .watch(dir, notify::RecursiveMode::NonRecursive)
+            .map_err(|err| ContextError::Other(err.to_string()))?;
+
+        Ok(Self {
+            path,
+            module,
+            _watcher: watcher,
+            events,
+        })
+    }
+
+    // The last module that compiled
+    pub fn module(&self) -> &wgpu::ShaderModule {
+        &self.module
+    }
+
+    // Some with the new module when the file changed and compiled since the last call, the
+    // pipelines using the shader have to be rebuilt with it
+    pub fn take_reloaded(&mut self, context: &Context) -> Option<&wgpu::ShaderModule> {
+        let file_name = self.path.file_name();
+        let changed = self.events.try_iter().any(|event| match event {
+            Ok(event) => {
+                !event.kind.is_access()
+                    && event.paths.iter().any(|path| path.file_name() == file_name)
+            }
+            Err(err) => {
+                log::warn!("shader watch error: {err}");
+                false
+            }
+        });
+        if !changed {
+            return None;
+        }
+
+        match compile(context, &self.path) {
+            Ok(module) => {
+                log::info!("reloaded {}", self.path.display());
+                self.module = module;
+                Some(&self.module)
+            }
+            Err(err) => {
+                log::error!("{err}, keeping the previous shader");
+                None
+            }
+        }
+    }
+}
+
+fn compile(context: &Context, path: &Path) -> Result<wgpu::ShaderModule, ContextError> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|err| ContextError::Other(format!("failed to read {}: {err}", path.display())))?;
+    assets::shader_from_source(context, &path.display().to_string(), &source)
+}
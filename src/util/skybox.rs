@@ -0,0 +1,88 @@
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{orbit_camera::CameraUniform, pipeline::RenderPipelineBuilder, texture::Texture},
+};
+
+// Draws a cubemap around the camera at the far plane. The view matrix loses its translation in
+// the shader and the depth test is LessEqual without writes, so draw it after the opaque
+// geometry to only shade the pixels nothing else covered. Targets the main render pass.
+pub struct Skybox {
+    pipeline: wgpu::RenderPipeline,
+    layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Skybox {
+    // cubemap comes from Texture::cubemap_from_paths or cubemap_from_rgba
+    pub fn new(context: &Context, cubemap: &Texture) -> Result<Self, ContextError> {
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Skybox Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("skybox.wgsl").into()),
+            });
+        let camera_layout = context
+            .device()
+            .create_bind_group_layout(&CameraUniform::desc());
+        let layout = context
+            .device()
+            .create_bind_group_layout(&Texture::desc_cube());
+        let pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Skybox Pipeline")
+            .bind_group_layout(&camera_layout)
+            .bind_group_layout(&layout)
+            .cull_mode(None)
+            .depth_write(false)
+            .depth_compare(wgpu::CompareFunction::LessEqual)
+            .try_build(context)?;
+        let bind_group = cubemap_bind_group(context, &layout, cubemap);
+
+        Ok(Self {
+            pipeline,
+            layout,
+            bind_group,
+        })
+    }
+
+    pub fn set_cubemap(&mut self, context: &Context, cubemap: &Texture) -> &mut Self {
+        self.bind_group = cubemap_bind_group(context, &self.layout, cubemap);
+        self
+    }
+
+    // camera_bind_group is a CameraUniform one, from Camera or OrbitCamera
+    pub fn draw(
+        &self,
+        render_pass: &mut wgpu::RenderPass<'_>,
+        camera_bind_group: &wgpu::BindGroup,
+    ) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, camera_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.bind_group, &[]);
+        render_pass.draw(0..36, 0..1);
+    }
+}
+
+fn cubemap_bind_group(
+    context: &Context,
+    layout: &wgpu::BindGroupLayout,
+    cubemap: &Texture,
+) -> wgpu::BindGroup {
+    context
+        .device()
+        .create_bind_group(&wgpu::BindGroupDescriptor {
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(cubemap.view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(cubemap.sampler()),
+                },
+            ],
+            label: Some("Skybox Bind Group"),
+        })
+}
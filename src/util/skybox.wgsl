@@ -0,0 +1,58 @@
+struct CameraUniform {
+    view: mat4x4<f32>,
+    proj: mat4x4<f32>,
+};
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var cubemap: texture_cube<f32>;
+@group(1) @binding(1) var cubemap_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) direction: vec3<f32>,
+};
+
+// The 36 vertices of a unit cube, seen from the inside so it isn't culled
+const CORNERS = array<vec3<f32>, 8>(
+    vec3<f32>(-1.0, -1.0, -1.0),
+    vec3<f32>(1.0, -1.0, -1.0),
+    vec3<f32>(1.0, 1.0, -1.0),
+    vec3<f32>(-1.0, 1.0, -1.0),
+    vec3<f32>(-1.0, -1.0, 1.0),
+    vec3<f32>(1.0, -1.0, 1.0),
+    vec3<f32>(1.0, 1.0, 1.0),
+    vec3<f32>(-1.0, 1.0, 1.0),
+);
+const INDICES = array<u32, 36>(
+    0u, 1u, 2u, 0u, 2u, 3u,
+    5u, 4u, 7u, 5u, 7u, 6u,
+    4u, 0u, 3u, 4u, 3u, 7u,
+    1u, 5u, 6u, 1u, 6u, 2u,
+    3u, 2u, 6u, 3u, 6u, 7u,
+    4u, 5u, 1u, 4u, 1u, 0u,
+);
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    var corners = CORNERS;
+    var indices = INDICES;
+    let position = corners[indices[index]];
+    // Rotation only, the sky stays around the camera wherever it moves
+    let view = mat4x4<f32>(
+        vec4<f32>(camera.view[0].xyz, 0.0),
+        vec4<f32>(camera.view[1].xyz, 0.0),
+        vec4<f32>(camera.view[2].xyz, 0.0),
+        vec4<f32>(0.0, 0.0, 0.0, 1.0),
+    );
+    let clip_position = camera.proj * view * vec4<f32>(position, 1.0);
+    var out: VertexOutput;
+    // z = w puts it at depth 1, behind everything else
+    out.clip_position = clip_position.xyww;
+    out.direction = position;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(cubemap, cubemap_sampler, in.direction);
+}
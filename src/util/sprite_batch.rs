@@ -0,0 +1,321 @@
+use std::ops::Range;
+
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{
+        dynamic_buffer::DynamicBuffer,
+        ortho_camera::{pixel_projection, OrthoCamera},
+        pipeline::RenderPipelineBuilder,
+        texture::Texture,
+        uniform_buffer::UniformBuffer,
+    },
+    Vertex,
+};
+
+pub use crate::util::ortho_camera::Origin;
+
+// In quads, before the buffer first grows
+const INITIAL_CAPACITY: usize = 256;
+
+// Returned by SpriteBatch::add_texture
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SpriteTexture(usize);
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct SpriteInstance {
+    // Corner and size in pixels
+    rect: [f32; 4],
+    // Corner and size in texture coordinates
+    uv_rect: [f32; 4],
+    color: [f32; 4],
+    radius: f32,
+}
+
+// Matches Params in sprite_batch.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SpriteParams {
+    view_proj: [[f32; 4]; 4],
+}
+
+// Screen-space quads queued during the frame (rect, circle, sprite...), sent with flush and
+// drawn with draw in App::render. Coordinates are in physical pixels, or in world units through
+// set_camera. Edges are anti-aliased over a unit and colors are alpha blended over what is
+// already drawn, without depth test.
+//
+// Consecutive quads on the same texture share a draw call, colored shapes use a white texture
+// of their own. With set_sort_by_texture the quads are grouped by texture first, for the fewest
+// draw calls when the order they overlap in doesn't matter.
+pub struct SpriteBatch {
+    sprites: Vec<(SpriteTexture, SpriteInstance)>,
+    buffer: DynamicBuffer<SpriteInstance>,
+    // Instance ranges of the last flush, by texture
+    batches: Vec<(SpriteTexture, Range<u32>)>,
+    // Bind group and size in texels
+    textures: Vec<(wgpu::BindGroup, [f32; 2])>,
+    texture_layout: wgpu::BindGroupLayout,
+    origin: Origin,
+    // View-projection of the OrthoCamera given to set_camera
+    camera: Option<[[f32; 4]; 4]>,
+    params_buffer: UniformBuffer<SpriteParams>,
+    params_bind_group: wgpu::BindGroup,
+    sort_by_texture: bool,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl SpriteBatch {
+    // Origin::TopLeft, for the context format, depth format and sample count
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let params = SpriteParams {
+            view_proj: pixel_projection(context.size().x, context.size().y, Origin::TopLeft).into(),
+        };
+        let params_buffer = UniformBuffer::new(context, params)?;
+        let params_layout =
+            context
+                .device()
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: Some("Sprite Batch Params Bind Group Layout"),
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }],
+                });
+        let params_bind_group = context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Sprite Batch Params Bind Group"),
+                layout: &params_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.binding_resource(),
+                }],
+            });
+        let texture_layout = context.device().create_bind_group_layout(&Texture::desc());
+
+        let shader = context
+            .device()
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some("Sprite Batch Shader"),
+                source: wgpu::ShaderSource::Wgsl(include_str!("sprite_batch.wgsl").into()),
+            });
+        let pipeline = RenderPipelineBuilder::new(&shader)
+            .label("Sprite Batch Pipeline")
+            .bind_group_layout(&params_layout)
+            .bind_group_layout(&texture_layout)
+            .vertex_layout(SpriteInstance::desc())
+            .primitive_topology(wgpu::PrimitiveTopology::TriangleStrip)
+            .blend_alpha()
+            .cull_mode(None)
+            .depth_write(false)
+            .depth_compare(wgpu::CompareFunction::Always)
+            .build(context);
+
+        let white = Texture::from_rgba(context, 1, 1, &[255; 4], "Sprite Batch White Texture")?;
+
+        let mut sprite_batch = Self {
+            sprites: Vec::new(),
+            buffer: DynamicBuffer::new(
+                context,
+                "Sprite Batch Instance Buffer",
+                wgpu::BufferUsages::VERTEX,
+                INITIAL_CAPACITY,
+            ),
+            batches: Vec::new(),
+            textures: Vec::new(),
+            texture_layout,
+            origin: Origin::TopLeft,
+            camera: None,
+            params_buffer,
+            params_bind_group,
+            sort_by_texture: false,
+            pipeline,
+        };
+        sprite_batch.add_texture(context, &white);
+        Ok(sprite_batch)
+    }
+
+    pub fn origin(&self) -> Origin {
+        self.origin
+    }
+
+    // Applies from the next flush, unless a camera is set
+    pub fn set_origin(&mut self, origin: Origin) -> &mut Self {
+        self.origin = origin;
+        self
+    }
+
+    // Draws in the camera's world units from the next flush, pass it again when it moves.
+    // None goes back to pixels from the origin.
+    pub fn set_camera(&mut self, camera: Option<&OrthoCamera>) -> &mut Self {
+        self.camera = camera.map(OrthoCamera::view_proj_matrix);
+        self
+    }
+
+    pub fn set_sort_by_texture(&mut self, sort_by_texture: bool) -> &mut Self {
+        self.sort_by_texture = sort_by_texture;
+        self
+    }
+
+    // The batch keeps its own bind group on the texture, regions are given in its texels
+    pub fn add_texture(&mut self, context: &Context, texture: &Texture) -> SpriteTexture {
+        let bind_group = context
+            .device()
+            .create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Sprite Batch Texture Bind Group"),
+                layout: &self.texture_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(texture.view()),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::Sampler(texture.sampler()),
+                    },
+                ],
+            });
+        let size = texture.texture().size();
+        self.textures
+            .push((bind_group, [size.width as f32, size.height as f32]));
+        SpriteTexture(self.textures.len() - 1)
+    }
+
+    pub fn rect(&mut self, position: [f32; 2], size: [f32; 2], color: [f32; 4]) -> &mut Self {
+        self.rounded_rect(position, size, 0.0, color)
+    }
+
+    // The radius is capped at half the smallest side
+    pub fn rounded_rect(
+        &mut self,
+        position: [f32; 2],
+        size: [f32; 2],
+        radius: f32,
+        color: [f32; 4],
+    ) -> &mut Self {
+        self.push(
+            SpriteTexture(0),
+            position,
+            size,
+            [0.0, 0.0, 1.0, 1.0],
+            radius,
+            color,
+        )
+    }
+
+    pub fn circle(&mut self, center: [f32; 2], radius: f32, color: [f32; 4]) -> &mut Self {
+        self.rounded_rect(
+            [center[0] - radius, center[1] - radius],
+            [2.0 * radius, 2.0 * radius],
+            radius,
+            color,
+        )
+    }
+
+    // The whole texture, multiplied by color
+    pub fn sprite(
+        &mut self,
+        texture: SpriteTexture,
+        position: [f32; 2],
+        size: [f32; 2],
+        color: [f32; 4],
+    ) -> &mut Self {
+        self.push(texture, position, size, [0.0, 0.0, 1.0, 1.0], 0.0, color)
+    }
+
+    // Part of the texture (an atlas cell, a glyph), region is its top-left corner and size in
+    // texels
+    pub fn sprite_region(
+        &mut self,
+        texture: SpriteTexture,
+        region: [f32; 4],
+        position: [f32; 2],
+        size: [f32; 2],
+        color: [f32; 4],
+    ) -> &mut Self {
+        let [width, height] = self.textures[texture.0].1;
+        let uv_rect = [
+            region[0] / width,
+            region[1] / height,
+            region[2] / width,
+            region[3] / height,
+        ];
+        self.push(texture, position, size, uv_rect, 0.0, color)
+    }
+
+    fn push(
+        &mut self,
+        texture: SpriteTexture,
+        position: [f32; 2],
+        size: [f32; 2],
+        uv_rect: [f32; 4],
+        radius: f32,
+        color: [f32; 4],
+    ) -> &mut Self {
+        self.sprites.push((
+            texture,
+            SpriteInstance {
+                rect: [position[0], position[1], size[0], size[1]],
+                uv_rect,
+                color,
+                radius,
+            },
+        ));
+        self
+    }
+
+    // Sends the queued quads for the next draw and starts a new batch
+    pub fn flush(&mut self, context: &Context) {
+        if self.sort_by_texture {
+            // Stable, quads on the same texture keep their order
+            self.sprites.sort_by_key(|(texture, _)| texture.0);
+        }
+        self.batches.clear();
+        for (i, (texture, _)) in self.sprites.iter().enumerate() {
+            let i = i as u32;
+            match self.batches.last_mut() {
+                Some((last, range)) if last == texture => range.end = i + 1,
+                _ => self.batches.push((*texture, i..i + 1)),
+            }
+        }
+        let instances: Vec<SpriteInstance> = self
+            .sprites
+            .drain(..)
+            .map(|(_, instance)| instance)
+            .collect();
+        self.buffer.write(context, &instances);
+
+        let view_proj = self.camera.unwrap_or_else(|| {
+            pixel_projection(context.size().x, context.size().y, self.origin).into()
+        });
+        self.params_buffer
+            .update(context.queue(), SpriteParams { view_proj });
+    }
+
+    // Draw calls of the last flush
+    pub fn batch_count(&self) -> usize {
+        self.batches.len()
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if self.buffer.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.params_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.buffer.buffer().slice(..));
+        for (texture, range) in &self.batches {
+            render_pass.set_bind_group(1, &self.textures[texture.0].0, &[]);
+            render_pass.draw(0..4, range.clone());
+        }
+    }
+}
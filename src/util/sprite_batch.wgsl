@@ -0,0 +1,73 @@
+struct Params {
+    // Pixels or world units to clip space
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(1) @binding(0)
+var sprite_texture: texture_2d<f32>;
+@group(1) @binding(1)
+var sprite_sampler: sampler;
+
+struct InstanceInput {
+    @location(0) rect: vec4<f32>,
+    @location(1) uv_rect: vec4<f32>,
+    @location(2) color: vec4<f32>,
+    @location(3) radius: f32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // 0 to 1 across the rect, past it in the margin
+    @location(0) uv_corner: vec2<f32>,
+    @location(1) color: vec4<f32>,
+    // Pixels from the center of the quad
+    @location(2) local: vec2<f32>,
+    @location(3) @interpolate(flat) half_size: vec2<f32>,
+    @location(4) @interpolate(flat) radius: f32,
+    @location(5) @interpolate(flat) uv_rect: vec4<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32, instance: InstanceInput) -> VertexOutput {
+    // Triangle strip corners, (0, 0) is the first corner of the rect
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let half_size = instance.rect.zw * 0.5;
+    // One pixel of margin around the quad for the edge fade
+    let local = (corner * 2.0 - 1.0) * (half_size + 1.0);
+    let position = instance.rect.xy + half_size + local;
+
+    // The texture's v goes down the screen either way, flipped when y goes up
+    var uv_corner = local / max(half_size, vec2<f32>(1e-6)) * 0.5 + 0.5;
+    if params.view_proj[1][1] > 0.0 {
+        uv_corner.y = 1.0 - uv_corner.y;
+    }
+
+    var out: VertexOutput;
+    out.clip_position = params.view_proj * vec4<f32>(position, 0.0, 1.0);
+    out.uv_corner = uv_corner;
+    out.uv_rect = instance.uv_rect;
+    out.color = instance.color;
+    out.local = local;
+    out.half_size = half_size;
+    out.radius = instance.radius;
+    return out;
+}
+
+fn rounded_box(p: vec2<f32>, half_size: vec2<f32>, radius: f32) -> f32 {
+    let q = abs(p) - half_size + radius;
+    return length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - radius;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = min(in.radius, min(in.half_size.x, in.half_size.y));
+    // Pixel coverage of the shape, from its signed distance
+    let coverage = clamp(0.5 - rounded_box(in.local, in.half_size, radius), 0.0, 1.0);
+    // Clamped so the margin doesn't sample outside the region (neighbours in an atlas)
+    let uv = in.uv_rect.xy + clamp(in.uv_corner, vec2<f32>(0.0), vec2<f32>(1.0)) * in.uv_rect.zw;
+    let color = textureSample(sprite_texture, sprite_sampler, uv) * in.color;
+    return vec4<f32>(color.rgb, color.a * coverage);
+}
@@ -0,0 +1,148 @@
+use eframe::wgpu;
+
+use crate::{
+    capture::read_texture,
+    context::{Context, ContextError},
+    resources::TrackedResource,
+};
+
+// A 2D texture compute shaders write into, then sampled or copied like any other: image
+// processing or a simulation on a grid. Bind it with BindGroupBuilder::storage_texture, and with
+// BindGroupBuilder::texture to read it in a later pass. Two of them ping-ponged let a shader read
+// the last step and write the next one with only write access.
+pub struct StorageTexture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    format: wgpu::TextureFormat,
+    _tracked: TrackedResource,
+}
+
+impl StorageTexture {
+    // Rgba8Unorm, Rgba16Float and R32Float can be storage textures everywhere compute shaders
+    // run, other formats depend on the adapter and the features enabled
+    pub fn new(
+        context: &Context,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Self, ContextError> {
+        Self::with_label(context, width, height, format, "Storage Texture")
+    }
+
+    // label names the texture for graphics debuggers
+    pub fn with_label(
+        context: &Context,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+        label: &str,
+    ) -> Result<Self, ContextError> {
+        if !context
+            .downlevel_flags()
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        {
+            return Err(ContextError::DownlevelNotSupported(
+                wgpu::DownlevelFlags::COMPUTE_SHADERS,
+            ));
+        }
+        if !context
+            .texture_format_features(format)
+            .allowed_usages
+            .contains(wgpu::TextureUsages::STORAGE_BINDING)
+        {
+            return Err(ContextError::UnsupportedFormat(
+                format,
+                String::from("it can't be a storage texture on this adapter"),
+            ));
+        }
+        let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(&format!("{label} View")),
+            ..Default::default()
+        });
+        Ok(Self {
+            _tracked: context.resources().track_texture(&texture, label),
+            texture,
+            view,
+            format,
+        })
+    }
+
+    // Write access works with any storage format. Read and read-write access need the
+    // TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES feature and a format the adapter can read back,
+    // R32Float, R32Uint and R32Sint on most of them.
+    pub fn check_access(
+        &self,
+        context: &Context,
+        access: wgpu::StorageTextureAccess,
+    ) -> Result<(), ContextError> {
+        if access == wgpu::StorageTextureAccess::WriteOnly {
+            return Ok(());
+        }
+        let feature = wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+        if !context.device().features().contains(feature) {
+            return Err(ContextError::FeatureNotEnabled(feature));
+        }
+        if !context
+            .texture_format_features(self.format)
+            .flags
+            .contains(wgpu::TextureFormatFeatureFlags::STORAGE_READ_WRITE)
+        {
+            return Err(ContextError::UnsupportedFormat(
+                self.format,
+                String::from("storage textures of it can't be read on this adapter"),
+            ));
+        }
+        Ok(())
+    }
+
+    // Waits for the GPU, only for Rgba8Unorm textures
+    pub fn read_rgba(&self, context: &Context) -> Result<image::RgbaImage, ContextError> {
+        if self.format != wgpu::TextureFormat::Rgba8Unorm {
+            return Err(ContextError::UnsupportedFormat(
+                self.format,
+                String::from("only Rgba8Unorm storage textures can be read as RGBA"),
+            ));
+        }
+        let pixels = read_texture(context.device(), context.queue(), &self.texture)?;
+        image::RgbaImage::from_raw(self.width(), self.height(), pixels).ok_or_else(|| {
+            ContextError::InvalidSize(String::from("storage texture readback has the wrong size"))
+        })
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.height()
+    }
+}
@@ -0,0 +1,74 @@
+use eframe::egui;
+
+use crate::context::Context;
+
+struct QueuedText {
+    text: String,
+    position: egui::Pos2,
+    size: f32,
+    color: egui::Color32,
+}
+
+// Screen-space text queued during the frame and painted by flush from App::render_gui. It goes
+// through egui's painter on a foreground layer, so it is drawn after the scene, in the surface
+// format, and follows resizes and scale factor changes.
+#[derive(Default)]
+pub struct TextRenderer {
+    queue: Vec<QueuedText>,
+}
+
+impl TextRenderer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Position of the top left corner and font size in physical pixels, color in sRGB
+    pub fn draw_text(
+        &mut self,
+        text: &str,
+        x: f32,
+        y: f32,
+        size: f32,
+        color: egui::Color32,
+    ) -> &mut Self {
+        self.queue.push(QueuedText {
+            text: text.to_string(),
+            position: egui::pos2(x, y),
+            size,
+            color,
+        });
+        self
+    }
+
+    // The FPS and frame time in the top left corner
+    pub fn draw_fps(&mut self, context: &Context, color: egui::Color32) -> &mut Self {
+        let fps_counter = context.fps_counter();
+        let mut text = format!(
+            "{:.0} FPS ({:.2} ms)",
+            fps_counter.fps(),
+            fps_counter.avg_frame_time_ms()
+        );
+        if let Some(gpu_time) = fps_counter.gpu_frame_time_ms() {
+            text.push_str(&format!("\nGPU {gpu_time:.2} ms"));
+        }
+        self.draw_text(&text, 8.0, 8.0, 16.0, color)
+    }
+
+    // Paints and clears the queue
+    pub fn flush(&mut self, egui_ctx: &egui::Context) {
+        let painter = egui_ctx.layer_painter(egui::LayerId::new(
+            egui::Order::Foreground,
+            egui::Id::new("wgpu_bootstrap_text"),
+        ));
+        let pixels_per_point = egui_ctx.pixels_per_point();
+        for text in self.queue.drain(..) {
+            painter.text(
+                (text.position.to_vec2() / pixels_per_point).to_pos2(),
+                egui::Align2::LEFT_TOP,
+                text.text,
+                egui::FontId::proportional(text.size / pixels_per_point),
+                text.color,
+            );
+        }
+    }
+}
@@ -0,0 +1,499 @@
+use std::path::Path;
+
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    resources::TrackedResource,
+    util::sampler::SamplerBuilder,
+};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+pub struct Texture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    layer_count: u32,
+    _tracked: TrackedResource,
+}
+
+impl Texture {
+    pub fn from_bytes(context: &Context, bytes: &[u8], label: &str) -> Result<Self, ContextError> {
+        let image = image::load_from_memory(bytes).map_err(ContextError::Image)?;
+        Self::from_image(context, &image, label, false)
+    }
+
+    pub fn from_path(context: &Context, path: impl AsRef<Path>) -> Result<Self, ContextError> {
+        let path = path.as_ref();
+        let image = image::open(path).map_err(ContextError::Image)?;
+        Self::from_image(context, &image, &path.display().to_string(), false)
+    }
+
+    pub fn from_bytes_with_mips(
+        context: &Context,
+        bytes: &[u8],
+        label: &str,
+    ) -> Result<Self, ContextError> {
+        let image = image::load_from_memory(bytes).map_err(ContextError::Image)?;
+        Self::from_image(context, &image, label, true)
+    }
+
+    pub fn from_path_with_mips(
+        context: &Context,
+        path: impl AsRef<Path>,
+    ) -> Result<Self, ContextError> {
+        let path = path.as_ref();
+        let image = image::open(path).map_err(ContextError::Image)?;
+        Self::from_image(context, &image, &path.display().to_string(), true)
+    }
+
+    // Tightly packed RGBA8 rows, sRGB like the decoded images
+    pub fn from_rgba(
+        context: &Context,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        label: &str,
+    ) -> Result<Self, ContextError> {
+        let rgba = image::RgbaImage::from_raw(width, height, data.to_vec()).ok_or_else(|| {
+            ContextError::InvalidSize(format!(
+                "{} bytes for a {width}x{height} RGBA texture",
+                data.len()
+            ))
+        })?;
+        Self::from_image(
+            context,
+            &image::DynamicImage::ImageRgba8(rgba),
+            label,
+            false,
+        )
+    }
+
+    pub(crate) fn from_image(
+        context: &Context,
+        image: &image::DynamicImage,
+        label: &str,
+        generate_mipmaps: bool,
+    ) -> Result<Self, ContextError> {
+        let rgba = image.to_rgba8();
+        let (width, height) = rgba.dimensions();
+        let size = wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        };
+        // A 1x1 image gets a single level either way
+        let mip_level_count = if generate_mipmaps {
+            size.max_mips(wgpu::TextureDimension::D2)
+        } else {
+            1
+        };
+        let mut usage = wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST;
+        if mip_level_count > 1 {
+            usage |= wgpu::TextureUsages::RENDER_ATTACHMENT;
+        }
+
+        let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage,
+            view_formats: &[],
+        });
+
+        let tracked = context.resources().track_texture(&texture, label);
+        write_layer(context, &texture, &rgba, 0);
+
+        if mip_level_count > 1 {
+            generate_mipmaps_for(context, &texture, mip_level_count)?;
+        }
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(label),
+            ..Default::default()
+        });
+        let sampler = SamplerBuilder::linear_clamp().label(label).build(context)?;
+
+        Ok(Self {
+            texture,
+            view,
+            sampler,
+            layer_count: 1,
+            _tracked: tracked,
+        })
+    }
+
+    // A D2Array texture with one layer per encoded image, in order, for a texture_2d_array in
+    // WGSL. The images must all have the same size. No mipmaps.
+    pub fn from_layers(
+        context: &Context,
+        layers: &[&[u8]],
+        label: &str,
+    ) -> Result<Self, ContextError> {
+        let images = layers
+            .iter()
+            .map(|bytes| image::load_from_memory(bytes).map_err(ContextError::Image))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::from_images(context, &images, label, wgpu::TextureViewDimension::D2Array)
+    }
+
+    // Tightly packed RGBA8 rows for each layer, see from_layers
+    pub fn from_rgba_layers(
+        context: &Context,
+        width: u32,
+        height: u32,
+        layers: &[&[u8]],
+        label: &str,
+    ) -> Result<Self, ContextError> {
+        let images = rgba_images(width, height, layers)?;
+        Self::from_images(context, &images, label, wgpu::TextureViewDimension::D2Array)
+    }
+
+    // The six faces of a cubemap in the +X, -X, +Y, -Y, +Z, -Z order of its layers, for a Skybox
+    // or a texture_cube in WGSL. Faces are square and all the same size.
+    pub fn cubemap_from_paths(
+        context: &Context,
+        paths: [impl AsRef<Path>; 6],
+    ) -> Result<Self, ContextError> {
+        let images = paths
+            .iter()
+            .map(|path| image::open(path.as_ref()).map_err(ContextError::Image))
+            .collect::<Result<Vec<_>, _>>()?;
+        let label = paths[0].as_ref().display().to_string();
+        Self::from_images(context, &images, &label, wgpu::TextureViewDimension::Cube)
+    }
+
+    // Tightly packed size x size RGBA8 rows for each face, see cubemap_from_paths
+    pub fn cubemap_from_rgba(
+        context: &Context,
+        size: u32,
+        faces: [&[u8]; 6],
+        label: &str,
+    ) -> Result<Self, ContextError> {
+        let images = rgba_images(size, size, &faces)?;
+        Self::from_images(context, &images, label, wgpu::TextureViewDimension::Cube)
+    }
+
+    // One layer per image, viewed as a D2Array or a Cube
+    fn from_images(
+        context: &Context,
+        images: &[image::DynamicImage],
+        label: &str,
+        view_dimension: wgpu::TextureViewDimension,
+    ) -> Result<Self, ContextError> {
+        let Some(first) = images.first() else {
+            return Err(ContextError::InvalidSize(format!(
+                "no layers for the array texture {label}"
+            )));
+        };
+        let (width, height) = (first.width(), first.height());
+        if view_dimension == wgpu::TextureViewDimension::Cube && width != height {
+            return Err(ContextError::InvalidSize(format!(
+                "the faces of the cubemap {label} are {width}x{height}, they must be square"
+            )));
+        }
+        for (layer, image) in images.iter().enumerate() {
+            if (image.width(), image.height()) != (width, height) {
+                return Err(ContextError::InvalidSize(format!(
+                    "layer {layer} of {label} is {}x{}, layer 0 is {width}x{height}",
+                    image.width(),
+                    image.height()
+                )));
+            }
+        }
+        let layer_count = images.len() as u32;
+        let max_layers = context.limits().max_texture_array_layers;
+        if layer_count > max_layers {
+            return Err(ContextError::OutOfRange(format!(
+                "{layer_count} layers for {label}, the device supports {max_layers}"
+            )));
+        }
+
+        let texture = context.device().create_texture(&wgpu::TextureDescriptor {
+            label: Some(label),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: layer_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let tracked = context.resources().track_texture(&texture, label);
+        for (layer, image) in images.iter().enumerate() {
+            write_layer(context, &texture, &image.to_rgba8(), layer as u32);
+        }
+
+        // Explicit, the default view would be D2 for a single layer and D2Array for six
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some(label),
+            dimension: Some(view_dimension),
+            ..Default::default()
+        });
+        let sampler = SamplerBuilder::linear_clamp().label(label).build(context)?;
+
+        Ok(Self {
+            texture,
+            view,
+            sampler,
+            layer_count,
+            _tracked: tracked,
+        })
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    // 1 for plain textures, 6 for cubemaps, the layer indices of the shader stop below it
+    pub fn layer_count(&self) -> u32 {
+        self.layer_count
+    }
+
+    // Texture at binding 0 and its sampler at binding 1
+    pub fn desc() -> wgpu::BindGroupLayoutDescriptor<'static> {
+        wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Texture Bind Group Layout"),
+        }
+    }
+
+    // desc for the textures of from_layers, a texture_2d_array at binding 0
+    pub fn desc_array() -> wgpu::BindGroupLayoutDescriptor<'static> {
+        wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Texture Array Bind Group Layout"),
+        }
+    }
+
+    // desc for cubemaps, a texture_cube at binding 0
+    pub fn desc_cube() -> wgpu::BindGroupLayoutDescriptor<'static> {
+        wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::Cube,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+            label: Some("Cubemap Bind Group Layout"),
+        }
+    }
+}
+
+fn rgba_images(
+    width: u32,
+    height: u32,
+    layers: &[&[u8]],
+) -> Result<Vec<image::DynamicImage>, ContextError> {
+    layers
+        .iter()
+        .enumerate()
+        .map(|(layer, data)| {
+            image::RgbaImage::from_raw(width, height, data.to_vec())
+                .map(image::DynamicImage::ImageRgba8)
+                .ok_or_else(|| {
+                    ContextError::InvalidSize(format!(
+                        "{} bytes in layer {layer} of a {width}x{height} RGBA texture",
+                        data.len()
+                    ))
+                })
+        })
+        .collect()
+}
+
+// Uploads mip level 0 of one layer. Rows are padded to the same 256 bytes alignment buffer
+// copies need.
+fn write_layer(context: &Context, texture: &wgpu::Texture, rgba: &image::RgbaImage, layer: u32) {
+    let (width, height) = rgba.dimensions();
+    let unpadded_bytes_per_row = 4 * width as usize;
+    let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as usize;
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(alignment) * alignment;
+    let mut data = vec![0; padded_bytes_per_row * height as usize];
+    for (src, dst) in rgba
+        .chunks_exact(unpadded_bytes_per_row)
+        .zip(data.chunks_exact_mut(padded_bytes_per_row))
+    {
+        dst[..unpadded_bytes_per_row].copy_from_slice(src);
+    }
+
+    context.queue().write_texture(
+        wgpu::ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: 0,
+                y: 0,
+                z: layer,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        &data,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(padded_bytes_per_row as u32),
+            rows_per_image: Some(height),
+        },
+        wgpu::Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+// Renders each level from the previous one with linear filtering, which also handles odd sizes
+// since every level is at least 1 texel wide.
+fn generate_mipmaps_for(
+    context: &Context,
+    texture: &wgpu::Texture,
+    mip_level_count: u32,
+) -> Result<(), ContextError> {
+    let device = context.device();
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Mipmap Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("mipmap.wgsl").into()),
+    });
+    let bind_group_layout = device.create_bind_group_layout(&Texture::desc());
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Mipmap Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Mipmap Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(FORMAT.into())],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+    let sampler = SamplerBuilder::linear_clamp()
+        .label("Mipmap Sampler")
+        .build(context)?;
+
+    let views: Vec<wgpu::TextureView> = (0..mip_level_count)
+        .map(|mip| {
+            texture.create_view(&wgpu::TextureViewDescriptor {
+                label: Some("Mipmap View"),
+                base_mip_level: mip,
+                mip_level_count: Some(1),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Mipmap Encoder"),
+    });
+    for target in 1..mip_level_count as usize {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&views[target - 1]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+            label: Some("Mipmap Bind Group"),
+        });
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Mipmap Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &views[target],
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+    context.queue().submit(std::iter::once(encoder.finish()));
+    Ok(())
+}
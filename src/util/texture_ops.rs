@@ -0,0 +1,351 @@
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::{pipeline::ComputePipelineBuilder, sampler::SamplerBuilder},
+};
+
+// Matches @workgroup_size in texture_ops.wgsl
+const WORKGROUP_SIZE: u32 = 8;
+
+// Fills every mip level after the first from the previous one with a compute shader, for
+// textures created with STORAGE_BINDING and TEXTURE_BINDING that can't be render attachments.
+// Each layer of an array texture gets its own chain. Only float formats that can be storage
+// textures work (rgba8unorm, rgba16float, r32float...), not the sRGB ones: create those with an
+// Rgba8Unorm view format and mip their render attachment instead.
+pub fn generate_mipmaps_compute(
+    context: &Context,
+    texture: &wgpu::Texture,
+) -> Result<(), ContextError> {
+    if !context
+        .downlevel_flags()
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+    {
+        return Err(ContextError::DownlevelNotSupported(
+            wgpu::DownlevelFlags::COMPUTE_SHADERS,
+        ));
+    }
+    // Without texture views GL reads zeros from a level while another one is a storage texture
+    if context.adapter_info().backend == wgpu::Backend::Gl {
+        return Err(ContextError::BackendNotSupported(
+            wgpu::Backend::Gl,
+            String::from("compute mipmaps"),
+        ));
+    }
+    let format = texture.format();
+    let Some(storage_format) = storage_format_name(format) else {
+        return Err(ContextError::UnsupportedFormat(
+            format,
+            String::from("compute mipmaps need a float or normalized storage format"),
+        ));
+    };
+    if !context
+        .texture_format_features(format)
+        .allowed_usages
+        .contains(wgpu::TextureUsages::STORAGE_BINDING)
+    {
+        return Err(ContextError::UnsupportedFormat(
+            format,
+            String::from("it can't be a storage texture on this device"),
+        ));
+    }
+    let usage = wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING;
+    if !texture.usage().contains(usage) {
+        return Err(ContextError::InvalidTexture(String::from(
+            "compute mipmaps need a texture with STORAGE_BINDING and TEXTURE_BINDING usages",
+        )));
+    }
+    if texture.dimension() != wgpu::TextureDimension::D2 || texture.sample_count() > 1 {
+        return Err(ContextError::InvalidTexture(String::from(
+            "compute mipmaps need a 2D texture that isn't multisampled",
+        )));
+    }
+    if texture.mip_level_count() < 2 {
+        return Ok(());
+    }
+
+    let device = context.device();
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Compute Mipmap Shader"),
+        source: wgpu::ShaderSource::Wgsl(
+            include_str!("texture_ops.wgsl")
+                .replace("STORAGE_FORMAT", storage_format)
+                .into(),
+        ),
+    });
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Compute Mipmap Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Texture {
+                    // Read with textureLoad, which works for unfilterable formats too
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::StorageTexture {
+                    access: wgpu::StorageTextureAccess::WriteOnly,
+                    format,
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                },
+                count: None,
+            },
+        ],
+    });
+    let pipeline = ComputePipelineBuilder::new(&shader)
+        .label("Compute Mipmap Pipeline")
+        .bind_group_layout(&bind_group_layout)
+        .build(context);
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Compute Mipmap Encoder"),
+    });
+    for layer in 0..texture.depth_or_array_layers() {
+        let views: Vec<wgpu::TextureView> = (0..texture.mip_level_count())
+            .map(|mip| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Compute Mipmap View"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_mip_level: mip,
+                    mip_level_count: Some(1),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        for target in 1..views.len() {
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Compute Mipmap Bind Group"),
+                layout: &bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&views[target - 1]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&views[target]),
+                    },
+                ],
+            });
+            let size = texture
+                .size()
+                .mip_level_size(target as u32, wgpu::TextureDimension::D2);
+            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Compute Mipmap Pass"),
+                timestamp_writes: None,
+            });
+            compute_pass.set_pipeline(&pipeline);
+            compute_pass.set_bind_group(0, &bind_group, &[]);
+            compute_pass.dispatch_workgroups(
+                size.width.div_ceil(WORKGROUP_SIZE),
+                size.height.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+    }
+    context.queue().submit(std::iter::once(encoder.finish()));
+    Ok(())
+}
+
+// Draws the first level and layer of src over all of dst with a full-screen triangle, scaled
+// with linear filtering (nearest for unfilterable formats such as r32float). The formats can
+// differ as long as both are float or normalized: sRGB is decoded on read and encoded on write,
+// channels missing from dst are dropped and those missing from src read as 0 (alpha as 1).
+//
+// Takes textures rather than views since a view doesn't tell its format, src needs
+// TEXTURE_BINDING usage and dst RENDER_ATTACHMENT.
+pub fn blit(
+    context: &Context,
+    src: &wgpu::Texture,
+    dst: &wgpu::Texture,
+) -> Result<(), ContextError> {
+    let device = context.device();
+    let features = device.features();
+    let filterable = match src.format().sample_type(None, Some(features)) {
+        Some(wgpu::TextureSampleType::Float { filterable }) => filterable,
+        _ => {
+            return Err(ContextError::UnsupportedFormat(
+                src.format(),
+                String::from("blit only reads float and normalized formats"),
+            ))
+        }
+    };
+    let dst_format = dst.format();
+    let renderable = dst_format
+        .guaranteed_format_features(features)
+        .allowed_usages
+        .contains(wgpu::TextureUsages::RENDER_ATTACHMENT);
+    if !renderable
+        || !matches!(
+            dst_format.sample_type(None, Some(features)),
+            Some(wgpu::TextureSampleType::Float { .. })
+        )
+    {
+        return Err(ContextError::UnsupportedFormat(
+            dst_format,
+            String::from("blit only writes float and normalized color formats"),
+        ));
+    }
+    if !src.usage().contains(wgpu::TextureUsages::TEXTURE_BINDING)
+        || !dst.usage().contains(wgpu::TextureUsages::RENDER_ATTACHMENT)
+    {
+        return Err(ContextError::InvalidTexture(String::from(
+            "blit needs a source with TEXTURE_BINDING usage and a destination with RENDER_ATTACHMENT",
+        )));
+    }
+    if src.sample_count() > 1 || dst.sample_count() > 1 {
+        return Err(ContextError::InvalidTexture(String::from(
+            "can't blit multisampled textures, resolve them first",
+        )));
+    }
+    // GL can't view a single layer of an array texture and would draw nothing
+    if context.adapter_info().backend == wgpu::Backend::Gl && src.depth_or_array_layers() > 1 {
+        return Err(ContextError::BackendNotSupported(
+            wgpu::Backend::Gl,
+            String::from("blit from an array texture"),
+        ));
+    }
+
+    // The full-screen triangle of the mipmap generator does the same copy
+    let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Blit Shader"),
+        source: wgpu::ShaderSource::Wgsl(include_str!("mipmap.wgsl").into()),
+    });
+    let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Blit Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(if filterable {
+                    wgpu::SamplerBindingType::Filtering
+                } else {
+                    wgpu::SamplerBindingType::NonFiltering
+                }),
+                count: None,
+            },
+        ],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("Blit Pipeline Layout"),
+        bind_group_layouts: &[&bind_group_layout],
+        push_constant_ranges: &[],
+    });
+    let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some("Blit Pipeline"),
+        layout: Some(&pipeline_layout),
+        vertex: wgpu::VertexState {
+            module: &shader,
+            entry_point: "vs_main",
+            buffers: &[],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: &shader,
+            entry_point: "fs_main",
+            targets: &[Some(dst_format.into())],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState::default(),
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState::default(),
+        multiview: None,
+        cache: None,
+    });
+    let sampler = if filterable {
+        SamplerBuilder::linear_clamp()
+    } else {
+        SamplerBuilder::nearest_clamp()
+    }
+    .label("Blit Sampler")
+    .build(context)?;
+
+    let src_view = src.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("Blit Source View"),
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        mip_level_count: Some(1),
+        array_layer_count: Some(1),
+        ..Default::default()
+    });
+    let dst_view = dst.create_view(&wgpu::TextureViewDescriptor {
+        label: Some("Blit Destination View"),
+        dimension: Some(wgpu::TextureViewDimension::D2),
+        mip_level_count: Some(1),
+        array_layer_count: Some(1),
+        ..Default::default()
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Blit Bind Group"),
+        layout: &bind_group_layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&src_view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(&sampler),
+            },
+        ],
+    });
+
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("Blit Encoder"),
+    });
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Blit Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &dst_view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        render_pass.set_pipeline(&pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+    context.queue().submit(std::iter::once(encoder.finish()));
+    Ok(())
+}
+
+// WGSL names of the float storage formats, the ones a box filter can average
+fn storage_format_name(format: wgpu::TextureFormat) -> Option<&'static str> {
+    use wgpu::TextureFormat as T;
+    let name = match format {
+        T::Rgba8Unorm => "rgba8unorm",
+        T::Rgba8Snorm => "rgba8snorm",
+        T::Bgra8Unorm => "bgra8unorm",
+        T::Rgba16Float => "rgba16float",
+        T::R32Float => "r32float",
+        T::Rg32Float => "rg32float",
+        T::Rgba32Float => "rgba32float",
+        _ => return None,
+    };
+    Some(name)
+}
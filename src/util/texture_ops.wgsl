@@ -0,0 +1,25 @@
+// STORAGE_FORMAT is replaced by the texture's format before compiling
+
+@group(0) @binding(0)
+var source: texture_2d<f32>;
+@group(0) @binding(1)
+var destination: texture_storage_2d<STORAGE_FORMAT, write>;
+
+// Each texel of the level is the average of 2x2 texels of the previous one, the last row or
+// column of an odd sized level is left out
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = textureDimensions(destination);
+    if id.x >= size.x || id.y >= size.y {
+        return;
+    }
+    let last = vec2<i32>(textureDimensions(source)) - 1;
+    let base = vec2<i32>(id.xy) * 2;
+    var sum = vec4<f32>(0.0);
+    for (var y = 0; y < 2; y++) {
+        for (var x = 0; x < 2; x++) {
+            sum += textureLoad(source, min(base + vec2<i32>(x, y), last), 0);
+        }
+    }
+    textureStore(destination, vec2<i32>(id.xy), sum * 0.25);
+}
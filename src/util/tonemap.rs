@@ -0,0 +1,104 @@
+use eframe::wgpu;
+
+use crate::{
+    context::{Context, ContextError},
+    util::post_process::PostProcess,
+};
+
+// What Runner::set_hdr renders the app into
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TonemapOperator {
+    Reinhard,
+    Aces,
+}
+
+// Matches Params in tonemap.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TonemapParams {
+    exposure: f32,
+    curve: u32,
+    encode_srgb: u32,
+    _padding: u32,
+}
+
+// Maps a linear HDR texture (HDR_FORMAT) to the [0, 1] range of the context color format. The
+// result is gamma encoded in the shader when that format isn't sRGB.
+pub struct Tonemap {
+    post_process: PostProcess,
+    params: TonemapParams,
+}
+
+impl Tonemap {
+    // ACES with an exposure of 1
+    pub fn new(context: &Context) -> Result<Self, ContextError> {
+        let params = TonemapParams {
+            exposure: 1.0,
+            curve: operator_index(TonemapOperator::Aces),
+            encode_srgb: u32::from(!context.format().is_srgb()),
+            _padding: 0,
+        };
+        let post_process = PostProcess::with_params(context, include_str!("tonemap.wgsl"), params)?;
+        Ok(Self {
+            post_process,
+            params,
+        })
+    }
+
+    pub fn exposure(&self) -> f32 {
+        self.params.exposure
+    }
+
+    // Scales the linear color before the curve, 2 is one stop brighter
+    pub fn set_exposure(&mut self, queue: &wgpu::Queue, exposure: f32) -> &mut Self {
+        self.params.exposure = exposure;
+        self.post_process.set_params(queue, self.params);
+        self
+    }
+
+    pub fn operator(&self) -> TonemapOperator {
+        match self.params.curve {
+            0 => TonemapOperator::Reinhard,
+            _ => TonemapOperator::Aces,
+        }
+    }
+
+    pub fn set_operator(&mut self, queue: &wgpu::Queue, operator: TonemapOperator) -> &mut Self {
+        self.params.curve = operator_index(operator);
+        self.post_process.set_params(queue, self.params);
+        self
+    }
+
+    pub fn set_sampler(&mut self, sampler: wgpu::Sampler) -> &mut Self {
+        self.post_process.set_sampler(sampler);
+        self
+    }
+
+    // Same as PostProcess::bind_group, input is the HDR texture
+    pub fn bind_group(&self, context: &Context, input: &wgpu::TextureView) -> wgpu::BindGroup {
+        self.post_process.bind_group(context, input)
+    }
+
+    pub fn apply(
+        &self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        input: &wgpu::TextureView,
+        output: &wgpu::TextureView,
+    ) {
+        self.post_process.apply(context, encoder, input, output);
+    }
+
+    pub fn draw(&self, render_pass: &mut wgpu::RenderPass<'_>, bind_group: &wgpu::BindGroup) {
+        self.post_process.draw(render_pass, bind_group);
+    }
+}
+
+fn operator_index(operator: TonemapOperator) -> u32 {
+    match operator {
+        TonemapOperator::Reinhard => 0,
+        TonemapOperator::Aces => 1,
+    }
+}
@@ -0,0 +1,61 @@
+use std::marker::PhantomData;
+
+use eframe::wgpu::{self, util::DeviceExt};
+
+use crate::{
+    context::{Context, ContextError},
+    resources::TrackedResource,
+};
+
+// WGSL rounds uniform structs up to 16 bytes, a smaller Rust type would leave the end unset
+const UNIFORM_ALIGNMENT: usize = 16;
+
+// For a T to be bound as a WGSL uniform struct, also used by PostProcess::with_params
+pub(crate) fn check_uniform_size<T>() -> Result<(), ContextError> {
+    let size = std::mem::size_of::<T>();
+    if size == 0 || size % UNIFORM_ALIGNMENT != 0 {
+        return Err(ContextError::UniformSize(size));
+    }
+    Ok(())
+}
+
+pub struct UniformBuffer<T: bytemuck::Pod> {
+    buffer: wgpu::Buffer,
+    phantom: PhantomData<T>,
+    _tracked: TrackedResource,
+}
+
+impl<T: bytemuck::Pod> UniformBuffer<T> {
+    pub fn new(context: &Context, initial: T) -> Result<Self, ContextError> {
+        Self::with_label(context, initial, "Uniform Buffer")
+    }
+
+    // label names the buffer for graphics debuggers
+    pub fn with_label(context: &Context, initial: T, label: &str) -> Result<Self, ContextError> {
+        check_uniform_size::<T>()?;
+        let buffer = context
+            .device()
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::bytes_of(&initial),
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            });
+        Ok(Self {
+            _tracked: context.resources().track_buffer(&buffer, label),
+            buffer,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn update(&self, queue: &wgpu::Queue, value: T) {
+        queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(&value));
+    }
+
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    pub fn binding_resource(&self) -> wgpu::BindingResource<'_> {
+        self.buffer.as_entire_binding()
+    }
+}
@@ -0,0 +1,22 @@
+// Renders the examples headlessly and compares them with the images in tests/golden. Run with
+// WGPU_BOOTSTRAP_UPDATE_GOLDEN=1 after a change meant to alter them.
+
+#[path = "../examples/triangle/triangle_app.rs"]
+mod triangle_app;
+
+use triangle_app::TriangleApp;
+use wgpu_bootstrap::{testing, ContextError};
+
+#[test]
+fn triangle() {
+    let image = match testing::render_to_image(TriangleApp::new, 64, 64) {
+        Ok(image) => image,
+        // Machines without any GPU or software adapter can't render it
+        Err(ContextError::NoAdapter) => {
+            eprintln!("no adapter, triangle golden test skipped");
+            return;
+        }
+        Err(err) => panic!("{err}"),
+    };
+    testing::assert_image_matches(&image, "tests/golden/triangle.png", 2);
+}
@@ -0,0 +1,231 @@
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Type};
+
+// Generates `fn desc() -> wgpu::VertexBufferLayout<'static>` for a #[repr(C)] struct. Each field
+// is an attribute, at the location given by #[vertex(location = N)] or the one after the previous
+// field. The format comes from the field type unless set with #[vertex(format = Unorm8x4)]. A
+// matrix [[f32; M]; N] takes N consecutive locations, one Float32xM per column, like
+// instance_buffer::TRANSFORM_ATTRIBUTES. Add #[vertex(instance)] on the struct for per-instance
+// data.
+#[proc_macro_derive(Vertex, attributes(vertex))]
+pub fn derive_vertex(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+
+    if !has_repr_c(&input) {
+        return Err(syn::Error::new(
+            name.span(),
+            "Vertex needs #[repr(C)] for the field offsets to match the layout",
+        ));
+    }
+
+    // The attributes are a const, which can't depend on the struct's parameters
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "Vertex can't be derived for a generic struct",
+        ));
+    }
+
+    let mut instance = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("vertex"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("instance") {
+                instance = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `instance`"))
+            }
+        })?;
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new(
+                    name.span(),
+                    "Vertex needs a struct with named fields",
+                ))
+            }
+        },
+        _ => return Err(syn::Error::new(name.span(), "Vertex needs a struct")),
+    };
+
+    let mut attributes = Vec::new();
+    let mut next_location = 0u32;
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let mut location = next_location;
+        let mut format = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("vertex"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    location = meta.value()?.parse::<syn::LitInt>()?.base10_parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("format") {
+                    format = Some(meta.value()?.parse::<syn::Ident>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `location` or `format`"))
+                }
+            })?;
+        }
+        let offset = quote!(::std::mem::offset_of!(#name, #ident));
+        match format {
+            Some(format) => {
+                attributes.push(attribute(&offset, location, &format));
+                next_location = location + 1;
+            }
+            None => match matrix_column(&field.ty) {
+                Some((column, count)) => {
+                    let format = syn::Ident::new(infer_format(column)?, Span::call_site());
+                    for i in 0..count {
+                        let offset =
+                            quote!(#offset + #i as usize * ::std::mem::size_of::<#column>());
+                        attributes.push(attribute(&offset, location + i, &format));
+                    }
+                    next_location = location + count;
+                }
+                None => {
+                    let format = syn::Ident::new(infer_format(&field.ty)?, Span::call_site());
+                    attributes.push(attribute(&offset, location, &format));
+                    next_location = location + 1;
+                }
+            },
+        }
+    }
+
+    let step_mode = if instance {
+        quote!(::wgpu_bootstrap::wgpu::VertexStepMode::Instance)
+    } else {
+        quote!(::wgpu_bootstrap::wgpu::VertexStepMode::Vertex)
+    };
+
+    Ok(quote! {
+        impl #name {
+            pub fn desc() -> ::wgpu_bootstrap::wgpu::VertexBufferLayout<'static> {
+                const ATTRIBUTES: &[::wgpu_bootstrap::wgpu::VertexAttribute] = &[#(#attributes),*];
+                ::wgpu_bootstrap::wgpu::VertexBufferLayout {
+                    array_stride: ::std::mem::size_of::<#name>() as ::wgpu_bootstrap::wgpu::BufferAddress,
+                    step_mode: #step_mode,
+                    attributes: ATTRIBUTES,
+                }
+            }
+        }
+
+        // Vertex buffers are filled with bytemuck, fail here rather than at the upload
+        const _: fn() = || {
+            fn assert_pod<T: ::wgpu_bootstrap::bytemuck::Pod>() {}
+            assert_pod::<#name>();
+        };
+    })
+}
+
+fn attribute(offset: &TokenStream, location: u32, format: &syn::Ident) -> TokenStream {
+    quote! {
+        ::wgpu_bootstrap::wgpu::VertexAttribute {
+            offset: (#offset) as ::wgpu_bootstrap::wgpu::BufferAddress,
+            shader_location: #location,
+            format: ::wgpu_bootstrap::wgpu::VertexFormat::#format,
+        }
+    }
+}
+
+// The column type and column count of [[T; M]; N], None for any other type
+fn matrix_column(ty: &Type) -> Option<(&Type, u32)> {
+    let Type::Array(outer) = ty else {
+        return None;
+    };
+    if !matches!(&*outer.elem, Type::Array(_)) {
+        return None;
+    }
+    match &outer.len {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(len),
+            ..
+        }) => Some((&outer.elem, len.base10_parse().ok()?)),
+        _ => None,
+    }
+}
+
+fn has_repr_c(input: &DeriveInput) -> bool {
+    input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+        .any(|attr| {
+            let mut repr_c = false;
+            let _ = attr.parse_nested_meta(|meta| {
+                repr_c |= meta.path.is_ident("C");
+                Ok(())
+            });
+            repr_c
+        })
+}
+
+fn infer_format(ty: &Type) -> syn::Result<&'static str> {
+    let unsupported = || {
+        syn::Error::new(
+            ty.span(),
+            "can't infer the vertex format of this type, set it with #[vertex(format = ...)]",
+        )
+    };
+    let (scalar, count) = match ty {
+        Type::Path(path) => (path.path.get_ident().ok_or_else(unsupported)?, 1),
+        Type::Array(array) => {
+            let scalar = match &*array.elem {
+                Type::Path(path) => path.path.get_ident().ok_or_else(unsupported)?,
+                _ => return Err(unsupported()),
+            };
+            let count = match &array.len {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Int(len),
+                    ..
+                }) => len.base10_parse::<usize>()?,
+                _ => return Err(unsupported()),
+            };
+            (scalar, count)
+        }
+        _ => return Err(unsupported()),
+    };
+    let format = match (scalar.to_string().as_str(), count) {
+        ("f32", 1) => "Float32",
+        ("f32", 2) => "Float32x2",
+        ("f32", 3) => "Float32x3",
+        ("f32", 4) => "Float32x4",
+        ("u32", 1) => "Uint32",
+        ("u32", 2) => "Uint32x2",
+        ("u32", 3) => "Uint32x3",
+        ("u32", 4) => "Uint32x4",
+        ("i32", 1) => "Sint32",
+        ("i32", 2) => "Sint32x2",
+        ("i32", 3) => "Sint32x3",
+        ("i32", 4) => "Sint32x4",
+        ("u8", 2) => "Uint8x2",
+        ("u8", 4) => "Uint8x4",
+        ("i8", 2) => "Sint8x2",
+        ("i8", 4) => "Sint8x4",
+        ("u16", 2) => "Uint16x2",
+        ("u16", 4) => "Uint16x4",
+        ("i16", 2) => "Sint16x2",
+        ("i16", 4) => "Sint16x4",
+        _ => return Err(unsupported()),
+    };
+    Ok(format)
+}
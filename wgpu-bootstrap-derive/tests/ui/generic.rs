@@ -0,0 +1,7 @@
+#[repr(C)]
+#[derive(Copy, Clone, wgpu_bootstrap::Vertex)]
+struct Vertex<T> {
+    position: [T; 3],
+}
+
+fn main() {}
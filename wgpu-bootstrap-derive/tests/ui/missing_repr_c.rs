@@ -0,0 +1,6 @@
+#[derive(Copy, Clone, wgpu_bootstrap::Vertex)]
+struct Vertex {
+    position: [f32; 3],
+}
+
+fn main() {}
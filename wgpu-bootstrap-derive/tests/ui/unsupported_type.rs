@@ -0,0 +1,7 @@
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, wgpu_bootstrap::Vertex)]
+struct Vertex {
+    position: [f64; 3],
+}
+
+fn main() {}
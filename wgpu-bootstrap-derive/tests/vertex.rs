@@ -0,0 +1,67 @@
+use wgpu_bootstrap::{util::instance_buffer::TRANSFORM_ATTRIBUTES, wgpu, Vertex};
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+struct Mixed {
+    position: [f32; 3],
+    uv: [f32; 2],
+    #[vertex(format = Unorm8x4)]
+    color: [u8; 4],
+    index: u32,
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Vertex)]
+#[vertex(instance)]
+struct Instance {
+    #[vertex(location = 5)]
+    model: [[f32; 4]; 4],
+    // Numbering goes on from the last column
+    tint: [f32; 4],
+    #[vertex(location = 2)]
+    layer: i32,
+}
+
+#[test]
+fn offsets_and_formats() {
+    let layout = Mixed::desc();
+    assert_eq!(layout.array_stride, 28);
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Vertex);
+    assert_eq!(
+        layout.attributes,
+        &wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x2,
+            2 => Unorm8x4,
+            3 => Uint32,
+        ]
+    );
+}
+
+#[test]
+fn matrix_columns_and_location_restarts() {
+    let layout = Instance::desc();
+    assert_eq!(layout.array_stride, 84);
+    assert_eq!(layout.step_mode, wgpu::VertexStepMode::Instance);
+    assert_eq!(&layout.attributes[..4], &TRANSFORM_ATTRIBUTES);
+    assert_eq!(
+        &layout.attributes[4..],
+        &[
+            wgpu::VertexAttribute {
+                offset: 64,
+                shader_location: 9,
+                format: wgpu::VertexFormat::Float32x4,
+            },
+            wgpu::VertexAttribute {
+                offset: 80,
+                shader_location: 2,
+                format: wgpu::VertexFormat::Sint32,
+            },
+        ]
+    );
+}
+
+#[test]
+fn compile_errors() {
+    trybuild::TestCases::new().compile_fail("tests/ui/*.rs");
+}